@@ -0,0 +1,405 @@
+//! This module implements the aggregation of several independent
+//! [`RecursiveSNARK`]s into a single compressed proof
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  check_in_subgroup,
+  errors::NovaError,
+  gadgets::scalar_as_base,
+  io_hash,
+  nifs::NIFS,
+  r1cs::{R1CSInstance, RelaxedR1CSInstance},
+  traits::{
+    commitment::CommitmentTrait, snark::RelaxedR1CSSNARKTrait, CurveCycleEquipped, Dual, Engine,
+  },
+  Commitment, ProverKey, PublicParams, RecursiveSNARK, VerifierKey,
+};
+
+/// The public part of one of the chains folded into an [`AggregateSNARK`]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+struct AggregateChain<E1>
+where E1: CurveCycleEquipped {
+  r_U_primary:    RelaxedR1CSInstance<E1>,
+  r_U_secondary:  RelaxedR1CSInstance<Dual<E1>>,
+  l_u_secondary:  R1CSInstance<Dual<E1>>,
+  nifs_secondary: NIFS<Dual<E1>>,
+
+  zn_primary:   Vec<E1::Scalar>,
+  zn_secondary: Vec<<Dual<E1> as Engine>::Scalar>,
+}
+
+/// A SNARK that proves the knowledge of several valid [`RecursiveSNARK`]s at
+/// once.
+///
+/// The chains are independent, i.e. they may differ in their initial inputs
+/// and number of steps, but must all have been produced with the same
+/// [`PublicParams`]: folding instances is only sound between instances of
+/// the same R1CS shape, so chains of different step circuits cannot be
+/// aggregated unless they are expressed as one circuit, e.g. with
+/// [`supernova`](crate::supernova).
+///
+/// As in [`CompressedSNARK`](crate::CompressedSNARK), the last secondary
+/// instance of each chain is folded into its running instance. The running
+/// instances of all chains are then folded together on each side with
+/// [`NIFS::prove_relaxed`], and only the two final instances are proven with
+/// `S1` and `S2`, so the proof grows with the number of chains by their
+/// public instances alone.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct AggregateSNARK<E1, S1, S2>
+where
+  E1: CurveCycleEquipped,
+  S1: RelaxedR1CSSNARKTrait<E1>,
+  S2: RelaxedR1CSSNARKTrait<Dual<E1>>, {
+  chains: Vec<AggregateChain<E1>>,
+
+  nifs_primary:   Vec<NIFS<E1>>,
+  nifs_secondary: Vec<NIFS<Dual<E1>>>,
+
+  snark_primary:   S1,
+  snark_secondary: S2,
+
+  pp_digest: E1::Scalar,
+}
+
+impl<E1, S1, S2> AggregateSNARK<E1, S1, S2>
+where
+  E1: CurveCycleEquipped,
+  S1: RelaxedR1CSSNARKTrait<E1>,
+  S2: RelaxedR1CSSNARKTrait<Dual<E1>>,
+{
+  /// Aggregate `recursive_snarks`, all produced with `pp`, into a single
+  /// proof, using the keys of
+  /// [`CompressedSNARK::setup`](crate::CompressedSNARK::setup).
+  ///
+  /// Each input is first verified against its own number of steps and
+  /// initial inputs, and the first failure is returned, so that an invalid
+  /// chain is rejected here rather than producing an unverifiable proof.
+  pub fn aggregate(
+    pp: &PublicParams<E1>,
+    pk: &ProverKey<E1, S1, S2>,
+    recursive_snarks: &[RecursiveSNARK<E1>],
+  ) -> Result<Self, NovaError> {
+    if recursive_snarks.is_empty() {
+      return Err(NovaError::InvalidInputLength);
+    }
+    for recursive_snark in recursive_snarks {
+      recursive_snark.verify(
+        pp,
+        recursive_snark.num_steps(),
+        &recursive_snark.z0_primary,
+        &recursive_snark.z0_secondary,
+      )?;
+    }
+
+    let pp_digest = pp.digest();
+    let shape_primary = &pp.circuit_shape_primary.r1cs_shape;
+    let shape_secondary = &pp.circuit_shape_secondary.r1cs_shape;
+
+    // fold the last secondary instance of each chain into its running instance
+    let mut chains = Vec::with_capacity(recursive_snarks.len());
+    let mut folded_secondary = Vec::with_capacity(recursive_snarks.len());
+    for recursive_snark in recursive_snarks {
+      let (nifs_secondary, f_secondary, _) = NIFS::prove(
+        &*pp.ck_secondary,
+        &pp.ro_consts_secondary,
+        &scalar_as_base::<E1>(pp_digest),
+        shape_secondary,
+        &recursive_snark.r_U_secondary,
+        &recursive_snark.r_W_secondary,
+        &recursive_snark.l_u_secondary,
+        &recursive_snark.l_w_secondary,
+      )?;
+      folded_secondary.push(f_secondary);
+      chains.push(AggregateChain {
+        r_U_primary: recursive_snark.r_U_primary.clone(),
+        r_U_secondary: recursive_snark.r_U_secondary.clone(),
+        l_u_secondary: recursive_snark.l_u_secondary.clone(),
+        nifs_secondary,
+        zn_primary: recursive_snark.zi_primary.clone(),
+        zn_secondary: recursive_snark.zi_secondary.clone(),
+      });
+    }
+
+    // fold the running instances of all chains together on each side
+    let mut nifs_primary = Vec::with_capacity(recursive_snarks.len() - 1);
+    let (mut U_primary, mut W_primary) =
+      (recursive_snarks[0].r_U_primary.clone(), recursive_snarks[0].r_W_primary.clone());
+    for recursive_snark in &recursive_snarks[1..] {
+      let (nifs, (U, W)) = NIFS::prove_relaxed(
+        &*pp.ck_primary,
+        &pp_digest,
+        shape_primary,
+        &U_primary,
+        &W_primary,
+        &recursive_snark.r_U_primary,
+        &recursive_snark.r_W_primary,
+      )?;
+      nifs_primary.push(nifs);
+      (U_primary, W_primary) = (U, W);
+    }
+
+    let mut nifs_secondary = Vec::with_capacity(recursive_snarks.len() - 1);
+    let mut folded_secondary = folded_secondary.into_iter();
+    let (mut U_secondary, mut W_secondary) =
+      folded_secondary.next().expect("at least one chain is aggregated");
+    for (U2, W2) in folded_secondary {
+      let (nifs, (U, W)) = NIFS::prove_relaxed(
+        &*pp.ck_secondary,
+        &scalar_as_base::<E1>(pp_digest),
+        shape_secondary,
+        &U_secondary,
+        &W_secondary,
+        &U2,
+        &W2,
+      )?;
+      nifs_secondary.push(nifs);
+      (U_secondary, W_secondary) = (U, W);
+    }
+
+    // create SNARKs proving the knowledge of the two aggregated witnesses
+    let (snark_primary, snark_secondary) = rayon::join(
+      || S1::prove(&pp.ck_primary, &pk.pk_primary, shape_primary, &U_primary, &W_primary),
+      || S2::prove(&pp.ck_secondary, &pk.pk_secondary, shape_secondary, &U_secondary, &W_secondary),
+    );
+
+    Ok(Self {
+      chains,
+      nifs_primary,
+      nifs_secondary,
+      snark_primary: snark_primary?,
+      snark_secondary: snark_secondary?,
+      pp_digest,
+    })
+  }
+
+  /// The number of chains aggregated in the proof
+  pub fn num_chains(&self) -> usize { self.chains.len() }
+
+  /// Verify the `AggregateSNARK` against one claim per aggregated chain, in
+  /// the order they were aggregated. Each claim holds the number of steps and
+  /// the initial inputs of its chain.
+  ///
+  /// On success, returns the primary and secondary outputs of each chain.
+  pub fn verify(
+    &self,
+    vk: &VerifierKey<E1, S1, S2>,
+    claims: &[(usize, Vec<E1::Scalar>, Vec<<Dual<E1> as Engine>::Scalar>)],
+  ) -> Result<Vec<(Vec<E1::Scalar>, Vec<<Dual<E1> as Engine>::Scalar>)>, NovaError> {
+    // the proof must have been produced with the parameters behind `vk`, and
+    // hold one chain per claim
+    if self.pp_digest != vk.pp_digest
+      || claims.is_empty()
+      || claims.len() != self.chains.len()
+      || self.nifs_primary.len() != self.chains.len() - 1
+      || self.nifs_secondary.len() != self.chains.len() - 1
+    {
+      return Err(NovaError::ProofVerifyError);
+    }
+
+    // check that the commitments of every chain and the cross-terms of every
+    // fold lie in the prime-order subgroup
+    let decompress = |nifs: &NIFS<Dual<E1>>| Commitment::<Dual<E1>>::decompress(&nifs.comm_T);
+    let mut comms_primary = Vec::with_capacity(3 * self.chains.len());
+    let mut comms_secondary = Vec::with_capacity(5 * self.chains.len());
+    for chain in &self.chains {
+      comms_primary.extend([chain.r_U_primary.comm_W, chain.r_U_primary.comm_E]);
+      comms_secondary.extend([
+        chain.r_U_secondary.comm_W,
+        chain.r_U_secondary.comm_E,
+        chain.l_u_secondary.comm_W,
+        decompress(&chain.nifs_secondary)?,
+      ]);
+    }
+    for nifs in &self.nifs_primary {
+      comms_primary.push(Commitment::<E1>::decompress(&nifs.comm_T)?);
+    }
+    for nifs in &self.nifs_secondary {
+      comms_secondary.push(decompress(nifs)?);
+    }
+    check_in_subgroup::<E1>(&comms_primary)?;
+    check_in_subgroup::<Dual<E1>>(&comms_secondary)?;
+
+    let folded_secondary = self
+      .chains
+      .iter()
+      .zip(claims)
+      .map(|(chain, (num_steps, z0_primary, z0_secondary))| {
+        chain.verify_io(vk, *num_steps, z0_primary, z0_secondary)
+      })
+      .collect::<Result<Vec<_>, NovaError>>()?;
+
+    // fold the running instances of all chains together on each side
+    let U_primary = self
+      .nifs_primary
+      .iter()
+      .zip(&self.chains[1..])
+      .try_fold(self.chains[0].r_U_primary.clone(), |U, (nifs, chain)| {
+        nifs.verify_relaxed(&vk.pp_digest, &U, &chain.r_U_primary)
+      })?;
+    let U_secondary = self
+      .nifs_secondary
+      .iter()
+      .zip(&folded_secondary[1..])
+      .try_fold(folded_secondary[0].clone(), |U, (nifs, U2)| {
+        nifs.verify_relaxed(&scalar_as_base::<E1>(vk.pp_digest), &U, U2)
+      })?;
+
+    // check the satisfiability of the aggregated instances
+    let (res_primary, res_secondary) = rayon::join(
+      || self.snark_primary.verify(&vk.vk_primary, &U_primary),
+      || self.snark_secondary.verify(&vk.vk_secondary, &U_secondary),
+    );
+
+    res_primary?;
+    res_secondary?;
+
+    Ok(
+      self
+        .chains
+        .iter()
+        .map(|chain| (chain.zn_primary.clone(), chain.zn_secondary.clone()))
+        .collect(),
+    )
+  }
+}
+
+impl<E1> AggregateChain<E1>
+where E1: CurveCycleEquipped
+{
+  /// Checks that the output hashes of the chain's instances point to its
+  /// running instances, as in [`CompressedSNARK::verify`], and returns the
+  /// fold of its last secondary instance into its running one
+  ///
+  /// [`CompressedSNARK::verify`]: crate::CompressedSNARK::verify
+  fn verify_io<S1, S2>(
+    &self,
+    vk: &VerifierKey<E1, S1, S2>,
+    num_steps: usize,
+    z0_primary: &[E1::Scalar],
+    z0_secondary: &[<Dual<E1> as Engine>::Scalar],
+  ) -> Result<RelaxedR1CSInstance<Dual<E1>>, NovaError>
+  where
+    S1: RelaxedR1CSSNARKTrait<E1>,
+    S2: RelaxedR1CSSNARKTrait<Dual<E1>>,
+  {
+    // the number of steps cannot be zero, and the (relaxed) R1CS instances
+    // must have two public outputs
+    if num_steps == 0
+      || self.l_u_secondary.X.len() != 2
+      || self.r_U_primary.X.len() != 2
+      || self.r_U_secondary.X.len() != 2
+    {
+      return Err(NovaError::ProofVerifyError);
+    }
+
+    let hash_primary = io_hash(
+      vk.pp_digest,
+      num_steps,
+      z0_primary,
+      &self.zn_primary,
+      &self.r_U_secondary,
+      &vk.ro_consts_secondary,
+      vk.F_arity_primary,
+    )?;
+    let hash_secondary = io_hash(
+      scalar_as_base::<E1>(vk.pp_digest),
+      num_steps,
+      z0_secondary,
+      &self.zn_secondary,
+      &self.r_U_primary,
+      &vk.ro_consts_primary,
+      vk.F_arity_secondary,
+    )?;
+
+    if hash_primary != self.l_u_secondary.X[0]
+      || hash_secondary != scalar_as_base::<Dual<E1>>(self.l_u_secondary.X[1])
+    {
+      return Err(NovaError::ProofVerifyError);
+    }
+
+    self.nifs_secondary.verify(
+      &vk.ro_consts_secondary,
+      &scalar_as_base::<E1>(vk.pp_digest),
+      &self.r_U_secondary,
+      &self.l_u_secondary,
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    provider::{ipa_pc::EvaluationEngine, Bn256EngineIPA, GrumpkinEngine},
+    spartan::snark::RelaxedR1CSSNARK,
+    testutil::{CounterCircuit, TrivialCircuit},
+    CompressedSNARK,
+  };
+
+  type S<E> = RelaxedR1CSSNARK<E, EvaluationEngine<E>>;
+
+  fn test_aggregate_with<E1, S1, S2>()
+  where
+    E1: CurveCycleEquipped,
+    S1: RelaxedR1CSSNARKTrait<E1>,
+    S2: RelaxedR1CSSNARKTrait<Dual<E1>>, {
+    let c_primary = CounterCircuit::new(E1::Scalar::from(2u64));
+    let c_secondary = TrivialCircuit::default();
+    let pp =
+      PublicParams::<E1>::setup(&c_primary, &c_secondary, &*S1::ck_floor(), &*S2::ck_floor())
+        .unwrap();
+    let (pk, vk) = CompressedSNARK::<E1, S1, S2>::setup(&pp).unwrap();
+
+    // three chains with different inputs and lengths
+    let mut claims = Vec::new();
+    let mut recursive_snarks = (1..=3u64)
+      .map(|i| {
+        let num_steps = i as usize + 1;
+        let z0_primary = vec![E1::Scalar::from(i)];
+        let z0_secondary = vec![<Dual<E1> as Engine>::Scalar::from(i)];
+        let mut recursive_snark =
+          RecursiveSNARK::new(&pp, &c_primary, &c_secondary, &z0_primary, &z0_secondary).unwrap();
+        for _ in 0..num_steps {
+          recursive_snark.prove_step(&pp, &c_primary, &c_secondary).unwrap();
+        }
+        claims.push((num_steps, z0_primary, z0_secondary));
+        recursive_snark
+      })
+      .collect::<Vec<_>>();
+
+    let snark = AggregateSNARK::<E1, S1, S2>::aggregate(&pp, &pk, &recursive_snarks).unwrap();
+    assert_eq!(snark.num_chains(), 3);
+    let outputs = snark.verify(&vk, &claims).unwrap();
+    for (i, (zn_primary, zn_secondary)) in outputs.iter().enumerate() {
+      let (num_steps, z0_primary, z0_secondary) = &claims[i];
+      assert_eq!(zn_primary, &vec![z0_primary[0] + E1::Scalar::from(2 * *num_steps as u64)]);
+      assert_eq!(zn_secondary, z0_secondary);
+    }
+
+    // a claim on a chain other than the one aggregated is rejected
+    let mut wrong_claims = claims.clone();
+    wrong_claims.swap(0, 1);
+    assert_eq!(snark.verify(&vk, &wrong_claims), Err(NovaError::ProofVerifyError));
+    assert_eq!(snark.verify(&vk, &claims[..2]), Err(NovaError::ProofVerifyError));
+
+    // so is a tampered output of one chain
+    let mut tampered = snark.clone();
+    tampered.chains[2].zn_primary[0] += E1::Scalar::from(1u64);
+    assert_eq!(tampered.verify(&vk, &claims), Err(NovaError::ProofVerifyError));
+
+    // an invalid chain is rejected when aggregating
+    recursive_snarks[1].zi_primary[0] += E1::Scalar::from(1u64);
+    assert!(AggregateSNARK::<E1, S1, S2>::aggregate(&pp, &pk, &recursive_snarks).is_err());
+    assert_eq!(
+      AggregateSNARK::<E1, S1, S2>::aggregate(&pp, &pk, &[]).unwrap_err(),
+      NovaError::InvalidInputLength
+    );
+  }
+
+  #[test]
+  fn test_aggregate() {
+    test_aggregate_with::<Bn256EngineIPA, S<Bn256EngineIPA>, S<GrumpkinEngine>>();
+  }
+}
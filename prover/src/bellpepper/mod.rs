@@ -59,4 +59,79 @@ mod tests {
 
   #[test]
   fn test_alloc_bit() { test_alloc_bit_with::<Bn256EngineKZG>(); }
+
+  fn test_circuit_sizes_with<E: Engine>() {
+    let mut cs: ShapeCS<E> = ShapeCS::new();
+    synthesize_alloc_bit(&mut cs);
+
+    // two allocations, each inputized, and one boolean check per bit
+    assert_eq!((cs.num_cons(), cs.num_vars(), cs.num_io()), (2, 2, 2));
+
+    let shape = cs.r1cs_shape();
+    assert_eq!(
+      (shape.num_cons(), shape.num_vars(), shape.num_io()),
+      (cs.num_cons(), cs.num_vars(), cs.num_io())
+    );
+  }
+
+  #[test]
+  fn test_circuit_sizes() { test_circuit_sizes_with::<Bn256EngineKZG>(); }
+
+  #[cfg(feature = "debug-symbols")]
+  fn synthesize_cube<Fr: PrimeField, CS: ConstraintSystem<Fr>>(cs: &mut CS) {
+    // x^3 + x + 5 = y, with y public
+    let x = AllocatedNum::alloc_infallible(cs.namespace(|| "x"), || Fr::from(3));
+    let x_sq = x.square(cs.namespace(|| "x_sq")).unwrap();
+    let x_cu = x_sq.mul(cs.namespace(|| "x_cu"), &x).unwrap();
+    let y = AllocatedNum::alloc_infallible(cs.namespace(|| "y"), || {
+      x_cu.get_value().unwrap() + x.get_value().unwrap() + Fr::from(5)
+    });
+    cs.enforce(
+      || "y = x^3 + x + 5",
+      |lc| lc + x_cu.get_variable() + x.get_variable() + (Fr::from(5), CS::one()),
+      |lc| lc + CS::one(),
+      |lc| lc + y.get_variable(),
+    );
+    let _ = y.inputize(cs.namespace(|| "y is input"));
+  }
+
+  #[cfg(feature = "debug-symbols")]
+  fn test_is_sat_symbolic_with<E: Engine>() {
+    use crate::{
+      bellpepper::test_shape_cs::TestShapeCS,
+      errors::NovaError,
+      r1cs::{commitment_key, R1CSInstance, R1CSWitness},
+    };
+
+    let mut cs: TestShapeCS<E> = TestShapeCS::new();
+    synthesize_cube(&mut cs);
+    let shape = cs.r1cs_shape();
+    let ck = commitment_key(&shape, &*default_ck_hint());
+    let names = cs.variable_names();
+
+    let mut cs = SatisfyingAssignment::<E>::new();
+    synthesize_cube(&mut cs);
+    let (input_assignment, mut aux_assignment) = cs.to_assignments();
+    let X = input_assignment[1..].to_vec();
+    let prove = |aux: Vec<E::Scalar>| {
+      let W = R1CSWitness::new(&shape, aux).unwrap();
+      let U = R1CSInstance::new(&shape, W.commit(&ck), X.clone()).unwrap();
+      shape.is_sat_symbolic(&ck, &U, &W, &names)
+    };
+    prove(aux_assignment.clone()).unwrap();
+
+    // corrupting x^3 breaks the constraint that computes it
+    let x_cu = names.iter().position(|name| name.starts_with("x_cu")).unwrap();
+    aux_assignment[x_cu] += E::Scalar::from(1);
+    match prove(aux_assignment) {
+      Err(err @ NovaError::UnSatSymbolic { .. }) => {
+        assert!(err.to_string().contains(&names[x_cu]), "{err}")
+      },
+      res => panic!("expected a symbolic unsat error, got {res:?}"),
+    }
+  }
+
+  #[cfg(feature = "debug-symbols")]
+  #[test]
+  fn test_is_sat_symbolic() { test_is_sat_symbolic_with::<Bn256EngineKZG>(); }
 }
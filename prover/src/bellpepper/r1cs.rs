@@ -51,7 +51,7 @@ impl<E: Engine> NovaWitness<E> for SatisfyingAssignment<E> {
     let W = R1CSWitness::<E>::new(shape, aux_assignment)?;
     let X = input_assignment[1..].to_owned();
 
-    let comm_W = W.commit(ck);
+    let comm_W = W.try_commit(ck)?;
 
     let instance = R1CSInstance::<E>::new(shape, comm_W, X)?;
 
@@ -27,6 +27,24 @@ impl<E: Engine> ShapeCS<E> {
 
   /// Returns the number of aux inputs defined for this `ShapeCS`.
   pub fn num_aux(&self) -> usize { self.aux }
+
+  /// Returns the number of constraints of the [`R1CSShape`] this `ShapeCS`
+  /// produces.
+  ///
+  /// [`R1CSShape`]: crate::r1cs::R1CSShape
+  pub fn num_cons(&self) -> usize { self.num_constraints() }
+
+  /// Returns the number of witness variables of the [`R1CSShape`] this
+  /// `ShapeCS` produces.
+  ///
+  /// [`R1CSShape`]: crate::r1cs::R1CSShape
+  pub fn num_vars(&self) -> usize { self.aux }
+
+  /// Returns the number of public inputs/outputs of the [`R1CSShape`] this
+  /// `ShapeCS` produces, which does not count the constant `ONE` input.
+  ///
+  /// [`R1CSShape`]: crate::r1cs::R1CSShape
+  pub fn num_io(&self) -> usize { self.inputs - 1 }
 }
 
 impl<E: Engine> Default for ShapeCS<E> {
@@ -103,6 +103,14 @@ where E::Scalar: PrimeField
   /// Returns the number of aux inputs defined for this `TestShapeCS`.
   pub fn num_aux(&self) -> usize { self.aux.len() }
 
+  /// Returns the names of the variables in the order of the `z = (W, 1, X)`
+  /// vector of the shape built from this `TestShapeCS`, i.e. the aux inputs,
+  /// then `ONE`, then the public inputs.
+  #[cfg(feature = "debug-symbols")]
+  pub fn variable_names(&self) -> Vec<String> {
+    self.aux.iter().chain(&self.inputs).cloned().collect()
+  }
+
   /// Print all public inputs, aux inputs, and constraint names.
   #[allow(dead_code)]
   pub fn pretty_print_list(&self) -> Vec<String> {
@@ -17,10 +17,11 @@ use ark_ff::Field;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-  constants::{NIO_NOVA_FOLD, NUM_FE_WITHOUT_IO_FOR_CRHF, NUM_HASH_BITS},
+  constants::{BN_N_LIMBS, NIO_NOVA_FOLD, NUM_FE_WITHOUT_IO_FOR_CRHF, NUM_HASH_BITS},
+  errors::NovaError,
   gadgets::{
-    alloc_num_equals, alloc_scalar_as_base, alloc_zero, le_bits_to_num, AllocatedPoint,
-    AllocatedR1CSInstance, AllocatedRelaxedR1CSInstance,
+    absorb_relaxed_instance, alloc_num_equals, alloc_scalar_as_base, alloc_zero, le_bits_to_num,
+    AllocatedPoint, AllocatedR1CSInstance, AllocatedRelaxedR1CSInstance,
   },
   r1cs::{R1CSInstance, RelaxedR1CSInstance},
   supernova::StepCircuit,
@@ -69,6 +70,27 @@ impl<E: Engine> NovaAugmentedCircuitInputs<E> {
   ) -> Self {
     Self { params, i, z0, zi, U, u, T }
   }
+
+  /// Returns the names and lengths, in field elements, of the values the
+  /// augmented circuit hashes into each of its two public outputs, in the
+  /// order they are absorbed. The offset of a value is the sum of the lengths
+  /// before it. An external verifier recomputes an output by absorbing
+  /// these in order, each coordinate and limb as an element of `E::Base`.
+  pub fn layout(&self) -> Vec<(&'static str, usize)> {
+    let arity = self.z0.len();
+    vec![
+      ("pp_digest", 1),
+      ("i", 1),
+      ("z0", arity),
+      ("zi", arity),
+      // x, y and is_infinity of each commitment
+      ("U.comm_W", 3),
+      ("U.comm_E", 3),
+      ("U.u", 1),
+      // each entry of X decomposed into limbs
+      ("U.X", NIO_NOVA_FOLD * BN_N_LIMBS),
+    ]
+  }
 }
 
 /// The augmented circuit F' in Nova that includes a step circuit F
@@ -209,7 +231,7 @@ impl<'a, E: Engine, SC: StepCircuit<E::Base>> NovaAugmentedCircuit<'a, E, SC> {
     for e in z_i {
       ro.absorb(e);
     }
-    U.absorb_in_ro(cs.namespace(|| "absorb U"), &mut ro)?;
+    absorb_relaxed_instance(&mut ro, cs.namespace(|| "absorb U"), &U)?;
 
     let hash_bits = ro.squeeze(cs.namespace(|| "Input hash"), NUM_HASH_BITS)?;
     let hash = le_bits_to_num(cs.namespace(|| "bits to hash"), &hash_bits)?;
@@ -236,10 +258,13 @@ impl<'a, E: Engine, SC: StepCircuit<E::Base>> NovaAugmentedCircuit<'a, E, SC> {
 
 impl<E: Engine, SC: StepCircuit<E::Base>> NovaAugmentedCircuit<'_, E, SC> {
   /// synthesize circuit giving constraint system
+  ///
+  /// Fails with [`NovaError::StepArityMismatch`] if the step circuit returns
+  /// a number of outputs other than its arity.
   pub fn synthesize<CS: ConstraintSystem<<E as Engine>::Base>>(
     self,
     cs: &mut CS,
-  ) -> Result<Vec<AllocatedNum<E::Base>>, SynthesisError> {
+  ) -> Result<Vec<AllocatedNum<E::Base>>, NovaError> {
     let arity = self.step_circuit.arity();
 
     // Allocate all witnesses
@@ -310,7 +335,7 @@ impl<E: Engine, SC: StepCircuit<E::Base>> NovaAugmentedCircuit<'_, E, SC> {
     let (_pc, z_next) = self.step_circuit.synthesize(&mut cs.namespace(|| "F"), None, &z_input)?;
 
     if z_next.len() != arity {
-      return Err(SynthesisError::IncompatibleLengthVector("z_next".to_string()));
+      return Err(NovaError::StepArityMismatch { expected: arity, got: z_next.len() });
     }
 
     // Compute the new hash H(params, Unew, i+1, z0, z_{i+1})
@@ -323,7 +348,7 @@ impl<E: Engine, SC: StepCircuit<E::Base>> NovaAugmentedCircuit<'_, E, SC> {
     for e in &z_next {
       ro.absorb(e);
     }
-    Unew.absorb_in_ro(cs.namespace(|| "absorb U_new"), &mut ro)?;
+    absorb_relaxed_instance(&mut ro, cs.namespace(|| "absorb U_new"), &Unew)?;
     let hash_bits = ro.squeeze(cs.namespace(|| "output hash bits"), NUM_HASH_BITS)?;
     let hash = le_bits_to_num(cs.namespace(|| "convert hash to num"), &hash_bits)?;
 
@@ -336,6 +361,129 @@ impl<E: Engine, SC: StepCircuit<E::Base>> NovaAugmentedCircuit<'_, E, SC> {
   }
 }
 
+#[cfg(test)]
+mod tests {
+  use ff::{Field, PrimeField};
+  use neptune::sponge::api::SpongeOp;
+
+  use super::*;
+  use crate::{
+    gadgets::scalar_as_base,
+    provider::{poseidon::PoseidonRO, Bn256EngineIPA, Bn256EngineKZG},
+    testutil::{fold_n, TrivialCircuit},
+    traits::{
+      snark::default_ck_hint, AbsorbInROTrait, CurveCycleEquipped, Dual, ROConstants, ROTrait,
+      StepIndex,
+    },
+    PublicParams,
+  };
+
+  // The number of elements absorbed so far by an oracle that infers its count
+  fn num_absorbed<Base: PrimeField, Scalar: PrimeField>(ro: &PoseidonRO<Base, Scalar>) -> usize {
+    match ro.io_pattern().0[..] {
+      [SpongeOp::Absorb(n), SpongeOp::Squeeze(1)] => n as usize,
+      _ => unreachable!(),
+    }
+  }
+
+  // Recompute an output hash of the augmented circuit, checking how many
+  // elements each value absorbs against the end offsets given by `layout`, with
+  // the entries of the running instance grouped under `U`
+  fn hash_by_layout<E>(
+    ro_consts: &ROConstants<E>,
+    pp_digest: E::Base,
+    num_steps: usize,
+    z0: &[E::Base],
+    zi: &[E::Base],
+    U: &RelaxedR1CSInstance<E>,
+  ) -> E::Scalar
+  where
+    E: Engine<RO = PoseidonRO<<E as Engine>::Base, <E as Engine>::Scalar>>,
+  {
+    let inputs = NovaAugmentedCircuitInputs::<E>::new(
+      E::Scalar::ZERO,
+      E::Base::ZERO,
+      z0.to_vec(),
+      None,
+      None,
+      None,
+      None,
+    );
+    let mut expected: Vec<(&str, usize)> = vec![];
+    let mut offset = 0;
+    for (name, len) in inputs.layout() {
+      offset += len;
+      let group = name.split('.').next().unwrap();
+      match expected.last_mut() {
+        Some((last, end)) if *last == group => *end = offset,
+        _ => expected.push((group, offset)),
+      }
+    }
+
+    let mut ro: E::RO = PoseidonRO::new_inferred(ro_consts.clone(), None);
+    let mut absorbed = vec![];
+    ro.absorb(pp_digest);
+    absorbed.push(("pp_digest", num_absorbed(&ro)));
+    AbsorbInROTrait::<E>::absorb_in_ro(&StepIndex(num_steps as u64), &mut ro);
+    absorbed.push(("i", num_absorbed(&ro)));
+    z0.iter().for_each(|e| ro.absorb(*e));
+    absorbed.push(("z0", num_absorbed(&ro)));
+    zi.iter().for_each(|e| ro.absorb(*e));
+    absorbed.push(("zi", num_absorbed(&ro)));
+    U.absorb_in_ro(&mut ro);
+    absorbed.push(("U", num_absorbed(&ro)));
+    assert_eq!(absorbed, expected);
+
+    ro.squeeze(NUM_HASH_BITS).unwrap()
+  }
+
+  fn test_layout_with<E1>()
+  where
+    E1: CurveCycleEquipped,
+    E1: Engine<RO = PoseidonRO<<E1 as Engine>::Base, <E1 as Engine>::Scalar>>,
+    Dual<E1>: Engine<RO = PoseidonRO<<Dual<E1> as Engine>::Base, <Dual<E1> as Engine>::Scalar>>, {
+    // the two sides take different arities, so that each hash is checked
+    // against its own layout
+    for (arity_primary, arity_secondary) in [(1, 1), (3, 2)] {
+      let pp = PublicParams::<E1>::setup(
+        &TrivialCircuit::new(arity_primary),
+        &TrivialCircuit::new(arity_secondary),
+        &*default_ck_hint(),
+        &*default_ck_hint(),
+      )
+      .unwrap();
+      let snark = fold_n(&pp, 3).unwrap();
+
+      // the hashes the augmented circuits synthesized and output are the ones
+      // recomputed over the layouts
+      let hash_primary = hash_by_layout::<Dual<E1>>(
+        &pp.ro_consts_secondary,
+        pp.digest(),
+        snark.i,
+        &snark.z0_primary,
+        &snark.zi_primary,
+        &snark.r_U_secondary,
+      );
+      assert_eq!(hash_primary, snark.l_u_secondary.X[0]);
+      let hash_secondary = hash_by_layout::<E1>(
+        &pp.ro_consts_primary,
+        scalar_as_base::<E1>(pp.digest()),
+        snark.i,
+        &snark.z0_secondary,
+        &snark.zi_secondary,
+        &snark.r_U_primary,
+      );
+      assert_eq!(hash_secondary, scalar_as_base::<Dual<E1>>(snark.l_u_secondary.X[1]));
+    }
+  }
+
+  #[test]
+  fn test_layout() {
+    test_layout_with::<Bn256EngineKZG>();
+    test_layout_with::<Bn256EngineIPA>();
+  }
+}
+
 // #[cfg(test)]
 // mod tests {
 //     use expect_test::{expect, Expect};
@@ -12,3 +12,8 @@ pub(crate) const NIO_CYCLE_FOLD: usize = 4; // 1 per point (3) + scalar
 
 /// Bit size of Nova field element hashes
 pub const NUM_HASH_BITS: usize = 250;
+
+/// Domain prefix of the hash-to-curve map behind [`Group::hash_to_group`]
+///
+/// [`Group::hash_to_group`]: crate::traits::Group::hash_to_group
+pub const HASH_TO_CURVE_DOMAIN: &str = "nova_hash_to_curve";
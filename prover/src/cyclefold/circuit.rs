@@ -6,11 +6,11 @@ use bellpepper_core::{
   ConstraintSystem, SynthesisError,
 };
 use ff::Field;
-use neptune::{circuit2::poseidon_hash_allocated, poseidon::PoseidonConstants};
+use neptune::poseidon::PoseidonConstants;
 
 use crate::{
   constants::NUM_CHALLENGE_BITS,
-  gadgets::{alloc_zero, le_bits_to_num, AllocatedPoint},
+  gadgets::{alloc_zero, le_bits_to_num, poseidon_hash2, AllocatedPoint},
   traits::{commitment::CommitmentTrait, Engine},
   Commitment,
 };
@@ -112,9 +112,7 @@ impl<E: Engine> CycleFoldCircuit<E> {
     CS: ConstraintSystem<E::Base>,
   {
     let (x, y, is_infinity) = point.get_coordinates();
-    let preimage = vec![x.clone(), y.clone()];
-    let val =
-      poseidon_hash_allocated(cs.namespace(|| "hash point"), preimage, &self.poseidon_constants)?;
+    let val = poseidon_hash2(cs.namespace(|| "hash point"), &self.poseidon_constants, x, y)?;
 
     let zero = alloc_zero(cs.namespace(|| "zero"));
 
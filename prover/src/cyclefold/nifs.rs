@@ -65,7 +65,7 @@ where
 
     absorb_primary_commitment::<E1, E2>(&comm_T, &mut ro);
 
-    let r = scalar_as_base::<E2>(ro.squeeze(NUM_CHALLENGE_BITS));
+    let r = scalar_as_base::<E2>(ro.squeeze(NUM_CHALLENGE_BITS)?);
 
     let U = U1.fold(U2, &comm_T, &r);
 
@@ -129,7 +129,7 @@ impl<E: Engine> CycleFoldNIFS<E> {
     comm_T.absorb_in_ro(&mut ro);
 
     // compute a challenge from the RO
-    let r = ro.squeeze(NUM_CHALLENGE_BITS);
+    let r = ro.squeeze(NUM_CHALLENGE_BITS)?;
 
     // fold the instance using `r` and `comm_T`
     let U = U1.fold(U2, &comm_T, &r);
@@ -31,7 +31,7 @@ use crate::{
   supernova::StepCircuit,
   traits::{
     commitment::CommitmentTrait, AbsorbInROTrait, CurveCycleEquipped, Dual, Engine,
-    ROConstantsCircuit, ROTrait,
+    ROConstantsCircuit, ROTrait, StepIndex,
   },
   Commitment, CommitmentKey, DigestComputer, R1CSWithArity, ROConstants, ResourceBuffer,
   SimpleDigestible,
@@ -349,7 +349,7 @@ where E1: CurveCycleEquipped
 
     let inputs_primary: AugmentedCircuitInputs<Dual<E1>, E1> = AugmentedCircuitInputs::new(
       scalar_as_base::<E1>(pp.digest()),
-      <Dual<E1> as Engine>::Base::from(self.i as u64),
+      StepIndex(self.i as u64).to_field(),
       self.z0_primary.clone(),
       Some(self.zi_primary.clone()),
       Some(data_p),
@@ -424,7 +424,7 @@ where E1: CurveCycleEquipped
         2 + 2 * pp.F_arity_primary + 2 * NUM_FE_IN_EMULATED_POINT + 3,
       );
       hasher.absorb(pp.digest());
-      hasher.absorb(E1::Scalar::from(num_steps as u64));
+      AbsorbInROTrait::<Dual<E1>>::absorb_in_ro(&StepIndex(num_steps as u64), &mut hasher);
       for e in z0_primary {
         hasher.absorb(*e);
       }
@@ -432,16 +432,16 @@ where E1: CurveCycleEquipped
         hasher.absorb(*e);
       }
       absorb_primary_relaxed_r1cs::<E1, Dual<E1>>(&self.r_U_primary, &mut hasher);
-      let hash_primary = hasher.squeeze(NUM_HASH_BITS);
+      let hash_primary = hasher.squeeze(NUM_HASH_BITS)?;
 
       let mut hasher = <Dual<E1> as Engine>::RO::new(
         pp.ro_consts_cyclefold.clone(),
         1 + 1 + 3 + 3 + 1 + NIO_CYCLE_FOLD * BN_N_LIMBS,
       );
       hasher.absorb(pp.digest());
-      hasher.absorb(E1::Scalar::from(num_steps as u64));
+      AbsorbInROTrait::<Dual<E1>>::absorb_in_ro(&StepIndex(num_steps as u64), &mut hasher);
       self.r_U_cyclefold.absorb_in_ro(&mut hasher);
-      let hash_cyclefold = hasher.squeeze(NUM_HASH_BITS);
+      let hash_cyclefold = hasher.squeeze(NUM_HASH_BITS)?;
 
       (hash_primary, hash_cyclefold)
     };
@@ -26,9 +26,30 @@ pub enum NovaError {
   #[error("UnSat")]
   UnSat,
   /// returned if the supplied witness is not a satisfying witness to a given
-  /// shape and instance, with error constraint index
+  /// shape and instance, with the checked stage and the first unsatisfied
+  /// constraint index
   #[error("UnSatIndex")]
-  UnSatIndex(usize),
+  UnSatIndex(&'static str, usize),
+  /// returned by `R1CSShape::is_sat_symbolic` with the first unsatisfied
+  /// constraint index and that constraint rendered over the variable names
+  #[error("UnSatSymbolic: constraint {row} is unsatisfied: {constraint}")]
+  UnSatSymbolic {
+    /// the index of the unsatisfied constraint
+    row:        usize,
+    /// the constraint as `(Σ a_i·z_i)(Σ b_i·z_i) - Σ c_i·z_i = d`
+    constraint: String,
+  },
+  /// returned by `RecursiveSNARK::verify` when the running instances of one of
+  /// the circuits of the cycle do not check out
+  #[error("UnSatCircuit: {check:?} check of the {which:?} circuit failed at row {row:?}")]
+  UnSatCircuit {
+    /// the circuit whose instances failed to verify
+    which: CircuitKind,
+    /// whether its public IO or its relation was found inconsistent
+    check: UnSatCheck,
+    /// the first unsatisfied constraint, when the relation check failed
+    row:   Option<usize>,
+  },
   /// returned when the supplied compressed commitment cannot be decompressed
   #[error("DecompressionError")]
   DecompressionError,
@@ -38,6 +59,22 @@ pub enum NovaError {
   /// returned if the provided commitment key is not of sufficient length
   #[error("InvalidCommitmentKeyLength")]
   InvalidCommitmentKeyLength,
+  /// returned if a vector longer than the commitment key is committed to
+  #[error("CommitmentKeyTooSmall: needed {needed}, have {have}")]
+  CommitmentKeyTooSmall {
+    /// the number of generators required to commit to the vector
+    needed: usize,
+    /// the number of generators in the commitment key
+    have:   usize,
+  },
+  /// returned if a commitment does not open to the claimed vector and
+  /// blinding factor
+  #[error("InvalidCommitmentOpening")]
+  InvalidCommitmentOpening,
+  /// returned if a commitment does not lie in the prime-order subgroup of a
+  /// curve with a cofactor
+  #[error("InvalidCommitmentSubgroup")]
+  InvalidCommitmentSubgroup,
   /// returned if the provided number of steps is zero
   #[error("InvalidNumSteps")]
   InvalidNumSteps,
@@ -55,6 +92,24 @@ pub enum NovaError {
   /// from a previously declared arity
   #[error("InvalidStepOutputLength")]
   InvalidStepOutputLength,
+  /// returned when a step circuit returns a number of outputs other than its
+  /// arity
+  #[error("StepArityMismatch: expected {expected}, got {got}")]
+  StepArityMismatch {
+    /// the arity declared by the step circuit
+    expected: usize,
+    /// the number of outputs it returned
+    got:      usize,
+  },
+  /// returned when two instances to be folded have different public IO
+  /// arities
+  #[error("ShapeMismatch: expected {expected} public IO, got {got}")]
+  ShapeMismatch {
+    /// the public IO arity of the running instance
+    expected: usize,
+    /// the public IO arity of the incoming instance
+    got:      usize,
+  },
   /// returned when the transcript engine encounters an overflow of the round
   /// number
   #[error("InternalTranscriptError")]
@@ -71,15 +126,57 @@ pub enum NovaError {
   /// return when error during synthesis
   #[error("SynthesisError: {0}")]
   SynthesisError(String),
+  /// returned when more bits are requested from an RO squeeze than the
+  /// squeezed field element holds
+  #[error("InvalidNumBits")]
+  InvalidNumBits,
+  /// returned when a Poseidon sponge is used against its IO pattern, e.g.
+  /// when an RO absorbs a number of elements other than it declared
+  #[error("SpongeError: {0}")]
+  SpongeError(String),
   /// returned when there is an error creating a digest
   #[error("DigestError")]
   DigestError,
+  /// returned when a `RecursiveSNARK` checkpoint is resumed with public
+  /// parameters other than the ones it was produced with
+  #[error("CheckpointDigestMismatch")]
+  CheckpointDigestMismatch,
+  /// returned when bytes were serialized in a format version other than the
+  /// one this library reads
+  #[error("IncompatibleVersion: found {found}, expected {expected}")]
+  IncompatibleVersion {
+    /// the version the bytes were serialized with
+    found:    u8,
+    /// the version this library reads
+    expected: u8,
+  },
+  /// returned when bytes cannot be deserialized into the requested type
+  #[error("DeserializationError: {0}")]
+  DeserializationError(String),
   /// returned when the prover cannot prove the provided statement due to
   /// completeness error
   #[error("InternalError")]
   InternalError,
 }
 
+/// One of the two circuits of a cycle of curves
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CircuitKind {
+  /// the circuit over the scalar field of the primary curve
+  Primary,
+  /// the circuit over the scalar field of the secondary curve
+  Secondary,
+}
+
+/// The verifier check an unsatisfied instance failed
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum UnSatCheck {
+  /// the public IO does not hash to the running instance and step outputs
+  IO,
+  /// the instance and witness do not satisfy the R1CS relation
+  Relation,
+}
+
 /// Errors specific to the Polynomial commitment scheme
 #[derive(Debug, Eq, PartialEq, Error)]
 pub enum PCSError {
@@ -0,0 +1,145 @@
+//! This module implements a wrapper around `AllocatedNum` that tracks
+//! compile-time constants, so that arithmetic on them costs no constraints
+use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+use ff::PrimeField;
+
+use super::utils::alloc_constant;
+
+/// A number that is either a constant known when the circuit is built or an
+/// allocated variable.
+///
+/// [`MaybeConstant::add`] and [`MaybeConstant::mul`] fold two constants
+/// without allocating or constraining anything, and fold a constant operand
+/// into the single constraint they add otherwise. A constant is only
+/// allocated when it has to enter a gadget taking an `AllocatedNum`, through
+/// [`MaybeConstant::to_allocated`].
+#[derive(Clone, Debug)]
+pub enum MaybeConstant<F: PrimeField> {
+  /// A constant, which consumes no variable
+  Constant(F),
+  /// An allocated variable
+  Allocated(AllocatedNum<F>),
+}
+
+impl<F: PrimeField> From<AllocatedNum<F>> for MaybeConstant<F> {
+  fn from(num: AllocatedNum<F>) -> Self { Self::Allocated(num) }
+}
+
+impl<F: PrimeField> MaybeConstant<F> {
+  /// Create a constant, without touching the constraint system
+  pub const fn constant(value: F) -> Self { Self::Constant(value) }
+
+  /// Returns the constant, if `self` is one
+  pub const fn as_constant(&self) -> Option<F> {
+    match self {
+      Self::Constant(value) => Some(*value),
+      Self::Allocated(_) => None,
+    }
+  }
+
+  /// Returns the value of `self`, if known
+  pub fn get_value(&self) -> Option<F> {
+    match self {
+      Self::Constant(value) => Some(*value),
+      Self::Allocated(num) => num.get_value(),
+    }
+  }
+
+  /// Returns `self` as an `AllocatedNum`, allocating and constraining it if it
+  /// is a constant
+  pub fn to_allocated<CS: ConstraintSystem<F>>(&self, cs: CS) -> AllocatedNum<F> {
+    match self {
+      Self::Constant(value) => alloc_constant(cs, *value),
+      Self::Allocated(num) => num.clone(),
+    }
+  }
+
+  /// Returns `self + other`, costing no constraint if both are constants
+  pub fn add<CS: ConstraintSystem<F>>(
+    &self,
+    mut cs: CS,
+    other: &Self,
+  ) -> Result<Self, SynthesisError> {
+    match (self, other) {
+      (Self::Constant(a), Self::Constant(b)) => Ok(Self::Constant(*a + b)),
+      (Self::Allocated(a), Self::Allocated(b)) => Ok(Self::Allocated(a.add(cs, b)?)),
+      (Self::Allocated(num), Self::Constant(c)) | (Self::Constant(c), Self::Allocated(num)) => {
+        let sum = AllocatedNum::alloc(cs.namespace(|| "sum"), || {
+          num.get_value().map(|v| v + c).ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        cs.enforce(
+          || "sum = num + c",
+          |lc| lc + num.get_variable() + (*c, CS::one()),
+          |lc| lc + CS::one(),
+          |lc| lc + sum.get_variable(),
+        );
+        Ok(Self::Allocated(sum))
+      },
+    }
+  }
+
+  /// Returns `self * other`, costing no constraint if both are constants
+  pub fn mul<CS: ConstraintSystem<F>>(
+    &self,
+    mut cs: CS,
+    other: &Self,
+  ) -> Result<Self, SynthesisError> {
+    match (self, other) {
+      (Self::Constant(a), Self::Constant(b)) => Ok(Self::Constant(*a * b)),
+      (Self::Allocated(a), Self::Allocated(b)) => Ok(Self::Allocated(a.mul(cs, b)?)),
+      (Self::Allocated(num), Self::Constant(c)) | (Self::Constant(c), Self::Allocated(num)) => {
+        let product = AllocatedNum::alloc(cs.namespace(|| "product"), || {
+          num.get_value().map(|v| v * c).ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        cs.enforce(
+          || "product = num * c",
+          |lc| lc + (*c, num.get_variable()),
+          |lc| lc + CS::one(),
+          |lc| lc + product.get_variable(),
+        );
+        Ok(Self::Allocated(product))
+      },
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use bellpepper_core::test_cs::TestConstraintSystem;
+
+  use super::*;
+  use crate::provider::bn256_grumpkin::bn256::Scalar;
+
+  // computes (x + 2) * (x + 3) + 5 and returns it allocated
+  fn synthesize<CS: ConstraintSystem<Scalar>>(
+    mut cs: CS,
+    x: MaybeConstant<Scalar>,
+  ) -> AllocatedNum<Scalar> {
+    let a = x.add(cs.namespace(|| "a"), &MaybeConstant::constant(Scalar::from(2u64))).unwrap();
+    let b = x.add(cs.namespace(|| "b"), &MaybeConstant::constant(Scalar::from(3u64))).unwrap();
+    let c = a.mul(cs.namespace(|| "c"), &b).unwrap();
+    let d = c.add(cs.namespace(|| "d"), &MaybeConstant::constant(Scalar::from(5u64))).unwrap();
+    d.to_allocated(cs.namespace(|| "out"))
+  }
+
+  #[test]
+  fn test_constant_folding() {
+    let x = Scalar::from(4u64);
+    let expected = Scalar::from(47u64);
+
+    // with an allocated input, every operation costs one constraint
+    let mut cs = TestConstraintSystem::<Scalar>::new();
+    let num = AllocatedNum::alloc_infallible(cs.namespace(|| "x"), || x);
+    let out = synthesize(cs.namespace(|| "unfolded"), num.into());
+    assert!(cs.is_satisfied());
+    assert_eq!(out.get_value(), Some(expected));
+    assert_eq!(cs.num_constraints(), 4);
+
+    // with a constant input, only the final allocation does
+    let mut cs = TestConstraintSystem::<Scalar>::new();
+    let out = synthesize(cs.namespace(|| "folded"), MaybeConstant::constant(x));
+    assert!(cs.is_satisfied());
+    assert_eq!(out.get_value(), Some(expected));
+    assert_eq!(cs.num_constraints(), 1);
+  }
+}
@@ -102,7 +102,6 @@ impl<G: Group> AllocatedPoint<G> {
   }
 
   /// Returns coordinates associated with the point.
-  #[allow(unused)]
   pub const fn get_coordinates(
     &self,
   ) -> (&AllocatedNum<G::Base>, &AllocatedNum<G::Base>, &AllocatedNum<G::Base>) {
@@ -736,6 +735,7 @@ impl<G: Group> AllocatedPointNonInfinity<G> {
 
 #[cfg(test)]
 mod tests {
+  use bellpepper_core::test_cs::TestConstraintSystem;
   use expect_test::{expect, Expect};
   use ff::{Field, PrimeFieldBits};
   use group::Curve;
@@ -751,6 +751,7 @@ mod tests {
     },
     provider::{
       bn256_grumpkin::{bn256, grumpkin},
+      traits::DlogGroup,
       Bn256EngineIPA, Bn256EngineKZG, GrumpkinEngine,
     },
     traits::{snark::default_ck_hint, Engine},
@@ -932,6 +933,49 @@ mod tests {
     (a, e, s)
   }
 
+  fn test_ecc_circuit_native_with<G: DlogGroup>() {
+    let p = <G as group::Group>::random(&mut OsRng);
+    let q = <G as group::Group>::random(&mut OsRng);
+    let s = G::ScalarExt::random(&mut OsRng);
+
+    let mut cs = TestConstraintSystem::<G::Base>::new();
+    let p_alloc =
+      AllocatedPoint::<G>::alloc(cs.namespace(|| "p"), Some(p.to_coordinates())).unwrap();
+    let q_alloc =
+      AllocatedPoint::<G>::alloc(cs.namespace(|| "q"), Some(q.to_coordinates())).unwrap();
+    let bits = s
+      .to_le_bits()
+      .into_iter()
+      .enumerate()
+      .map(|(i, bit)| AllocatedBit::alloc(cs.namespace(|| format!("bit {i}")), Some(bit)))
+      .collect::<Result<Vec<_>, _>>()
+      .unwrap();
+
+    let sum = p_alloc.add(cs.namespace(|| "p + q"), &q_alloc).unwrap();
+    let double = p_alloc.double(cs.namespace(|| "2p")).unwrap();
+    let product = p_alloc.scalar_mul(cs.namespace(|| "s * p"), &bits).unwrap();
+    assert!(cs.is_satisfied());
+
+    // the gadgets agree with the group law of the curve itself
+    let value = |point: &AllocatedPoint<G>| {
+      let (x, y, is_infinity) = point.get_coordinates();
+      (
+        x.get_value().unwrap(),
+        y.get_value().unwrap(),
+        is_infinity.get_value().unwrap() == G::Base::ONE,
+      )
+    };
+    assert_eq!(value(&sum), (p + q).to_coordinates());
+    assert_eq!(value(&double), (p + p).to_coordinates());
+    assert_eq!(value(&product), (p * s).to_coordinates());
+  }
+
+  #[test]
+  fn test_ecc_circuit_native() {
+    test_ecc_circuit_native_with::<bn256::Point>();
+    test_ecc_circuit_native_with::<grumpkin::Point>();
+  }
+
   #[test]
   fn test_ecc_circuit_ops() {
     test_ecc_circuit_ops_with::<Bn256EngineIPA, GrumpkinEngine>(&expect!["2738"], &expect!["2724"]);
@@ -0,0 +1,85 @@
+//! This module implements a gadget verifying Merkle paths over an arity-2
+//! Poseidon hash
+use bellpepper::gadgets::boolean_utils::conditionally_select;
+use bellpepper_core::{boolean::Boolean, num::AllocatedNum, ConstraintSystem, SynthesisError};
+use ff::PrimeField;
+use generic_array::typenum::U2;
+use neptune::poseidon::PoseidonConstants;
+
+use super::utils::{enforce_equal, poseidon_hash2};
+
+/// Enforce that `leaf` is a member of the Merkle tree with root `root`.
+///
+/// `path` lists, from the leaf up, the sibling at each level together with a
+/// bit that is set when the current node is the right child, i.e. when the
+/// sibling is hashed on the left. Each node is
+/// [`poseidon_hash2`](super::poseidon_hash2) of its two children.
+pub fn verify_merkle_path<F: PrimeField, CS: ConstraintSystem<F>>(
+  mut cs: CS,
+  constants: &PoseidonConstants<F, U2>,
+  leaf: &AllocatedNum<F>,
+  path: &[(AllocatedNum<F>, Boolean)],
+  root: &AllocatedNum<F>,
+) -> Result<(), SynthesisError> {
+  let mut node = leaf.clone();
+  for (i, (sibling, is_right)) in path.iter().enumerate() {
+    let left =
+      conditionally_select(cs.namespace(|| format!("left {i}")), sibling, &node, is_right)?;
+    let right =
+      conditionally_select(cs.namespace(|| format!("right {i}")), &node, sibling, is_right)?;
+    node = poseidon_hash2(cs.namespace(|| format!("hash {i}")), constants, &left, &right)?;
+  }
+
+  enforce_equal(cs.namespace(|| "root"), &node, root);
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use bellpepper_core::{boolean::AllocatedBit, test_cs::TestConstraintSystem};
+  use ff::Field;
+
+  use super::*;
+  use crate::{
+    provider::{bn256_grumpkin::bn256::Scalar, poseidon_hash2 as native_hash2, Bn256EngineKZG},
+    traits::Engine,
+  };
+
+  #[test]
+  fn test_verify_merkle_path() {
+    let constants = PoseidonConstants::<Scalar, U2>::new();
+    let mut rng = Bn256EngineKZG::test_rng(0);
+
+    // a tree of depth 2 over 4 leaves
+    let leaves = (0..4).map(|_| Scalar::random(&mut rng)).collect::<Vec<_>>();
+    let nodes =
+      leaves.chunks(2).map(|pair| native_hash2(&constants, pair[0], pair[1])).collect::<Vec<_>>();
+    let root = native_hash2(&constants, nodes[0], nodes[1]);
+
+    let check = |index: usize, root: Scalar| {
+      let mut cs = TestConstraintSystem::<Scalar>::new();
+      let alloc = |cs: &mut TestConstraintSystem<Scalar>, name: &str, value: Scalar| {
+        AllocatedNum::alloc_infallible(cs.namespace(|| name.to_string()), || value)
+      };
+      let leaf = alloc(&mut cs, "leaf", leaves[index]);
+      let root = alloc(&mut cs, "root", root);
+      let path = [(leaves[index ^ 1], index & 1 == 1), (nodes[(index >> 1) ^ 1], index & 2 == 2)]
+        .into_iter()
+        .enumerate()
+        .map(|(i, (sibling, is_right))| {
+          let sibling = alloc(&mut cs, &format!("sibling {i}"), sibling);
+          let bit = AllocatedBit::alloc(cs.namespace(|| format!("bit {i}")), Some(is_right));
+          (sibling, Boolean::from(bit.unwrap()))
+        })
+        .collect::<Vec<_>>();
+
+      verify_merkle_path(cs.namespace(|| "verify"), &constants, &leaf, &path, &root).unwrap();
+      cs.is_satisfied()
+    };
+
+    for index in 0..leaves.len() {
+      assert!(check(index, root));
+    }
+    assert!(!check(2, root + Scalar::ONE));
+  }
+}
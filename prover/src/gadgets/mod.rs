@@ -1,7 +1,13 @@
 //! This module implements various gadgets necessary for Nova and applications
 //! built with Nova.
+mod constant;
+pub use constant::MaybeConstant;
+
 mod ecc;
-pub(crate) use ecc::AllocatedPoint;
+pub use ecc::AllocatedPoint;
+
+mod merkle;
+pub use merkle::verify_merkle_path;
 
 mod nonnative;
 pub(crate) use nonnative::{
@@ -10,8 +16,9 @@ pub(crate) use nonnative::{
 };
 
 mod r1cs;
+pub use r1cs::fold_error_term;
 pub(crate) use r1cs::{
-  conditionally_select_alloc_relaxed_r1cs,
+  absorb_relaxed_instance, conditionally_select_alloc_relaxed_r1cs,
   conditionally_select_vec_allocated_relaxed_r1cs_instance, AllocatedR1CSInstance,
   AllocatedRelaxedR1CSInstance,
 };
@@ -22,3 +29,7 @@ pub(crate) use utils::{
   alloc_bignat_constant, alloc_num_equals, alloc_scalar_as_base, alloc_zero,
   conditionally_select_allocated_bit, conditionally_select_bignat, le_bits_to_num, scalar_as_base,
 };
+pub use utils::{
+  alloc_constant, be_bits_to_num, enforce_equal, enforce_pc_in_range, is_equal_constant,
+  le_bits_to_num_checked, num_to_bits_be, num_to_bytes_le, poseidon_hash2, range_check, select,
+};
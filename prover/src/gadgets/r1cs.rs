@@ -2,7 +2,7 @@
 use bellpepper::gadgets::{
   boolean::Boolean, boolean_utils::conditionally_select, num::AllocatedNum, Assignment,
 };
-use bellpepper_core::{ConstraintSystem, SynthesisError};
+use bellpepper_core::{boolean::AllocatedBit, ConstraintSystem, SynthesisError};
 use ff::Field;
 use itertools::Itertools as _;
 
@@ -305,6 +305,25 @@ impl<E: Engine, const N: usize> AllocatedRelaxedR1CSInstance<E, N> {
   }
 }
 
+/// Absorb `inst` in `ro` in the canonical order of the native
+/// `AbsorbInROTrait` implementation for `RelaxedR1CSInstance`: the coordinates
+/// of `W` and `E`, then `u`, then the limbs of each entry of `X`.
+///
+/// The native side decomposes `X` into `BN_N_LIMBS` limbs of `BN_LIMB_WIDTH`
+/// bits, so `inst` must have been allocated with the same parameters for the
+/// two to agree.
+pub fn absorb_relaxed_instance<
+  E: Engine,
+  CS: ConstraintSystem<<E as Engine>::Base>,
+  const N: usize,
+>(
+  ro: &mut E::ROCircuit,
+  cs: CS,
+  inst: &AllocatedRelaxedR1CSInstance<E, N>,
+) -> Result<(), SynthesisError> {
+  inst.absorb_in_ro(cs, ro)
+}
+
 /// c = cond ? a: b, where a, b: `AllocatedRelaxedR1CSInstance`
 pub fn conditionally_select_alloc_relaxed_r1cs<
   E: Engine,
@@ -393,3 +412,192 @@ pub fn conditionally_select_point<G: Group, CS: ConstraintSystem<G::Base>>(
   };
   Ok(c)
 }
+
+/// Computes the error commitment of a fold of two relaxed instances,
+/// `E1 + r * T + r^2 * E2`, where `r_bits` is the little-endian decomposition
+/// of the challenge `r`. This lets an auditor recompute in-circuit the
+/// `comm_E` a native fold produces.
+pub fn fold_error_term<G: Group, CS: ConstraintSystem<G::Base>>(
+  mut cs: CS,
+  E1: &AllocatedPoint<G>,
+  T: &AllocatedPoint<G>,
+  E2: &AllocatedPoint<G>,
+  r_bits: &[AllocatedBit],
+) -> Result<AllocatedPoint<G>, SynthesisError> {
+  // E1 + r * (T + r * E2), which costs two scalar multiplications
+  let rE2 = E2.scalar_mul(cs.namespace(|| "r * E2"), r_bits)?;
+  let T_rE2 = T.add(cs.namespace(|| "T + r * E2"), &rE2)?;
+  let r_T_rE2 = T_rE2.scalar_mul(cs.namespace(|| "r * (T + r * E2)"), r_bits)?;
+  E1.add(cs.namespace(|| "E1 + r * (T + r * E2)"), &r_T_rE2)
+}
+
+#[cfg(test)]
+mod tests {
+  use bellpepper_core::test_cs::TestConstraintSystem;
+  use rand::{rngs::OsRng, Rng};
+
+  use super::*;
+  use crate::{
+    constants::{BN_LIMB_WIDTH, BN_N_LIMBS, NIO_NOVA_FOLD},
+    gadgets::scalar_as_base,
+    nifs::NIFS,
+    provider::{Bn256EngineKZG, GrumpkinEngine, PallasEngine, VestaEngine},
+    traits::{commitment::CommitmentEngineTrait, AbsorbInROTrait, ROConstants, ROTrait},
+  };
+
+  fn test_absorb_relaxed_instance_with<E: Engine>() {
+    let mut csprng: OsRng = OsRng;
+    let ck = E::CE::setup(b"test", 4);
+    let random_vec = |n: usize| (0..n).map(|_| E::Scalar::random(&mut csprng)).collect::<Vec<_>>();
+    let inst = RelaxedR1CSInstance::<E> {
+      comm_W: E::CE::commit(&ck, &random_vec(4)),
+      comm_E: E::CE::commit(&ck, &random_vec(4)),
+      X:      random_vec(2),
+      u:      E::Scalar::random(&mut csprng),
+    };
+    let num_absorbs = 7 + 2 * BN_N_LIMBS;
+
+    let mut ro = E::RO::new(ROConstants::<E>::default(), num_absorbs);
+    inst.absorb_in_ro(&mut ro);
+    let expected = ro.squeeze(NUM_CHALLENGE_BITS).unwrap();
+
+    let mut cs = TestConstraintSystem::<E::Base>::new();
+    let alloc_inst = AllocatedRelaxedR1CSInstance::<E, 2>::alloc(
+      cs.namespace(|| "inst"),
+      Some(&inst),
+      BN_LIMB_WIDTH,
+      BN_N_LIMBS,
+    )
+    .unwrap();
+    let mut ro_gadget = E::ROCircuit::new(ROConstantsCircuit::<E>::default(), num_absorbs);
+    absorb_relaxed_instance(&mut ro_gadget, cs.namespace(|| "absorb"), &alloc_inst).unwrap();
+    let bits = ro_gadget.squeeze(cs.namespace(|| "squeeze"), NUM_CHALLENGE_BITS).unwrap();
+    let challenge = le_bits_to_num(cs.namespace(|| "challenge"), &bits).unwrap();
+
+    assert!(cs.is_satisfied());
+    assert_eq!(challenge.get_value(), Some(scalar_as_base::<E>(expected)));
+  }
+
+  #[test]
+  fn test_absorb_relaxed_instance() {
+    test_absorb_relaxed_instance_with::<Bn256EngineKZG>();
+    test_absorb_relaxed_instance_with::<GrumpkinEngine>();
+    test_absorb_relaxed_instance_with::<PallasEngine>();
+    test_absorb_relaxed_instance_with::<VestaEngine>();
+  }
+
+  fn test_fold_with_r1cs_with<E: Engine>() {
+    let mut csprng: OsRng = OsRng;
+    let ck = E::CE::setup(b"test", 4);
+    let random_vec = |n: usize| (0..n).map(|_| E::Scalar::random(&mut csprng)).collect::<Vec<_>>();
+    let U1 = RelaxedR1CSInstance::<E> {
+      comm_W: E::CE::commit(&ck, &random_vec(4)),
+      comm_E: E::CE::commit(&ck, &random_vec(4)),
+      X:      random_vec(NIO_NOVA_FOLD),
+      u:      E::Scalar::random(&mut csprng),
+    };
+    let U2 =
+      R1CSInstance::<E> { comm_W: E::CE::commit(&ck, &random_vec(4)), X: random_vec(2) };
+    let comm_T = E::CE::commit(&ck, &random_vec(4));
+    let pp_digest = E::Scalar::random(&mut csprng);
+
+    // the native verifier folds exactly as the circuit does
+    let expected = NIFS::verify_with_comm_T(
+      &ROConstants::<E>::default(),
+      &pp_digest,
+      &U1,
+      &U2,
+      &comm_T.compress(),
+    )
+    .unwrap();
+
+    let mut cs = TestConstraintSystem::<E::Base>::new();
+    let params = alloc_scalar_as_base::<E, _>(cs.namespace(|| "params"), Some(pp_digest)).unwrap();
+    let alloc_U1 = AllocatedRelaxedR1CSInstance::<E, NIO_NOVA_FOLD>::alloc(
+      cs.namespace(|| "U1"),
+      Some(&U1),
+      BN_LIMB_WIDTH,
+      BN_N_LIMBS,
+    )
+    .unwrap();
+    let alloc_U2 =
+      AllocatedR1CSInstance::<E, NIO_NOVA_FOLD>::alloc(cs.namespace(|| "U2"), Some(&U2)).unwrap();
+    let T = AllocatedPoint::alloc(cs.namespace(|| "T"), Some(comm_T.to_coordinates())).unwrap();
+    let folded = alloc_U1
+      .fold_with_r1cs(
+        cs.namespace(|| "fold"),
+        &params,
+        &alloc_U2,
+        &T,
+        ROConstantsCircuit::<E>::default(),
+        BN_LIMB_WIDTH,
+        BN_N_LIMBS,
+      )
+      .unwrap();
+    assert!(cs.is_satisfied());
+
+    let coordinates = |p: &AllocatedPoint<E::GE>| {
+      (
+        p.x.get_value().unwrap(),
+        p.y.get_value().unwrap(),
+        p.is_infinity.get_value() == Some(E::Base::ONE),
+      )
+    };
+    assert_eq!(coordinates(&folded.W), expected.comm_W.to_coordinates());
+    assert_eq!(coordinates(&folded.E), expected.comm_E.to_coordinates());
+    assert_eq!(folded.u.get_value(), Some(scalar_as_base::<E>(expected.u)));
+    for (X, x) in folded.X.iter().zip_eq(expected.X.iter()) {
+      assert_eq!(X.value, Some(f_to_nat(x)));
+    }
+  }
+
+  #[test]
+  fn test_fold_with_r1cs() {
+    test_fold_with_r1cs_with::<Bn256EngineKZG>();
+    test_fold_with_r1cs_with::<PallasEngine>();
+  }
+
+  fn test_fold_error_term_with<E: Engine>() {
+    let mut csprng: OsRng = OsRng;
+    let ck = E::CE::setup(b"test", 4);
+    let random_vec = |n: usize| (0..n).map(|_| E::Scalar::random(&mut csprng)).collect::<Vec<_>>();
+    let comm_E1 = E::CE::commit(&ck, &random_vec(4));
+    let comm_T = E::CE::commit(&ck, &random_vec(4));
+    let comm_E2 = E::CE::commit(&ck, &random_vec(4));
+    let bits = (0..NUM_CHALLENGE_BITS).map(|_| csprng.gen::<bool>()).collect::<Vec<_>>();
+    let r = bits
+      .iter()
+      .rev()
+      .fold(E::Scalar::ZERO, |acc, bit| acc.double() + E::Scalar::from(u64::from(*bit)));
+
+    let expected = comm_E1 + comm_T * r + comm_E2 * (r * r);
+
+    let mut cs = TestConstraintSystem::<E::Base>::new();
+    let E1 = AllocatedPoint::alloc(cs.namespace(|| "E1"), Some(comm_E1.to_coordinates())).unwrap();
+    let T = AllocatedPoint::alloc(cs.namespace(|| "T"), Some(comm_T.to_coordinates())).unwrap();
+    let E2 = AllocatedPoint::alloc(cs.namespace(|| "E2"), Some(comm_E2.to_coordinates())).unwrap();
+    let r_bits = bits
+      .iter()
+      .enumerate()
+      .map(|(i, bit)| AllocatedBit::alloc(cs.namespace(|| format!("r bit {i}")), Some(*bit)))
+      .collect::<Result<Vec<_>, _>>()
+      .unwrap();
+    let folded = fold_error_term(cs.namespace(|| "fold E"), &E1, &T, &E2, &r_bits).unwrap();
+    assert!(cs.is_satisfied());
+
+    assert_eq!(
+      (
+        folded.x.get_value().unwrap(),
+        folded.y.get_value().unwrap(),
+        folded.is_infinity.get_value() == Some(E::Base::ONE),
+      ),
+      expected.to_coordinates()
+    );
+  }
+
+  #[test]
+  fn test_fold_error_term() {
+    test_fold_error_term_with::<Bn256EngineKZG>();
+    test_fold_error_term_with::<PallasEngine>();
+  }
+}
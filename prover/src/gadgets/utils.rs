@@ -1,11 +1,13 @@
 //! This module implements various low-level gadgets
-use bellpepper::gadgets::Assignment;
+use bellpepper::gadgets::{boolean_utils::conditionally_select, Assignment};
 use bellpepper_core::{
   boolean::{AllocatedBit, Boolean},
   num::AllocatedNum,
   ConstraintSystem, LinearCombination, SynthesisError,
 };
 use ff::{Field, PrimeField, PrimeFieldBits};
+use generic_array::typenum::U2;
+use neptune::{circuit2::poseidon_hash_allocated, poseidon::PoseidonConstants};
 use num_bigint::BigInt;
 
 use super::nonnative::bignat::{nat_to_limbs, BigNat};
@@ -39,6 +41,143 @@ where
   Ok(num)
 }
 
+/// Same as [`le_bits_to_num`], but errors if there are more bits than the
+/// capacity of the field, where the result would wrap around the modulus
+pub fn le_bits_to_num_checked<Scalar, CS>(
+  cs: CS,
+  bits: &[AllocatedBit],
+) -> Result<AllocatedNum<Scalar>, SynthesisError>
+where
+  Scalar: PrimeField + PrimeFieldBits,
+  CS: ConstraintSystem<Scalar>,
+{
+  if bits.len() > Scalar::CAPACITY as usize {
+    return Err(SynthesisError::IncompatibleLengthVector(format!(
+      "{} > {}",
+      bits.len(),
+      Scalar::CAPACITY
+    )));
+  }
+  le_bits_to_num(cs, bits)
+}
+
+/// Decompose `x` into `n_bits` little-endian bits and enforce that they
+/// recompose to `x`, which constrains `x` to be less than `2^n_bits`.
+/// `n_bits` must not exceed the capacity of the field, so that the
+/// recomposition cannot wrap around the modulus.
+pub fn range_check<F, CS>(
+  mut cs: CS,
+  x: &AllocatedNum<F>,
+  n_bits: usize,
+) -> Result<Vec<AllocatedBit>, SynthesisError>
+where
+  F: PrimeField + PrimeFieldBits,
+  CS: ConstraintSystem<F>,
+{
+  if n_bits > F::CAPACITY as usize {
+    return Err(SynthesisError::IncompatibleLengthVector(format!("{n_bits} > {}", F::CAPACITY)));
+  }
+
+  let x_bits = x.get_value().map(|x| x.to_le_bits());
+  let bits = (0..n_bits)
+    .map(|i| {
+      AllocatedBit::alloc(cs.namespace(|| format!("bit {i}")), x_bits.as_ref().map(|b| b[i]))
+    })
+    .collect::<Result<Vec<_>, _>>()?;
+
+  let mut lc = LinearCombination::zero();
+  let mut coeff = F::ONE;
+  for bit in bits.iter() {
+    lc = lc + (coeff, bit.get_variable());
+    coeff = coeff.double();
+  }
+  cs.enforce(|| "recompose bits", |_| lc, |lc| lc + CS::one(), |lc| lc + x.get_variable());
+
+  Ok(bits)
+}
+
+/// Same as [`range_check`], but returns the bits of `x` in big-endian order,
+/// most significant bit first, for protocols such as the EVM that encode
+/// numbers big-endian.
+pub fn num_to_bits_be<F, CS>(
+  cs: CS,
+  x: &AllocatedNum<F>,
+  n_bits: usize,
+) -> Result<Vec<AllocatedBit>, SynthesisError>
+where
+  F: PrimeField + PrimeFieldBits,
+  CS: ConstraintSystem<F>,
+{
+  let mut bits = range_check(cs, x, n_bits)?;
+  bits.reverse();
+  Ok(bits)
+}
+
+/// Decompose `x` into its canonical little-endian bytes, each given as
+/// little-endian bits, enforcing that they recompose to `x` and that `x` is
+/// reduced. When the bit length of the field is not a multiple of 8, the top
+/// byte is padded with constant zero bits.
+pub fn num_to_bytes_le<F, CS>(
+  mut cs: CS,
+  x: &AllocatedNum<F>,
+) -> Result<Vec<[Boolean; 8]>, SynthesisError>
+where
+  F: PrimeField + PrimeFieldBits,
+  CS: ConstraintSystem<F>,
+{
+  let mut bits = x.to_bits_le_strict(cs.namespace(|| "x bits"))?;
+  bits.resize(bits.len().div_ceil(8) * 8, Boolean::constant(false));
+  Ok(bits.chunks(8).map(|byte| core::array::from_fn(|i| byte[i].clone())).collect())
+}
+
+/// Gets as input the big-endian representation of a number and spits out the
+/// number, see [`le_bits_to_num`]
+pub fn be_bits_to_num<Scalar, CS>(
+  cs: CS,
+  bits: &[AllocatedBit],
+) -> Result<AllocatedNum<Scalar>, SynthesisError>
+where
+  Scalar: PrimeField + PrimeFieldBits,
+  CS: ConstraintSystem<Scalar>,
+{
+  let bits = bits.iter().rev().cloned().collect::<Vec<_>>();
+  le_bits_to_num(cs, &bits)
+}
+
+/// Enforce that the program counter `pc` of a SuperNova step is a valid
+/// circuit index, i.e. `0 <= pc < num_circuits`.
+///
+/// Both `pc` and `num_circuits - 1 - pc` are range-checked to the bit length
+/// of `num_circuits - 1`, which costs two constraints per bit.
+pub fn enforce_pc_in_range<F, CS>(
+  mut cs: CS,
+  pc: &AllocatedNum<F>,
+  num_circuits: usize,
+) -> Result<(), SynthesisError>
+where
+  F: PrimeField + PrimeFieldBits,
+  CS: ConstraintSystem<F>,
+{
+  if num_circuits == 0 {
+    return Err(SynthesisError::Unsatisfiable);
+  }
+
+  let max_pc = F::from((num_circuits - 1) as u64);
+  let n_bits = (usize::BITS - (num_circuits - 1).leading_zeros()) as usize;
+  let slack =
+    AllocatedNum::alloc(cs.namespace(|| "slack"), || Ok(max_pc - *pc.get_value().get()?))?;
+  cs.enforce(
+    || "pc + slack = num_circuits - 1",
+    |lc| lc + pc.get_variable() + slack.get_variable(),
+    |lc| lc + CS::one(),
+    |lc| lc + (max_pc, CS::one()),
+  );
+
+  range_check(cs.namespace(|| "pc range"), pc, n_bits)?;
+  range_check(cs.namespace(|| "slack range"), &slack, n_bits)?;
+  Ok(())
+}
+
 /// Allocate a variable that is set to zero
 pub fn alloc_zero<F: PrimeField, CS: ConstraintSystem<F>>(mut cs: CS) -> AllocatedNum<F> {
   let zero = AllocatedNum::alloc_infallible(cs.namespace(|| "alloc"), || F::ZERO);
@@ -59,6 +198,35 @@ pub fn alloc_one<F: PrimeField, CS: ConstraintSystem<F>>(mut cs: CS) -> Allocate
   one
 }
 
+/// Allocate a variable that is constrained to the constant `value`
+pub fn alloc_constant<F: PrimeField, CS: ConstraintSystem<F>>(
+  mut cs: CS,
+  value: F,
+) -> AllocatedNum<F> {
+  let num = AllocatedNum::alloc_infallible(cs.namespace(|| "alloc"), || value);
+  cs.enforce(
+    || "check constant is valid",
+    |lc| lc + num.get_variable(),
+    |lc| lc + CS::one(),
+    |lc| lc + (value, CS::one()),
+  );
+  num
+}
+
+/// Enforce that `a` and `b` are equal
+pub fn enforce_equal<F: PrimeField, CS: ConstraintSystem<F>>(
+  mut cs: CS,
+  a: &AllocatedNum<F>,
+  b: &AllocatedNum<F>,
+) {
+  cs.enforce(
+    || "check a = b",
+    |lc| lc + a.get_variable() - b.get_variable(),
+    |lc| lc + CS::one(),
+    |lc| lc,
+  );
+}
+
 /// Allocate a scalar as a base. Only to be used is the scalar fits in base!
 pub fn alloc_scalar_as_base<E, CS>(
   mut cs: CS,
@@ -155,6 +323,49 @@ pub fn alloc_num_equals<F: PrimeField, CS: ConstraintSystem<F>>(
   Ok(r)
 }
 
+/// Returns a number that is `1` if `x` equals the constant `c` and `0`
+/// otherwise, in two constraints.
+///
+/// Unlike [`alloc_num_equals`] this neither allocates `c` nor `x - c`, and it
+/// skips the booleanity constraint: the two constraints below already force
+/// `r = 1` when `x == c` and `r = 0` otherwise. Neither one is sound on its
+/// own. With only `t*(x - c) = 1 - r`, a prover with `x != c` sets `t = 0` and
+/// `r = 1`; with only `r*(x - c) = 0`, a prover with `x == c` sets `r = 0`.
+///
+/// The result is an [`AllocatedNum`] because bellpepper only wraps a variable
+/// in a [`Boolean`] together with its own booleanity constraint.
+pub fn is_equal_constant<F: PrimeField, CS: ConstraintSystem<F>>(
+  mut cs: CS,
+  x: &AllocatedNum<F>,
+  c: F,
+) -> Result<AllocatedNum<F>, SynthesisError> {
+  let r = AllocatedNum::alloc(cs.namespace(|| "r"), || {
+    Ok(if *x.get_value().get()? == c { F::ONE } else { F::ZERO })
+  })?;
+
+  // t = 1 if x == c else 1/(x - c)
+  let t = AllocatedNum::alloc(cs.namespace(|| "t"), || {
+    let diff = *x.get_value().get()? - c;
+    Ok(diff.invert().unwrap_or(F::ONE))
+  })?;
+
+  cs.enforce(
+    || "t*(x - c) = 1 - r",
+    |lc| lc + t.get_variable(),
+    |lc| lc + x.get_variable() - (c, CS::one()),
+    |lc| lc + CS::one() - r.get_variable(),
+  );
+
+  cs.enforce(
+    || "r*(x - c) = 0",
+    |lc| lc + r.get_variable(),
+    |lc| lc + x.get_variable() - (c, CS::one()),
+    |lc| lc,
+  );
+
+  Ok(r)
+}
+
 // TODO: Figure out if this can be done better
 pub fn conditionally_select_allocated_bit<F: PrimeField, CS: ConstraintSystem<F>>(
   mut cs: CS,
@@ -178,6 +389,28 @@ pub fn conditionally_select_allocated_bit<F: PrimeField, CS: ConstraintSystem<F>
 
   Ok(c)
 }
+
+/// If condition return `a` otherwise `b`, element-wise over two vectors of
+/// `AllocatedNum` of the same length
+pub fn select<F: PrimeField, CS: ConstraintSystem<F>>(
+  mut cs: CS,
+  condition: &Boolean,
+  a: &[AllocatedNum<F>],
+  b: &[AllocatedNum<F>],
+) -> Result<Vec<AllocatedNum<F>>, SynthesisError> {
+  if a.len() != b.len() {
+    return Err(SynthesisError::IncompatibleLengthVector(format!("{} != {}", a.len(), b.len())));
+  }
+
+  a.iter()
+    .zip(b.iter())
+    .enumerate()
+    .map(|(i, (a, b))| {
+      conditionally_select(cs.namespace(|| format!("select {i}")), a, b, condition)
+    })
+    .collect()
+}
+
 /// If condition return a otherwise b where a and b are `BigNats`
 pub fn conditionally_select_bignat<F: PrimeField, CS: ConstraintSystem<F>>(
   mut cs: CS,
@@ -383,3 +616,241 @@ pub fn select_num_or_one<F: PrimeField, CS: ConstraintSystem<F>>(
 
   Ok(c)
 }
+
+/// Hash exactly two elements with an arity-2 Poseidon, e.g. for a Merkle node.
+/// This is cheaper than the [`ROCircuitTrait`](crate::traits::ROCircuitTrait)
+/// sponge and matches [`poseidon_hash2`](crate::provider::poseidon_hash2)
+/// natively.
+pub fn poseidon_hash2<F: PrimeField, CS: ConstraintSystem<F>>(
+  cs: CS,
+  constants: &PoseidonConstants<F, U2>,
+  a: &AllocatedNum<F>,
+  b: &AllocatedNum<F>,
+) -> Result<AllocatedNum<F>, SynthesisError> {
+  poseidon_hash_allocated(cs, vec![a.clone(), b.clone()], constants)
+}
+
+#[cfg(test)]
+mod tests {
+  use bellpepper_core::test_cs::TestConstraintSystem;
+
+  use super::*;
+  use crate::provider::{bn256_grumpkin::bn256::Scalar, Bn256EngineKZG};
+
+  #[test]
+  fn test_is_equal_constant() {
+    let c = Scalar::from(7u64);
+    let check = |value: u64| {
+      let mut cs = TestConstraintSystem::<Scalar>::new();
+      let x = AllocatedNum::alloc_infallible(cs.namespace(|| "x"), || Scalar::from(value));
+      let eq = is_equal_constant(cs.namespace(|| "x == c"), &x, c).unwrap();
+      assert!(cs.is_satisfied());
+      assert_eq!(cs.num_constraints(), 2);
+      eq.get_value().unwrap() == Scalar::ONE
+    };
+
+    assert!(check(7));
+    assert!(!check(0));
+    assert!(!check(8));
+
+    // without a booleanity constraint the result still cannot be flipped
+    for (value, flipped) in [(7u64, Scalar::ZERO), (8, Scalar::ONE)] {
+      let mut cs = TestConstraintSystem::<Scalar>::new();
+      let x = AllocatedNum::alloc_infallible(cs.namespace(|| "x"), || Scalar::from(value));
+      is_equal_constant(cs.namespace(|| "x == c"), &x, c).unwrap();
+      cs.set("x == c/r/num", flipped);
+      assert!(!cs.is_satisfied());
+    }
+  }
+
+  #[test]
+  fn test_num_to_bytes_le() {
+    let mut rng = Bn256EngineKZG::test_rng(0);
+    let value = Scalar::random(&mut rng);
+    let mut cs = TestConstraintSystem::<Scalar>::new();
+    let x = AllocatedNum::alloc_infallible(cs.namespace(|| "x"), || value);
+    let bytes = num_to_bytes_le(cs.namespace(|| "bytes"), &x).unwrap();
+    assert!(cs.is_satisfied());
+
+    // the 254-bit field takes 32 bytes, the top one padded with two zero bits
+    assert_eq!(bytes.len(), 32);
+    assert!(bytes[31][6..].iter().all(|bit| bit.get_value() == Some(false)));
+
+    // the bytes are the canonical representation and recompose to x
+    let bytes = bytes
+      .iter()
+      .map(|byte| {
+        byte.iter().rev().fold(0u8, |acc, bit| (acc << 1) | u8::from(bit.get_value().unwrap()))
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(bytes, value.to_repr().as_ref());
+    let recomposed = bytes
+      .iter()
+      .rev()
+      .fold(Scalar::ZERO, |acc, byte| acc * Scalar::from(256) + Scalar::from(u64::from(*byte)));
+    assert_eq!(recomposed, value);
+  }
+
+  #[test]
+  fn test_range_check() {
+    let check = |value: u64, n_bits: usize| {
+      let mut cs = TestConstraintSystem::<Scalar>::new();
+      let x = AllocatedNum::alloc_infallible(cs.namespace(|| "x"), || Scalar::from(value));
+      let bits = range_check(cs.namespace(|| "range check"), &x, n_bits).unwrap();
+      assert_eq!(bits.len(), n_bits);
+      cs.is_satisfied()
+    };
+
+    assert!(check(0, 8));
+    assert!(check(255, 8));
+    assert!(!check(256, 8));
+    assert!(check(256, 9));
+
+    // more bits than the field can hold are rejected
+    let mut cs = TestConstraintSystem::<Scalar>::new();
+    let x = AllocatedNum::alloc_infallible(cs.namespace(|| "x"), || Scalar::ONE);
+    assert!(range_check(cs.namespace(|| "range check"), &x, Scalar::NUM_BITS as usize).is_err());
+  }
+
+  #[test]
+  fn test_bits_be() {
+    let mut cs = TestConstraintSystem::<Scalar>::new();
+    let x = AllocatedNum::alloc_infallible(cs.namespace(|| "x"), || Scalar::from(0b1011_0010));
+    let be_bits = num_to_bits_be(cs.namespace(|| "to bits"), &x, 8).unwrap();
+    let values = be_bits.iter().map(|bit| bit.get_value().unwrap()).collect::<Vec<_>>();
+    assert_eq!(values, [true, false, true, true, false, false, true, false]);
+
+    // recomposing the big-endian bits, or the reversed ones little-endian, yields x
+    let be_num = be_bits_to_num(cs.namespace(|| "from be bits"), &be_bits).unwrap();
+    let le_bits = be_bits.iter().rev().cloned().collect::<Vec<_>>();
+    let le_num = le_bits_to_num(cs.namespace(|| "from le bits"), &le_bits).unwrap();
+    assert_eq!(be_num.get_value(), x.get_value());
+    assert_eq!(le_num.get_value(), x.get_value());
+    assert!(cs.is_satisfied());
+
+    // a value that does not fit in the bits is rejected
+    let mut cs = TestConstraintSystem::<Scalar>::new();
+    let x = AllocatedNum::alloc_infallible(cs.namespace(|| "x"), || Scalar::from(256));
+    num_to_bits_be(cs.namespace(|| "to bits"), &x, 8).unwrap();
+    assert!(!cs.is_satisfied());
+  }
+
+  #[test]
+  fn test_enforce_pc_in_range() {
+    let check = |pc: u64, num_circuits: usize| {
+      let mut cs = TestConstraintSystem::<Scalar>::new();
+      let pc = AllocatedNum::alloc_infallible(cs.namespace(|| "pc"), || Scalar::from(pc));
+      enforce_pc_in_range(cs.namespace(|| "pc in range"), &pc, num_circuits).unwrap();
+      cs.is_satisfied()
+    };
+
+    for num_circuits in [1, 3, 4, 5] {
+      for pc in 0..num_circuits as u64 {
+        assert!(check(pc, num_circuits));
+      }
+      assert!(!check(num_circuits as u64, num_circuits));
+    }
+    assert!(!check(7, 5));
+
+    // a negative pc wraps around to a large field element
+    let mut cs = TestConstraintSystem::<Scalar>::new();
+    let pc = AllocatedNum::alloc_infallible(cs.namespace(|| "pc"), || -Scalar::ONE);
+    enforce_pc_in_range(cs.namespace(|| "pc in range"), &pc, 4).unwrap();
+    assert!(!cs.is_satisfied());
+  }
+
+  #[test]
+  fn test_select() {
+    let mut cs = TestConstraintSystem::<Scalar>::new();
+    let alloc = |cs: &mut TestConstraintSystem<Scalar>, name: &str, values: &[u64]| {
+      values
+        .iter()
+        .enumerate()
+        .map(|(i, v)| {
+          AllocatedNum::alloc_infallible(cs.namespace(|| format!("{name} {i}")), || {
+            Scalar::from(*v)
+          })
+        })
+        .collect::<Vec<_>>()
+    };
+    let a = alloc(&mut cs, "a", &[1, 2, 3]);
+    let b = alloc(&mut cs, "b", &[4, 5, 6]);
+
+    for (i, (condition, expected)) in [(true, &a), (false, &b)].into_iter().enumerate() {
+      let condition = Boolean::from(
+        AllocatedBit::alloc(cs.namespace(|| format!("condition {i}")), Some(condition)).unwrap(),
+      );
+      let c = select(cs.namespace(|| format!("select {i}")), &condition, &a, &b).unwrap();
+      let values = c.iter().map(|c| c.get_value()).collect::<Vec<_>>();
+      assert_eq!(values, expected.iter().map(|e| e.get_value()).collect::<Vec<_>>());
+    }
+    assert!(cs.is_satisfied());
+
+    // vectors of different lengths are rejected
+    let res = select(cs.namespace(|| "mismatch"), &Boolean::constant(true), &a, &b[..2]);
+    assert!(matches!(res, Err(SynthesisError::IncompatibleLengthVector(_))));
+  }
+
+  #[test]
+  fn test_alloc_constant() {
+    let mut cs = TestConstraintSystem::<Scalar>::new();
+    let value = Scalar::from(42u64);
+    let c = alloc_constant(cs.namespace(|| "c"), value);
+    assert_eq!(c.get_value(), Some(value));
+    assert_eq!(cs.num_constraints(), 1);
+    assert!(cs.is_satisfied());
+
+    // the allocated value cannot deviate from the constant
+    cs.set("c/alloc/num", Scalar::from(43u64));
+    assert!(!cs.is_satisfied());
+  }
+
+  #[test]
+  fn test_enforce_equal() {
+    let check = |a: u64, b: u64| {
+      let mut cs = TestConstraintSystem::<Scalar>::new();
+      let a = AllocatedNum::alloc_infallible(cs.namespace(|| "a"), || Scalar::from(a));
+      let b = AllocatedNum::alloc_infallible(cs.namespace(|| "b"), || Scalar::from(b));
+      enforce_equal(cs.namespace(|| "a = b"), &a, &b);
+      assert_eq!(cs.num_constraints(), 1);
+      cs.is_satisfied()
+    };
+
+    assert!(check(7, 7));
+    assert!(!check(7, 8));
+  }
+
+  #[test]
+  fn test_le_bits_to_num_checked() {
+    let bits_to_num = |n_bits: usize| {
+      let mut cs = TestConstraintSystem::<Scalar>::new();
+      let bits = (0..n_bits)
+        .map(|i| AllocatedBit::alloc(cs.namespace(|| format!("bit {i}")), Some(true)).unwrap())
+        .collect::<Vec<_>>();
+      le_bits_to_num_checked(cs.namespace(|| "num"), &bits).map(|num| num.get_value())
+    };
+
+    let capacity = Scalar::CAPACITY as usize;
+    let expected = (0..capacity).fold(Scalar::ZERO, |acc, _| acc.double() + Scalar::ONE);
+    assert_eq!(bits_to_num(capacity).unwrap(), Some(expected));
+    assert!(matches!(bits_to_num(capacity + 1), Err(SynthesisError::IncompatibleLengthVector(_))));
+  }
+
+  #[test]
+  fn test_poseidon_hash2() {
+    let constants = PoseidonConstants::<Scalar, U2>::new();
+    let mut rng = Bn256EngineKZG::test_rng(0);
+    let mut cs = TestConstraintSystem::<Scalar>::new();
+
+    for i in 0..8 {
+      let (a, b) = (Scalar::random(&mut rng), Scalar::random(&mut rng));
+      let alloc_a = AllocatedNum::alloc_infallible(cs.namespace(|| format!("a {i}")), || a);
+      let alloc_b = AllocatedNum::alloc_infallible(cs.namespace(|| format!("b {i}")), || b);
+      let hash =
+        poseidon_hash2(cs.namespace(|| format!("hash {i}")), &constants, &alloc_a, &alloc_b)
+          .unwrap();
+      assert_eq!(hash.get_value(), Some(crate::provider::poseidon_hash2(&constants, a, b)));
+    }
+    assert!(cs.is_satisfied());
+  }
+}
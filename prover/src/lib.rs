@@ -7,6 +7,7 @@ pub mod digest;
 pub mod nifs;
 
 // public modules
+pub mod aggregate;
 pub mod constants;
 pub mod errors;
 pub mod fast_serde;
@@ -19,6 +20,8 @@ pub mod traits;
 pub mod cyclefold;
 pub mod supernova;
 
+#[cfg(any(test, feature = "testutil"))] pub mod testutil;
+
 use std::sync::Arc;
 
 use ark_r1cs_std::{
@@ -30,20 +33,23 @@ use ark_relations::{
 };
 use circuit::{NovaAugmentedCircuit, NovaAugmentedCircuitInputs, NovaAugmentedCircuitParams};
 use constants::{BN_LIMB_WIDTH, BN_N_LIMBS, NUM_FE_WITHOUT_IO_FOR_CRHF, NUM_HASH_BITS};
-use errors::NovaError;
+use errors::{CircuitKind, NovaError, UnSatCheck};
 use ark_ff::Field;
 use gadgets::scalar_as_base;
 use nifs::NIFS;
 use once_cell::sync::OnceCell;
 use r1cs::{
-  CommitmentKeyHint, R1CSInstance, R1CSShape, R1CSWitness, RelaxedR1CSInstance, RelaxedR1CSWitness,
+  commitment_key, CommitmentKeyHint, R1CSInstance, R1CSShape, R1CSWitness, RelaxedR1CSInstance,
+  RelaxedR1CSWitness,
 };
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use supernova::StepCircuit;
 use traits::{
-  commitment::{CommitmentEngineTrait, CommitmentTrait},
+  commitment::{CommitmentEngineTrait, CommitmentTrait, Len},
   snark::RelaxedR1CSSNARKTrait,
   AbsorbInROTrait, CurveCycleEquipped, Dual, Engine, ROConstants, ROConstantsCircuit, ROTrait,
+  StepIndex,
 };
 
 use crate::{
@@ -78,6 +84,32 @@ impl<E: Engine> R1CSWithArity<E> {
   }
 }
 
+/// The number of constraints, variables and public inputs/outputs
+/// `(num_cons, num_vars, num_io)` of the primary and secondary circuits
+pub type CircuitSizes = ((usize, usize, usize), (usize, usize, usize));
+
+/// The version of the byte format of [`PublicParams::to_bytes`] and
+/// [`RecursiveSNARK::to_bytes`], bumped whenever that format changes
+pub const SERIALIZATION_VERSION: u8 = 1;
+
+/// Serializes `value` with bincode, preceded by [`SERIALIZATION_VERSION`]
+fn to_versioned_bytes<T: Serialize>(value: &T) -> Vec<u8> {
+  let mut bytes = vec![SERIALIZATION_VERSION];
+  bincode::serialize_into(&mut bytes, value).expect("value is serializable");
+  bytes
+}
+
+/// Inverse of [`to_versioned_bytes`], rejecting bytes of another version
+fn from_versioned_bytes<T: for<'de> Deserialize<'de>>(bytes: &[u8]) -> Result<T, NovaError> {
+  let (&found, bytes) = bytes
+    .split_first()
+    .ok_or_else(|| NovaError::DeserializationError("missing version byte".to_string()))?;
+  if found != SERIALIZATION_VERSION {
+    return Err(NovaError::IncompatibleVersion { found, expected: SERIALIZATION_VERSION });
+  }
+  bincode::deserialize(bytes).map_err(|e| NovaError::DeserializationError(e.to_string()))
+}
+
 /// A type that holds public parameters of Nova
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(bound = "")]
@@ -101,6 +133,55 @@ where E: CurveCycleEquipped {
 
 impl<E1> SimpleDigestible for PublicParams<E1> where E1: CurveCycleEquipped {}
 
+/// Checks that `comms` lie in the prime-order subgroup, which only needs
+/// checking on curves with a cofactor. Every verifier runs this on the
+/// commitments it receives before folding or hashing them.
+fn check_in_subgroup<E: Engine>(comms: &[Commitment<E>]) -> Result<(), NovaError> {
+  if E::COFACTOR == 1 || comms.iter().all(|comm| comm.is_in_correct_subgroup()) {
+    Ok(())
+  } else {
+    Err(NovaError::InvalidCommitmentSubgroup)
+  }
+}
+
+/// Computes `H(pp_digest, num_steps, z0, zn, U)`, the hash an augmented circuit
+/// outputs over the running instance `U` of the other circuit, in the order
+/// given by [`NovaAugmentedCircuitInputs::layout`]
+fn io_hash<E: Engine>(
+  pp_digest: E::Base,
+  num_steps: usize,
+  z0: &[E::Base],
+  zn: &[E::Base],
+  U: &RelaxedR1CSInstance<E>,
+  ro_consts: &ROConstants<E>,
+  arity: usize,
+) -> Result<E::Scalar, NovaError> {
+  let mut hasher = E::RO::new(ro_consts.clone(), NUM_FE_WITHOUT_IO_FOR_CRHF + 2 * arity);
+  hasher.absorb(pp_digest);
+  AbsorbInROTrait::<E>::absorb_in_ro(&StepIndex(num_steps as u64), &mut hasher);
+  for e in z0 {
+    hasher.absorb(*e);
+  }
+  for e in zn {
+    hasher.absorb(*e);
+  }
+  U.absorb_in_ro(&mut hasher);
+  hasher.squeeze(NUM_HASH_BITS)
+}
+
+/// Checks that `ck` has a generator for each constraint and variable of `shape`
+fn check_size<E: Engine>(
+  shape: &R1CSShape<E>,
+  ck: Arc<CommitmentKey<E>>,
+) -> Result<Arc<CommitmentKey<E>>, NovaError> {
+  let needed = shape.num_cons.max(shape.num_vars);
+  let have = ck.length();
+  if have < needed {
+    return Err(NovaError::CommitmentKeyTooSmall { needed, have });
+  }
+  Ok(ck)
+}
+
 impl<E1> PublicParams<E1>
 where E1: CurveCycleEquipped
 {
@@ -159,6 +240,90 @@ where E1: CurveCycleEquipped
     ck_hint1: &CommitmentKeyHint<E1>,
     ck_hint2: &CommitmentKeyHint<Dual<E1>>,
   ) -> Result<Self, NovaError> {
+    Self::setup_inner(
+      c_primary,
+      c_secondary,
+      |shape| Ok(Arc::new(commitment_key(shape, ck_hint1))),
+      |shape| Ok(Arc::new(commitment_key(shape, ck_hint2))),
+    )
+  }
+
+  /// Create `PublicParams` for a pair of circuits `C1` and `C2` like
+  /// [`PublicParams::setup`], reusing the supplied commitment keys, e.g. from a
+  /// ceremony, instead of generating them.
+  ///
+  /// Returns [`NovaError::CommitmentKeyTooSmall`] if a key has fewer
+  /// generators than the number of constraints or variables of its circuit.
+  /// Keys for compressing SNARKs that use computation commitments must
+  /// additionally cover their `RelaxedR1CSSNARKTrait::ck_floor()`.
+  pub fn setup_with_keys<
+    C1: StepCircuit<E1::Scalar>,
+    C2: StepCircuit<<Dual<E1> as Engine>::Scalar>,
+  >(
+    c_primary: &C1,
+    c_secondary: &C2,
+    ck_primary: Arc<CommitmentKey<E1>>,
+    ck_secondary: Arc<CommitmentKey<Dual<E1>>>,
+  ) -> Result<Self, NovaError> {
+    Self::setup_inner(
+      c_primary,
+      c_secondary,
+      |shape| check_size(shape, ck_primary),
+      |shape| check_size(shape, ck_secondary),
+    )
+  }
+
+  /// Returns the sizes [`PublicParams::circuit_sizes`] reports for the
+  /// augmented circuits of `c_primary` and `c_secondary`, by synthesizing
+  /// their shapes only, without building the R1CS matrices or generating
+  /// commitment keys.
+  pub fn dry_run<C1: StepCircuit<E1::Scalar>, C2: StepCircuit<<Dual<E1> as Engine>::Scalar>>(
+    c_primary: &C1,
+    c_secondary: &C2,
+  ) -> CircuitSizes {
+    let augmented_circuit_params_primary =
+      NovaAugmentedCircuitParams::new(BN_LIMB_WIDTH, BN_N_LIMBS, true);
+    let augmented_circuit_params_secondary =
+      NovaAugmentedCircuitParams::new(BN_LIMB_WIDTH, BN_N_LIMBS, false);
+
+    let circuit_primary: NovaAugmentedCircuit<'_, Dual<E1>, C1> = NovaAugmentedCircuit::new(
+      &augmented_circuit_params_primary,
+      None,
+      c_primary,
+      ROConstantsCircuit::<Dual<E1>>::default(),
+    );
+    let mut cs_primary: ShapeCS<E1> = ShapeCS::new();
+    let _ = circuit_primary.synthesize(&mut cs_primary);
+
+    let circuit_secondary: NovaAugmentedCircuit<'_, E1, C2> = NovaAugmentedCircuit::new(
+      &augmented_circuit_params_secondary,
+      None,
+      c_secondary,
+      ROConstantsCircuit::<E1>::default(),
+    );
+    let mut cs_secondary: ShapeCS<Dual<E1>> = ShapeCS::new();
+    let _ = circuit_secondary.synthesize(&mut cs_secondary);
+
+    (
+      (cs_primary.num_cons(), cs_primary.num_vars(), cs_primary.num_io()),
+      (cs_secondary.num_cons(), cs_secondary.num_vars(), cs_secondary.num_io()),
+    )
+  }
+
+  /// Synthesize the shapes of the augmented circuits and obtain a commitment
+  /// key for each of them from `ck_primary` and `ck_secondary`
+  fn setup_inner<C1, C2, K1, K2>(
+    c_primary: &C1,
+    c_secondary: &C2,
+    ck_primary: K1,
+    ck_secondary: K2,
+  ) -> Result<Self, NovaError>
+  where
+    C1: StepCircuit<E1::Scalar>,
+    C2: StepCircuit<<Dual<E1> as Engine>::Scalar>,
+    K1: FnOnce(&R1CSShape<E1>) -> Result<Arc<CommitmentKey<E1>>, NovaError>,
+    K2: FnOnce(&R1CSShape<Dual<E1>>) -> Result<Arc<CommitmentKey<Dual<E1>>>, NovaError>,
+  {
     let augmented_circuit_params_primary =
       NovaAugmentedCircuitParams::new(BN_LIMB_WIDTH, BN_N_LIMBS, true);
     let augmented_circuit_params_secondary =
@@ -176,7 +341,7 @@ where E1: CurveCycleEquipped
       ROConstantsCircuit::<Dual<E1>>::default();
     let ro_consts_circuit_secondary: ROConstantsCircuit<E1> = ROConstantsCircuit::<E1>::default();
 
-    // Initialize ck for the primary
+    // Synthesize the shape of the primary
     let circuit_primary: NovaAugmentedCircuit<'_, Dual<E1>, C1> = NovaAugmentedCircuit::new(
       &augmented_circuit_params_primary,
       None,
@@ -185,10 +350,9 @@ where E1: CurveCycleEquipped
     );
     let mut cs: ShapeCS<E1> = ShapeCS::new();
     let _ = circuit_primary.synthesize(&mut cs);
-    let (r1cs_shape_primary, ck_primary) = cs.r1cs_shape_and_key(ck_hint1);
-    let ck_primary = Arc::new(ck_primary);
+    let r1cs_shape_primary = cs.r1cs_shape();
 
-    // Initialize ck for the secondary
+    // Synthesize the shape of the secondary
     let circuit_secondary: NovaAugmentedCircuit<'_, E1, C2> = NovaAugmentedCircuit::new(
       &augmented_circuit_params_secondary,
       None,
@@ -197,13 +361,15 @@ where E1: CurveCycleEquipped
     );
     let mut cs: ShapeCS<Dual<E1>> = ShapeCS::new();
     let _ = circuit_secondary.synthesize(&mut cs);
-    let (r1cs_shape_secondary, ck_secondary) = cs.r1cs_shape_and_key(ck_hint2);
-    let ck_secondary = Arc::new(ck_secondary);
+    let r1cs_shape_secondary = cs.r1cs_shape();
 
     if r1cs_shape_primary.num_io != 2 || r1cs_shape_secondary.num_io != 2 {
       return Err(NovaError::InvalidStepCircuitIO);
     }
 
+    let ck_primary = ck_primary(&r1cs_shape_primary)?;
+    let ck_secondary = ck_secondary(&r1cs_shape_secondary)?;
+
     let circuit_shape_primary = R1CSWithArity::new(r1cs_shape_primary, F_arity_primary);
     let circuit_shape_secondary = R1CSWithArity::new(r1cs_shape_secondary, F_arity_secondary);
 
@@ -225,6 +391,9 @@ where E1: CurveCycleEquipped
   }
 
   /// Retrieve the digest of the public parameters.
+  ///
+  /// The digest is computed on the first call and cached, so that later calls
+  /// are O(1); methods mutating the parameters reset the cache.
   pub fn digest(&self) -> E1::Scalar {
     self
       .digest
@@ -233,6 +402,33 @@ where E1: CurveCycleEquipped
       .expect("Failure in retrieving digest")
   }
 
+  /// Replace the commitment keys, e.g. with keys from a ceremony, resetting
+  /// the cached digest.
+  ///
+  /// Returns [`NovaError::CommitmentKeyTooSmall`], leaving `self` unchanged,
+  /// if a key has fewer generators than the number of constraints or
+  /// variables of its circuit.
+  pub fn set_commitment_keys(
+    &mut self,
+    ck_primary: Arc<CommitmentKey<E1>>,
+    ck_secondary: Arc<CommitmentKey<Dual<E1>>>,
+  ) -> Result<(), NovaError> {
+    let ck_primary = check_size(&self.circuit_shape_primary.r1cs_shape, ck_primary)?;
+    let ck_secondary = check_size(&self.circuit_shape_secondary.r1cs_shape, ck_secondary)?;
+    self.ck_primary = ck_primary;
+    self.ck_secondary = ck_secondary;
+    self.digest.take();
+    Ok(())
+  }
+
+  /// Serializes the parameters, preceded by [`SERIALIZATION_VERSION`]
+  pub fn to_bytes(&self) -> Vec<u8> { to_versioned_bytes(self) }
+
+  /// Deserializes parameters written by [`PublicParams::to_bytes`], returning
+  /// [`NovaError::IncompatibleVersion`] if they were written in another format
+  /// version
+  pub fn from_bytes(bytes: &[u8]) -> Result<Self, NovaError> { from_versioned_bytes(bytes) }
+
   /// Returns the number of constraints in the primary and secondary circuits
   pub const fn num_constraints(&self) -> (usize, usize) {
     (
@@ -248,6 +444,113 @@ where E1: CurveCycleEquipped
       self.circuit_shape_secondary.r1cs_shape.num_vars,
     )
   }
+
+  /// Returns the number of constraints, variables and public inputs/outputs
+  /// `(num_cons, num_vars, num_io)` of the primary and secondary circuits
+  pub const fn circuit_sizes(&self) -> CircuitSizes {
+    let primary = &self.circuit_shape_primary.r1cs_shape;
+    let secondary = &self.circuit_shape_secondary.r1cs_shape;
+    (
+      (primary.num_cons(), primary.num_vars(), primary.num_io()),
+      (secondary.num_cons(), secondary.num_vars(), secondary.num_io()),
+    )
+  }
+
+  /// Extract the [`RecursiveVerifierKey`] holding only what
+  /// [`RecursiveSNARK::verify`] reads, dropping the in-circuit RO constants
+  /// and augmented circuit parameters only a prover needs.
+  pub fn into_verifier_key(self) -> RecursiveVerifierKey<E1> {
+    RecursiveVerifierKey {
+      pp_digest:            self.digest(),
+      F_arity_primary:      self.F_arity_primary,
+      F_arity_secondary:    self.F_arity_secondary,
+      ro_consts_primary:    self.ro_consts_primary,
+      ro_consts_secondary:  self.ro_consts_secondary,
+      ck_primary:           self.ck_primary,
+      ck_secondary:         self.ck_secondary,
+      r1cs_shape_primary:   self.circuit_shape_primary.r1cs_shape,
+      r1cs_shape_secondary: self.circuit_shape_secondary.r1cs_shape,
+    }
+  }
+}
+
+/// The part of [`PublicParams`] needed to verify a [`RecursiveSNARK`].
+///
+/// Besides the digest of the parameters and the RO constants used to check
+/// the public IO, checking the running instances against their R1CS relation
+/// still takes both shapes and commitment keys.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct RecursiveVerifierKey<E1>
+where E1: CurveCycleEquipped {
+  pp_digest:            E1::Scalar,
+  F_arity_primary:      usize,
+  F_arity_secondary:    usize,
+  ro_consts_primary:    ROConstants<E1>,
+  ro_consts_secondary:  ROConstants<Dual<E1>>,
+  ck_primary:           Arc<CommitmentKey<E1>>,
+  ck_secondary:         Arc<CommitmentKey<Dual<E1>>>,
+  r1cs_shape_primary:   R1CSShape<E1>,
+  r1cs_shape_secondary: R1CSShape<Dual<E1>>,
+}
+
+/// The parameters [`RecursiveSNARK::verify`] checks a proof against,
+/// implemented by both [`PublicParams`] and [`RecursiveVerifierKey`]
+pub trait RecursiveVerifierParams<E1: CurveCycleEquipped> {
+  /// The digest of the public parameters the proof was produced with
+  fn pp_digest(&self) -> E1::Scalar;
+
+  /// The arities of the primary and secondary step circuits
+  fn F_arity(&self) -> (usize, usize);
+
+  /// The native RO constants of the primary and secondary circuits
+  fn ro_consts(&self) -> (&ROConstants<E1>, &ROConstants<Dual<E1>>);
+
+  /// The commitment keys of the primary and secondary circuits
+  fn cks(&self) -> (&CommitmentKey<E1>, &CommitmentKey<Dual<E1>>);
+
+  /// The R1CS shapes of the primary and secondary augmented circuits
+  fn r1cs_shapes(&self) -> (&R1CSShape<E1>, &R1CSShape<Dual<E1>>);
+}
+
+impl<E1> RecursiveVerifierParams<E1> for PublicParams<E1>
+where E1: CurveCycleEquipped
+{
+  fn pp_digest(&self) -> E1::Scalar { self.digest() }
+
+  fn F_arity(&self) -> (usize, usize) { (self.F_arity_primary, self.F_arity_secondary) }
+
+  fn ro_consts(&self) -> (&ROConstants<E1>, &ROConstants<Dual<E1>>) {
+    (&self.ro_consts_primary, &self.ro_consts_secondary)
+  }
+
+  fn cks(&self) -> (&CommitmentKey<E1>, &CommitmentKey<Dual<E1>>) {
+    (&self.ck_primary, &self.ck_secondary)
+  }
+
+  fn r1cs_shapes(&self) -> (&R1CSShape<E1>, &R1CSShape<Dual<E1>>) {
+    (&self.circuit_shape_primary.r1cs_shape, &self.circuit_shape_secondary.r1cs_shape)
+  }
+}
+
+impl<E1> RecursiveVerifierParams<E1> for RecursiveVerifierKey<E1>
+where E1: CurveCycleEquipped
+{
+  fn pp_digest(&self) -> E1::Scalar { self.pp_digest }
+
+  fn F_arity(&self) -> (usize, usize) { (self.F_arity_primary, self.F_arity_secondary) }
+
+  fn ro_consts(&self) -> (&ROConstants<E1>, &ROConstants<Dual<E1>>) {
+    (&self.ro_consts_primary, &self.ro_consts_secondary)
+  }
+
+  fn cks(&self) -> (&CommitmentKey<E1>, &CommitmentKey<Dual<E1>>) {
+    (&self.ck_primary, &self.ck_secondary)
+  }
+
+  fn r1cs_shapes(&self) -> (&R1CSShape<E1>, &R1CSShape<Dual<E1>>) {
+    (&self.r1cs_shape_primary, &self.r1cs_shape_secondary)
+  }
 }
 
 /// A resource buffer for [`RecursiveSNARK`] for storing scratch values that are
@@ -288,6 +591,11 @@ where E1: CurveCycleEquipped {
   i:            usize,
   zi_primary:   Vec<E1::Scalar>,
   zi_secondary: Vec<<Dual<E1> as Engine>::Scalar>,
+
+  /// Cross-term commitments of the primary and secondary folds of the last
+  /// step, kept for auditing rather than serialized
+  #[serde(skip)]
+  comm_T: Option<(Commitment<E1>, Commitment<Dual<E1>>)>,
 }
 
 impl<E1> RecursiveSNARK<E1>
@@ -413,6 +721,7 @@ where E1: CurveCycleEquipped
       i: 0,
       zi_primary,
       zi_secondary,
+      comm_T: None,
     })
   }
 
@@ -422,14 +731,71 @@ where E1: CurveCycleEquipped
   /// Outputs of the primary circuits
   pub fn zi_primary(&self) -> &Vec<E1::Scalar> { &self.zi_primary }
 
+  /// The commitments to the cross-terms `T` of the primary and secondary folds
+  /// of the last step, from which a verifier outside this crate can recompute
+  /// their challenges with [`NIFS::verify_with_comm_T`]. This is `None` before
+  /// the first fold, and on a `RecursiveSNARK` that was deserialized.
+  pub fn comm_T(&self) -> Option<(Commitment<E1>, Commitment<Dual<E1>>)> { self.comm_T }
+
   /// Create a new `RecursiveSNARK` (or updates the provided `RecursiveSNARK`)
   /// by executing a step of the incremental computation
+  ///
+  /// No satisfiability check is run on the folded instances: a step whose
+  /// circuit is unsatisfied still folds, and is only rejected by
+  /// [`RecursiveSNARK::verify`].
+  ///
+  /// With the `parallel-paths` feature each witness fold overlaps the synthesis of
+  /// the other circuit. The resulting proof is the same either way.
   #[tracing::instrument(skip_all, name = "nova::RecursiveSNARK::prove_step")]
   pub fn prove_step<C1: StepCircuit<E1::Scalar>, C2: StepCircuit<<Dual<E1> as Engine>::Scalar>>(
     &mut self,
     pp: &PublicParams<E1>,
     c_primary: &C1,
     c_secondary: &C2,
+  ) -> Result<(), NovaError> {
+    self.prove_step_with(pp, c_primary, c_secondary, cfg!(feature = "parallel-paths"))
+  }
+
+  /// Create a `RecursiveSNARK` and fold one step for each primary circuit
+  /// drawn from `inputs`, where each circuit carries the private input of its
+  /// step. Inputs are drawn lazily, so they can be generated while folding.
+  /// If set, `progress` is called after each step with the number of steps
+  /// folded so far. Returns `NovaError::InvalidNumSteps` if `inputs` is
+  /// empty.
+  pub fn fold_stream<C1, C2, I>(
+    pp: &PublicParams<E1>,
+    c_secondary: &C2,
+    z0_primary: &[E1::Scalar],
+    z0_secondary: &[<Dual<E1> as Engine>::Scalar],
+    inputs: I,
+    mut progress: Option<&mut dyn FnMut(usize)>,
+  ) -> Result<Self, NovaError>
+  where
+    C1: StepCircuit<E1::Scalar>,
+    C2: StepCircuit<<Dual<E1> as Engine>::Scalar>,
+    I: IntoIterator<Item = C1>,
+  {
+    let mut inputs = inputs.into_iter();
+    let c_primary = inputs.next().ok_or(NovaError::InvalidNumSteps)?;
+    let mut recursive_snark = Self::new(pp, &c_primary, c_secondary, z0_primary, z0_secondary)?;
+
+    for c_primary in std::iter::once(c_primary).chain(inputs) {
+      recursive_snark.prove_step(pp, &c_primary, c_secondary)?;
+      if let Some(progress) = progress.as_mut() {
+        progress(recursive_snark.num_steps());
+      }
+    }
+    Ok(recursive_snark)
+  }
+
+  /// Same as [`RecursiveSNARK::prove_step`], with the overlap of each
+  /// witness fold and the synthesis of the other circuit chosen at runtime.
+  fn prove_step_with<C1: StepCircuit<E1::Scalar>, C2: StepCircuit<<Dual<E1> as Engine>::Scalar>>(
+    &mut self,
+    pp: &PublicParams<E1>,
+    c_primary: &C1,
+    c_secondary: &C2,
+    parallel: bool,
   ) -> Result<(), NovaError> {
     // first step was already done in the constructor
     if self.i == 0 {
@@ -442,111 +808,140 @@ where E1: CurveCycleEquipped
     let r_U_secondary_i = self.r_U_secondary.clone();
     let l_u_secondary_i = self.l_u_secondary.clone();
 
-    // fold the secondary circuit's instance
-    let (nifs_secondary, _) = NIFS::prove_mut(
+    // fold the secondary circuit's instance; its witness is folded below,
+    // alongside the synthesis of the primary circuit
+    let (nifs_secondary, r_secondary) = NIFS::prove_instance_mut(
       &*pp.ck_secondary,
       &pp.ro_consts_secondary,
       &scalar_as_base::<E1>(pp.digest()),
       &pp.circuit_shape_secondary.r1cs_shape,
       &mut self.r_U_secondary,
-      &mut self.r_W_secondary,
+      &self.r_W_secondary,
       &self.l_u_secondary,
       &self.l_w_secondary,
       &mut self.buffer_secondary.T,
       &mut self.buffer_secondary.ABC_Z_1,
       &mut self.buffer_secondary.ABC_Z_2,
     )?;
+    let comm_T_secondary = Commitment::<Dual<E1>>::decompress(&nifs_secondary.comm_T)?;
 
-    let mut cs_primary = SatisfyingAssignment::<E1>::with_capacity(
-      pp.circuit_shape_primary.r1cs_shape.num_io + 1,
-      pp.circuit_shape_primary.r1cs_shape.num_vars,
-    );
-    let inputs_primary: NovaAugmentedCircuitInputs<Dual<E1>> = NovaAugmentedCircuitInputs::new(
-      scalar_as_base::<E1>(pp.digest()),
-      E1::Scalar::from(self.i as u64),
-      self.z0_primary.to_vec(),
-      Some(self.zi_primary.clone()),
-      Some(r_U_secondary_i),
-      Some(l_u_secondary_i),
-      Some(Commitment::<Dual<E1>>::decompress(&nifs_secondary.comm_T)?),
-    );
-
-    let circuit_primary: NovaAugmentedCircuit<'_, Dual<E1>, C1> = NovaAugmentedCircuit::new(
-      &pp.augmented_circuit_params_primary,
-      Some(inputs_primary),
-      c_primary,
-      pp.ro_consts_circuit_primary.clone(),
+    let (res_fold_secondary, res_primary) = join(
+      parallel,
+      || self.r_W_secondary.fold_mut(&self.l_w_secondary, &self.buffer_secondary.T, &r_secondary),
+      || {
+        let mut cs_primary = SatisfyingAssignment::<E1>::with_capacity(
+          pp.circuit_shape_primary.r1cs_shape.num_io + 1,
+          pp.circuit_shape_primary.r1cs_shape.num_vars,
+        );
+        let inputs_primary: NovaAugmentedCircuitInputs<Dual<E1>> = NovaAugmentedCircuitInputs::new(
+          scalar_as_base::<E1>(pp.digest()),
+          StepIndex(self.i as u64).to_field(),
+          self.z0_primary.to_vec(),
+          Some(self.zi_primary.clone()),
+          Some(r_U_secondary_i),
+          Some(l_u_secondary_i),
+          Some(comm_T_secondary),
+        );
+
+        let circuit_primary: NovaAugmentedCircuit<'_, Dual<E1>, C1> = NovaAugmentedCircuit::new(
+          &pp.augmented_circuit_params_primary,
+          Some(inputs_primary),
+          c_primary,
+          pp.ro_consts_circuit_primary.clone(),
+        );
+
+        let zi_primary = circuit_primary
+          .synthesize(&mut cs_primary)?
+          .iter()
+          .map(|v| v.get_value().ok_or(SynthesisError::AssignmentMissing))
+          .collect::<Result<Vec<<E1 as Engine>::Scalar>, _>>()?;
+
+        let (l_u_primary, l_w_primary) = cs_primary
+          .r1cs_instance_and_witness(&pp.circuit_shape_primary.r1cs_shape, &pp.ck_primary)?;
+        Ok::<_, NovaError>((zi_primary, l_u_primary, l_w_primary))
+      },
     );
+    res_fold_secondary?;
+    let (zi_primary, l_u_primary, l_w_primary) = res_primary?;
 
-    let zi_primary = circuit_primary.synthesize(&mut cs_primary)?;
-
-    let (l_u_primary, l_w_primary) =
-      cs_primary.r1cs_instance_and_witness(&pp.circuit_shape_primary.r1cs_shape, &pp.ck_primary)?;
-
-    // fold the primary circuit's instance
-    let (nifs_primary, _) = NIFS::prove_mut(
+    // fold the primary circuit's instance; its witness is folded below,
+    // alongside the synthesis of the secondary circuit
+    let (nifs_primary, r_primary) = NIFS::prove_instance_mut(
       &*pp.ck_primary,
       &pp.ro_consts_primary,
       &pp.digest(),
       &pp.circuit_shape_primary.r1cs_shape,
       &mut self.r_U_primary,
-      &mut self.r_W_primary,
+      &self.r_W_primary,
       &l_u_primary,
       &l_w_primary,
       &mut self.buffer_primary.T,
       &mut self.buffer_primary.ABC_Z_1,
       &mut self.buffer_primary.ABC_Z_2,
     )?;
+    let comm_T_primary = Commitment::<E1>::decompress(&nifs_primary.comm_T)?;
 
-    let mut cs_secondary = SatisfyingAssignment::<Dual<E1>>::with_capacity(
-      pp.circuit_shape_secondary.r1cs_shape.num_io + 1,
-      pp.circuit_shape_secondary.r1cs_shape.num_vars,
-    );
-    let inputs_secondary: NovaAugmentedCircuitInputs<E1> = NovaAugmentedCircuitInputs::new(
-      pp.digest(),
-      <Dual<E1> as Engine>::Scalar::from(self.i as u64),
-      self.z0_secondary.to_vec(),
-      Some(self.zi_secondary.clone()),
-      Some(r_U_primary_i),
-      Some(l_u_primary),
-      Some(Commitment::<E1>::decompress(&nifs_primary.comm_T)?),
-    );
-
-    let circuit_secondary: NovaAugmentedCircuit<'_, E1, C2> = NovaAugmentedCircuit::new(
-      &pp.augmented_circuit_params_secondary,
-      Some(inputs_secondary),
-      c_secondary,
-      pp.ro_consts_circuit_secondary.clone(),
+    let (res_fold_primary, res_secondary) = join(
+      parallel,
+      || self.r_W_primary.fold_mut(&l_w_primary, &self.buffer_primary.T, &r_primary),
+      || {
+        let mut cs_secondary = SatisfyingAssignment::<Dual<E1>>::with_capacity(
+          pp.circuit_shape_secondary.r1cs_shape.num_io + 1,
+          pp.circuit_shape_secondary.r1cs_shape.num_vars,
+        );
+        let inputs_secondary: NovaAugmentedCircuitInputs<E1> = NovaAugmentedCircuitInputs::new(
+          pp.digest(),
+          StepIndex(self.i as u64).to_field(),
+          self.z0_secondary.to_vec(),
+          Some(self.zi_secondary.clone()),
+          Some(r_U_primary_i),
+          Some(l_u_primary),
+          Some(comm_T_primary),
+        );
+
+        let circuit_secondary: NovaAugmentedCircuit<'_, E1, C2> = NovaAugmentedCircuit::new(
+          &pp.augmented_circuit_params_secondary,
+          Some(inputs_secondary),
+          c_secondary,
+          pp.ro_consts_circuit_secondary.clone(),
+        );
+        let zi_secondary = circuit_secondary
+          .synthesize(&mut cs_secondary)?
+          .iter()
+          .map(|v| v.get_value().ok_or(SynthesisError::AssignmentMissing))
+          .collect::<Result<Vec<<Dual<E1> as Engine>::Scalar>, _>>()?;
+
+        let (l_u_secondary, l_w_secondary) = cs_secondary
+          .r1cs_instance_and_witness(&pp.circuit_shape_secondary.r1cs_shape, &pp.ck_secondary)
+          .map_err(|_e| NovaError::UnSat)?;
+        Ok::<_, NovaError>((zi_secondary, l_u_secondary, l_w_secondary))
+      },
     );
-    let zi_secondary = circuit_secondary.synthesize(&mut cs_secondary)?;
-
-    let (l_u_secondary, l_w_secondary) = cs_secondary
-      .r1cs_instance_and_witness(&pp.circuit_shape_secondary.r1cs_shape, &pp.ck_secondary)
-      .map_err(|_e| NovaError::UnSat)?;
+    res_fold_primary?;
+    let (zi_secondary, l_u_secondary, l_w_secondary) = res_secondary?;
 
     // update the running instances and witnesses
-    self.zi_primary = zi_primary
-      .iter()
-      .map(|v| v.get_value().ok_or(SynthesisError::AssignmentMissing))
-      .collect::<Result<Vec<<E1 as Engine>::Scalar>, _>>()?;
-    self.zi_secondary = zi_secondary
-      .iter()
-      .map(|v| v.get_value().ok_or(SynthesisError::AssignmentMissing))
-      .collect::<Result<Vec<<Dual<E1> as Engine>::Scalar>, _>>()?;
+    self.zi_primary = zi_primary;
+    self.zi_secondary = zi_secondary;
 
     self.l_u_secondary = l_u_secondary;
     self.l_w_secondary = l_w_secondary;
 
+    self.comm_T = Some((comm_T_primary, comm_T_secondary));
     self.i += 1;
 
     Ok(())
   }
 
-  /// Verify the correctness of the `RecursiveSNARK`
+  /// Verify the correctness of the `RecursiveSNARK` against either the full
+  /// [`PublicParams`] or the [`RecursiveVerifierKey`] extracted from them
+  ///
+  /// On success, returns the primary and secondary outputs `z_i` that were
+  /// verified, i.e. the same values as [`RecursiveSNARK::outputs`].
   #[allow(clippy::type_complexity)]
-  pub fn verify(
+  pub fn verify<P: RecursiveVerifierParams<E1>>(
     &self,
-    pp: &PublicParams<E1>,
+    pp: &P,
     num_steps: usize,
     z0_primary: &[E1::Scalar],
     z0_secondary: &[<Dual<E1> as Engine>::Scalar],
@@ -573,79 +968,84 @@ where E1: CurveCycleEquipped
       return Err(NovaError::ProofVerifyError);
     }
 
-    // check if the output hashes in R1CS instances point to the right running
-    // instances
-    let (hash_primary, hash_secondary) = {
-      let mut hasher = <Dual<E1> as Engine>::RO::new(
-        pp.ro_consts_secondary.clone(),
-        NUM_FE_WITHOUT_IO_FOR_CRHF + 2 * pp.F_arity_primary,
-      );
-      hasher.absorb(pp.digest());
-      hasher.absorb(E1::Scalar::from(num_steps as u64));
-      for e in z0_primary {
-        hasher.absorb(*e);
-      }
-      for e in &self.zi_primary {
-        hasher.absorb(*e);
-      }
-      self.r_U_secondary.absorb_in_ro(&mut hasher);
+    // check that the commitments lie in the prime-order subgroup
+    check_in_subgroup::<E1>(&[self.r_U_primary.comm_W, self.r_U_primary.comm_E])?;
+    check_in_subgroup::<Dual<E1>>(&[
+      self.r_U_secondary.comm_W,
+      self.r_U_secondary.comm_E,
+      self.l_u_secondary.comm_W,
+    ])?;
 
-      let mut hasher2 = <E1 as Engine>::RO::new(
-        pp.ro_consts_primary.clone(),
-        NUM_FE_WITHOUT_IO_FOR_CRHF + 2 * pp.F_arity_secondary,
-      );
-      hasher2.absorb(scalar_as_base::<E1>(pp.digest()));
-      hasher2.absorb(<Dual<E1> as Engine>::Scalar::from(num_steps as u64));
-      for e in z0_secondary {
-        hasher2.absorb(*e);
-      }
-      for e in &self.zi_secondary {
-        hasher2.absorb(*e);
-      }
-      self.r_U_primary.absorb_in_ro(&mut hasher2);
+    let (F_arity_primary, F_arity_secondary) = pp.F_arity();
+    let (ro_consts_primary, ro_consts_secondary) = pp.ro_consts();
+    let (ck_primary, ck_secondary) = pp.cks();
+    let (r1cs_shape_primary, r1cs_shape_secondary) = pp.r1cs_shapes();
 
-      (hasher.squeeze(NUM_HASH_BITS), hasher2.squeeze(NUM_HASH_BITS))
-    };
+    // check if the output hashes in R1CS instances point to the right running
+    // instances
+    let hash_primary = io_hash(
+      pp.pp_digest(),
+      num_steps,
+      z0_primary,
+      &self.zi_primary,
+      &self.r_U_secondary,
+      ro_consts_secondary,
+      F_arity_primary,
+    )?;
+    let hash_secondary = io_hash(
+      scalar_as_base::<E1>(pp.pp_digest()),
+      num_steps,
+      z0_secondary,
+      &self.zi_secondary,
+      &self.r_U_primary,
+      ro_consts_primary,
+      F_arity_secondary,
+    )?;
 
-    if hash_primary != self.l_u_secondary.X[0]
-      || hash_secondary != scalar_as_base::<Dual<E1>>(self.l_u_secondary.X[1])
-    {
-      return Err(NovaError::ProofVerifyError);
+    if hash_primary != self.l_u_secondary.X[0] {
+      return Err(NovaError::UnSatCircuit {
+        which: CircuitKind::Primary,
+        check: UnSatCheck::IO,
+        row:   None,
+      });
+    }
+    if hash_secondary != scalar_as_base::<Dual<E1>>(self.l_u_secondary.X[1]) {
+      return Err(NovaError::UnSatCircuit {
+        which: CircuitKind::Secondary,
+        check: UnSatCheck::IO,
+        row:   None,
+      });
     }
 
     // check the satisfiability of the provided instances
     let (res_r_primary, (res_r_secondary, res_l_secondary)) = rayon::join(
-      || {
-        pp.circuit_shape_primary.r1cs_shape.is_sat_relaxed(
-          &pp.ck_primary,
-          &self.r_U_primary,
-          &self.r_W_primary,
-        )
-      },
+      || r1cs_shape_primary.is_sat_relaxed(ck_primary, &self.r_U_primary, &self.r_W_primary),
       || {
         rayon::join(
           || {
-            pp.circuit_shape_secondary.r1cs_shape.is_sat_relaxed(
-              &pp.ck_secondary,
+            r1cs_shape_secondary.is_sat_relaxed(
+              ck_secondary,
               &self.r_U_secondary,
               &self.r_W_secondary,
             )
           },
-          || {
-            pp.circuit_shape_secondary.r1cs_shape.is_sat(
-              &pp.ck_secondary,
-              &self.l_u_secondary,
-              &self.l_w_secondary,
-            )
-          },
+          || r1cs_shape_secondary.is_sat(ck_secondary, &self.l_u_secondary, &self.l_w_secondary),
         )
       },
     );
 
-    // check the returned res objects
-    res_r_primary?;
-    res_r_secondary?;
-    res_l_secondary?;
+    // check the returned res objects, reporting an unsatisfied row with the
+    // circuit it belongs to and passing any other error through
+    let unsat = |which: CircuitKind| {
+      move |err: NovaError| match err {
+        NovaError::UnSatIndex(_, row) =>
+          NovaError::UnSatCircuit { which, check: UnSatCheck::Relation, row: Some(row) },
+        err => err,
+      }
+    };
+    res_r_primary.map_err(unsat(CircuitKind::Primary))?;
+    res_r_secondary.map_err(unsat(CircuitKind::Secondary))?;
+    res_l_secondary.map_err(unsat(CircuitKind::Secondary))?;
 
     Ok((self.zi_primary.clone(), self.zi_secondary.clone()))
   }
@@ -657,6 +1057,48 @@ where E1: CurveCycleEquipped
 
   /// The number of steps which have been executed thus far.
   pub fn num_steps(&self) -> usize { self.i }
+
+  /// The running relaxed R1CS instance of the primary circuit.
+  pub fn primary_instance(&self) -> &RelaxedR1CSInstance<E1> { &self.r_U_primary }
+
+  /// The running relaxed R1CS instance of the secondary circuit.
+  pub fn secondary_instance(&self) -> &RelaxedR1CSInstance<Dual<E1>> { &self.r_U_secondary }
+
+  /// Snapshot the `RecursiveSNARK` together with the digest of `pp`, so that
+  /// the computation can be continued later with [`RecursiveSNARK::resume`].
+  pub fn checkpoint(&self, pp: &PublicParams<E1>) -> RecursiveSNARKCheckpoint<E1> {
+    RecursiveSNARKCheckpoint { pp_digest: pp.digest(), recursive_snark: self.clone() }
+  }
+
+  /// Restore a `RecursiveSNARK` from a checkpoint, rejecting it if it was made
+  /// with public parameters other than `pp`.
+  pub fn resume(
+    pp: &PublicParams<E1>,
+    checkpoint: RecursiveSNARKCheckpoint<E1>,
+  ) -> Result<Self, NovaError> {
+    if checkpoint.pp_digest != pp.digest() {
+      return Err(NovaError::CheckpointDigestMismatch);
+    }
+    Ok(checkpoint.recursive_snark)
+  }
+
+  /// Serializes the `RecursiveSNARK`, preceded by [`SERIALIZATION_VERSION`]
+  pub fn to_bytes(&self) -> Vec<u8> { to_versioned_bytes(self) }
+
+  /// Deserializes a `RecursiveSNARK` written by [`RecursiveSNARK::to_bytes`],
+  /// returning [`NovaError::IncompatibleVersion`] if it was written in another
+  /// format version
+  pub fn from_bytes(bytes: &[u8]) -> Result<Self, NovaError> { from_versioned_bytes(bytes) }
+}
+
+/// A serializable snapshot of a [`RecursiveSNARK`], bound to the digest of the
+/// [`PublicParams`] it was produced with
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct RecursiveSNARKCheckpoint<E1>
+where E1: CurveCycleEquipped {
+  pp_digest:       E1::Scalar,
+  recursive_snark: RecursiveSNARK<E1>,
 }
 
 /// A type that holds the prover key for `CompressedSNARK`
@@ -705,6 +1147,18 @@ where
 
   zn_primary:   Vec<E1::Scalar>,
   zn_secondary: Vec<<Dual<E1> as Engine>::Scalar>,
+
+  pp_digest: E1::Scalar,
+}
+
+impl<E1, S1, S2> VerifierKey<E1, S1, S2>
+where
+  E1: CurveCycleEquipped,
+  S1: RelaxedR1CSSNARKTrait<E1>,
+  S2: RelaxedR1CSSNARKTrait<Dual<E1>>,
+{
+  /// The digest of the [`PublicParams`] this key was generated from.
+  pub fn pp_digest(&self) -> E1::Scalar { self.pp_digest }
 }
 
 impl<E1, S1, S2> CompressedSNARK<E1, S1, S2>
@@ -789,9 +1243,21 @@ where
 
       zn_primary: recursive_snark.zi_primary.clone(),
       zn_secondary: recursive_snark.zi_secondary.clone(),
+
+      pp_digest: pp.digest(),
     })
   }
 
+  /// Returns the size in bytes of the bincode serialization of the proof
+  pub fn size_bytes(&self) -> usize {
+    bincode::serialized_size(self).expect("CompressedSNARK is serializable") as usize
+  }
+
+  /// Returns the size in bytes of the bincode serialization of `vk`
+  pub fn vk_size_bytes(vk: &VerifierKey<E1, S1, S2>) -> usize {
+    bincode::serialized_size(vk).expect("VerifierKey is serializable") as usize
+  }
+
   #[allow(clippy::type_complexity)]
   /// Verify the correctness of the `CompressedSNARK`
   pub fn verify(
@@ -801,6 +1267,11 @@ where
     z0_primary: &[E1::Scalar],
     z0_secondary: &[<Dual<E1> as Engine>::Scalar],
   ) -> Result<(Vec<E1::Scalar>, Vec<<Dual<E1> as Engine>::Scalar>), NovaError> {
+    // the proof must have been produced with the parameters behind `vk`
+    if self.pp_digest != vk.pp_digest {
+      return Err(NovaError::ProofVerifyError);
+    }
+
     // the number of steps cannot be zero
     if num_steps == 0 {
       return Err(NovaError::ProofVerifyError);
@@ -814,39 +1285,36 @@ where
       return Err(NovaError::ProofVerifyError);
     }
 
+    // check that the commitments, including the cross-term of the secondary
+    // fold, lie in the prime-order subgroup
+    check_in_subgroup::<E1>(&[self.r_U_primary.comm_W, self.r_U_primary.comm_E])?;
+    check_in_subgroup::<Dual<E1>>(&[
+      self.r_U_secondary.comm_W,
+      self.r_U_secondary.comm_E,
+      self.l_u_secondary.comm_W,
+      Commitment::<Dual<E1>>::decompress(&self.nifs_secondary.comm_T)?,
+    ])?;
+
     // check if the output hashes in R1CS instances point to the right running
     // instances
-    let (hash_primary, hash_secondary) = {
-      let mut hasher = <Dual<E1> as Engine>::RO::new(
-        vk.ro_consts_secondary.clone(),
-        NUM_FE_WITHOUT_IO_FOR_CRHF + 2 * vk.F_arity_primary,
-      );
-      hasher.absorb(vk.pp_digest);
-      hasher.absorb(E1::Scalar::from(num_steps as u64));
-      for e in z0_primary {
-        hasher.absorb(*e);
-      }
-      for e in &self.zn_primary {
-        hasher.absorb(*e);
-      }
-      self.r_U_secondary.absorb_in_ro(&mut hasher);
-
-      let mut hasher2 = <E1 as Engine>::RO::new(
-        vk.ro_consts_primary.clone(),
-        NUM_FE_WITHOUT_IO_FOR_CRHF + 2 * vk.F_arity_secondary,
-      );
-      hasher2.absorb(scalar_as_base::<E1>(vk.pp_digest));
-      hasher2.absorb(<Dual<E1> as Engine>::Scalar::from(num_steps as u64));
-      for e in z0_secondary {
-        hasher2.absorb(*e);
-      }
-      for e in &self.zn_secondary {
-        hasher2.absorb(*e);
-      }
-      self.r_U_primary.absorb_in_ro(&mut hasher2);
-
-      (hasher.squeeze(NUM_HASH_BITS), hasher2.squeeze(NUM_HASH_BITS))
-    };
+    let hash_primary = io_hash(
+      vk.pp_digest,
+      num_steps,
+      z0_primary,
+      &self.zn_primary,
+      &self.r_U_secondary,
+      &vk.ro_consts_secondary,
+      vk.F_arity_primary,
+    )?;
+    let hash_secondary = io_hash(
+      scalar_as_base::<E1>(vk.pp_digest),
+      num_steps,
+      z0_secondary,
+      &self.zn_secondary,
+      &self.r_U_primary,
+      &vk.ro_consts_primary,
+      vk.F_arity_secondary,
+    )?;
 
     if hash_primary != self.l_u_secondary.X[0]
       || hash_secondary != scalar_as_base::<Dual<E1>>(self.l_u_secondary.X[1])
@@ -875,6 +1343,23 @@ where
 
     Ok((self.zn_primary.clone(), self.zn_secondary.clone()))
   }
+
+  /// Verify a batch of independent `CompressedSNARK`s against the same `vk`.
+  /// Each entry holds a proof with its number of steps and initial inputs.
+  /// The batch fails if any of its proofs is invalid.
+  ///
+  /// The proofs are not combined with random weights: this runs the full
+  /// [`CompressedSNARK::verify`] on each proof, one proof per rayon task, so
+  /// the cost is that of verifying them one at a time, spread over the
+  /// thread pool.
+  pub fn batch_verify(
+    vk: &VerifierKey<E1, S1, S2>,
+    proofs: &[(Self, usize, Vec<E1::Scalar>, Vec<<Dual<E1> as Engine>::Scalar>)],
+  ) -> Result<(), NovaError> {
+    proofs.par_iter().try_for_each(|(snark, num_steps, z0_primary, z0_secondary)| {
+      snark.verify(vk, *num_steps, z0_primary, z0_secondary).map(|_| ())
+    })
+  }
 }
 
 /// Compute the circuit digest of a [`StepCircuit`].
@@ -899,11 +1384,706 @@ pub fn circuit_digest<E1: CurveCycleEquipped, C: StepCircuit<E1::Scalar>>(
   cs.r1cs_shape().digest()
 }
 
+/// Runs `a` and `b` on separate rayon tasks if `parallel` is set, and one after
+/// the other otherwise. The results are the same either way.
+fn join<A, B, RA, RB>(parallel: bool, a: A, b: B) -> (RA, RB)
+where
+  A: FnOnce() -> RA + Send,
+  B: FnOnce() -> RB + Send,
+  RA: Send,
+  RB: Send, {
+  if parallel {
+    rayon::join(a, b)
+  } else {
+    (a(), b())
+  }
+}
+
 pub type CommitmentKey<E> = <<E as Engine>::CE as CommitmentEngineTrait<E>>::CommitmentKey;
 type Commitment<E> = <<E as Engine>::CE as CommitmentEngineTrait<E>>::Commitment;
 type CompressedCommitment<E> = <<<E as Engine>::CE as CommitmentEngineTrait<E>>::Commitment as CommitmentTrait<E>>::CompressedCommitment;
 type CE<E> = <E as Engine>::CE;
 
+#[cfg(test)]
+mod tests {
+  use core::marker::PhantomData;
+
+  use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+  use ff::PrimeField;
+
+  use super::*;
+  use crate::{
+    provider::{Bn256EngineIPA, GrumpkinEngine},
+    supernova::circuit::TrivialCircuit,
+    traits::snark::default_ck_hint,
+  };
+
+  /// A step circuit that drops the last element of `z`, so it returns one
+  /// output fewer than its arity
+  #[derive(Clone, Debug, Default)]
+  struct TruncatingCircuit<F> {
+    _p: PhantomData<F>,
+  }
+
+  impl<F: PrimeField> StepCircuit<F> for TruncatingCircuit<F> {
+    fn arity(&self) -> usize { 2 }
+
+    fn circuit_index(&self) -> usize { 0 }
+
+    fn synthesize<CS: ConstraintSystem<F>>(
+      &self,
+      _cs: &mut CS,
+      pc: Option<&AllocatedNum<F>>,
+      z: &[AllocatedNum<F>],
+    ) -> Result<(Option<AllocatedNum<F>>, Vec<AllocatedNum<F>>), SynthesisError> {
+      Ok((pc.cloned(), z[..1].to_vec()))
+    }
+  }
+
+  /// A step circuit with a constraint no assignment satisfies
+  #[derive(Clone, Debug, Default)]
+  struct UnsatisfiableCircuit<F> {
+    _p: PhantomData<F>,
+  }
+
+  impl<F: PrimeField> StepCircuit<F> for UnsatisfiableCircuit<F> {
+    fn arity(&self) -> usize { 1 }
+
+    fn circuit_index(&self) -> usize { 0 }
+
+    fn synthesize<CS: ConstraintSystem<F>>(
+      &self,
+      cs: &mut CS,
+      pc: Option<&AllocatedNum<F>>,
+      z: &[AllocatedNum<F>],
+    ) -> Result<(Option<AllocatedNum<F>>, Vec<AllocatedNum<F>>), SynthesisError> {
+      cs.enforce(|| "1 * 1 = 0", |lc| lc + CS::one(), |lc| lc + CS::one(), |lc| lc);
+      Ok((pc.cloned(), z.to_vec()))
+    }
+  }
+
+  /// Sets up the parameters of two `TrivialCircuit`s and starts a
+  /// `RecursiveSNARK` on them from `z0 = [1]` on both sides, returned along
+  /// with the parameters and the initial inputs
+  fn trivial_snark<E1: CurveCycleEquipped>(
+  ) -> (PublicParams<E1>, RecursiveSNARK<E1>, Vec<E1::Scalar>, Vec<<Dual<E1> as Engine>::Scalar>)
+  {
+    let c_primary = TrivialCircuit::<E1::Scalar>::default();
+    let c_secondary = TrivialCircuit::<<Dual<E1> as Engine>::Scalar>::default();
+    let pp =
+      PublicParams::<E1>::setup(&c_primary, &c_secondary, &*default_ck_hint(), &*default_ck_hint())
+        .unwrap();
+    let z0_primary = vec![E1::Scalar::from(1u64)];
+    let z0_secondary = vec![<Dual<E1> as Engine>::Scalar::from(1u64)];
+    let recursive_snark =
+      RecursiveSNARK::new(&pp, &c_primary, &c_secondary, &z0_primary, &z0_secondary).unwrap();
+    (pp, recursive_snark, z0_primary, z0_secondary)
+  }
+
+  fn test_prove_step_parallel_determinism_with<E1: CurveCycleEquipped>() {
+    let c_primary = TrivialCircuit::<E1::Scalar>::default();
+    let c_secondary = TrivialCircuit::<<Dual<E1> as Engine>::Scalar>::default();
+    let (pp, mut serial, z0_primary, z0_secondary) = trivial_snark::<E1>();
+    let mut parallel = serial.clone();
+
+    let num_steps = 4;
+    for _ in 0..num_steps {
+      serial.prove_step_with(&pp, &c_primary, &c_secondary, false).unwrap();
+      parallel.prove_step_with(&pp, &c_primary, &c_secondary, true).unwrap();
+    }
+    serial.verify(&pp, num_steps, &z0_primary, &z0_secondary).unwrap();
+
+    // the parallel path must produce a bit-identical proof
+    assert_eq!(bincode::serialize(&serial).unwrap(), bincode::serialize(&parallel).unwrap());
+  }
+
+  #[test]
+  fn test_prove_step_parallel_determinism() {
+    test_prove_step_parallel_determinism_with::<Bn256EngineIPA>();
+  }
+
+  fn test_verify_unsat_circuit_with<E1: CurveCycleEquipped>() {
+    let c_primary = TrivialCircuit::<E1::Scalar>::default();
+    let c_secondary = TrivialCircuit::<<Dual<E1> as Engine>::Scalar>::default();
+    let (pp, mut recursive_snark, z0_primary, z0_secondary) = trivial_snark::<E1>();
+    let num_steps = 3;
+    for _ in 0..num_steps {
+      recursive_snark.prove_step(&pp, &c_primary, &c_secondary).unwrap();
+    }
+    recursive_snark.verify(&pp, num_steps, &z0_primary, &z0_secondary).unwrap();
+
+    let check = |corrupt: &dyn Fn(&mut RecursiveSNARK<E1>), which, check, row| {
+      let mut corrupted = recursive_snark.clone();
+      corrupt(&mut corrupted);
+      assert_eq!(
+        corrupted.verify(&pp, num_steps, &z0_primary, &z0_secondary),
+        Err(NovaError::UnSatCircuit { which, check, row })
+      );
+    };
+    check(
+      &|s| s.zi_primary[0] += E1::Scalar::from(1u64),
+      CircuitKind::Primary,
+      UnSatCheck::IO,
+      None,
+    );
+    check(
+      &|s| s.zi_secondary[0] += <Dual<E1> as Engine>::Scalar::from(1u64),
+      CircuitKind::Secondary,
+      UnSatCheck::IO,
+      None,
+    );
+    check(
+      &|s| s.r_W_primary.E[3] += E1::Scalar::from(1u64),
+      CircuitKind::Primary,
+      UnSatCheck::Relation,
+      Some(3),
+    );
+    check(
+      &|s| s.r_W_secondary.E[0] += <Dual<E1> as Engine>::Scalar::from(1u64),
+      CircuitKind::Secondary,
+      UnSatCheck::Relation,
+      Some(0),
+    );
+
+    // errors other than an unsatisfied row, here a witness that no longer
+    // opens the commitment, are passed through
+    let mut corrupted = recursive_snark.clone();
+    corrupted.l_u_secondary.comm_W =
+      corrupted.l_u_secondary.comm_W + corrupted.l_u_secondary.comm_W;
+    assert_eq!(corrupted.verify(&pp, num_steps, &z0_primary, &z0_secondary), Err(NovaError::UnSat));
+  }
+
+  #[test]
+  fn test_verify_unsat_circuit() { test_verify_unsat_circuit_with::<Bn256EngineIPA>(); }
+
+  fn test_circuit_sizes_with<E1: CurveCycleEquipped>() {
+    let (pp, ..) = trivial_snark::<E1>();
+
+    let ((cons_primary, vars_primary, io_primary), (cons_secondary, vars_secondary, io_secondary)) =
+      pp.circuit_sizes();
+    assert_eq!(pp.num_constraints(), (cons_primary, cons_secondary));
+    assert_eq!(pp.num_variables(), (vars_primary, vars_secondary));
+    // the augmented circuits only expose the hashes of their running instances
+    assert_eq!((io_primary, io_secondary), (2, 2));
+  }
+
+  #[test]
+  fn test_circuit_sizes() { test_circuit_sizes_with::<Bn256EngineIPA>(); }
+
+  fn test_dry_run_with<E1: CurveCycleEquipped>() {
+    let c_primary = testutil::CounterCircuit::new(E1::Scalar::from(2u64));
+    let c_secondary = TrivialCircuit::<<Dual<E1> as Engine>::Scalar>::default();
+    let pp =
+      PublicParams::<E1>::setup(&c_primary, &c_secondary, &*default_ck_hint(), &*default_ck_hint())
+        .unwrap();
+
+    assert_eq!(PublicParams::<E1>::dry_run(&c_primary, &c_secondary), pp.circuit_sizes());
+  }
+
+  #[test]
+  fn test_dry_run() { test_dry_run_with::<Bn256EngineIPA>(); }
+
+  fn test_fold_stream_with<E1: CurveCycleEquipped>() {
+    // each step adds its own increment
+    let inputs = || {
+      (1..=10u64).map(|i| testutil::CounterCircuit::new(E1::Scalar::from(i))).collect::<Vec<_>>()
+    };
+    let c_secondary = TrivialCircuit::<<Dual<E1> as Engine>::Scalar>::default();
+    let pp = PublicParams::<E1>::setup(
+      &inputs()[0],
+      &c_secondary,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+    let z0_primary = vec![E1::Scalar::from(0u64)];
+    let z0_secondary = vec![<Dual<E1> as Engine>::Scalar::from(0u64)];
+
+    let mut manual =
+      RecursiveSNARK::new(&pp, &inputs()[0], &c_secondary, &z0_primary, &z0_secondary).unwrap();
+    for c_primary in inputs() {
+      manual.prove_step(&pp, &c_primary, &c_secondary).unwrap();
+    }
+
+    let mut steps = vec![];
+    let streamed = RecursiveSNARK::fold_stream(
+      &pp,
+      &c_secondary,
+      &z0_primary,
+      &z0_secondary,
+      inputs(),
+      Some(&mut |i| steps.push(i)),
+    )
+    .unwrap();
+    assert_eq!(steps, (1..=10).collect::<Vec<_>>());
+    assert_eq!(streamed.outputs(), manual.outputs());
+    assert_eq!(streamed.primary_instance(), manual.primary_instance());
+    assert_eq!(streamed.secondary_instance(), manual.secondary_instance());
+    assert_eq!(streamed.verify(&pp, 10, &z0_primary, &z0_secondary).unwrap().0, vec![
+      E1::Scalar::from(55u64)
+    ]);
+
+    // an empty stream has no step to start from
+    let empty = RecursiveSNARK::fold_stream(
+      &pp,
+      &c_secondary,
+      &z0_primary,
+      &z0_secondary,
+      Vec::<testutil::CounterCircuit<E1::Scalar>>::new(),
+      None,
+    );
+    assert!(matches!(empty, Err(NovaError::InvalidNumSteps)));
+  }
+
+  #[test]
+  fn test_fold_stream() { test_fold_stream_with::<Bn256EngineIPA>(); }
+
+  fn test_verifier_key_with<E1: CurveCycleEquipped>() {
+    let c_primary = testutil::CounterCircuit::new(E1::Scalar::from(2u64));
+    let c_secondary = TrivialCircuit::<<Dual<E1> as Engine>::Scalar>::default();
+    let pp =
+      PublicParams::<E1>::setup(&c_primary, &c_secondary, &*default_ck_hint(), &*default_ck_hint())
+        .unwrap();
+
+    let z0_primary = vec![E1::Scalar::from(1u64)];
+    let z0_secondary = vec![<Dual<E1> as Engine>::Scalar::from(1u64)];
+    let mut recursive_snark =
+      RecursiveSNARK::new(&pp, &c_primary, &c_secondary, &z0_primary, &z0_secondary).unwrap();
+    for _ in 0..3 {
+      recursive_snark.prove_step(&pp, &c_primary, &c_secondary).unwrap();
+    }
+
+    let res = recursive_snark.verify(&pp, 3, &z0_primary, &z0_secondary);
+    let vk = pp.into_verifier_key();
+    assert_eq!(recursive_snark.verify(&vk, 3, &z0_primary, &z0_secondary), res);
+    assert!(res.is_ok());
+
+    // both reject a wrong number of steps
+    assert_eq!(
+      recursive_snark.verify(&vk, 2, &z0_primary, &z0_secondary),
+      Err(NovaError::ProofVerifyError)
+    );
+  }
+
+  #[test]
+  fn test_verifier_key() { test_verifier_key_with::<Bn256EngineIPA>(); }
+
+  fn test_setup_with_keys_with<E1: CurveCycleEquipped>() {
+    let c_primary = TrivialCircuit::<E1::Scalar>::default();
+    let c_secondary = TrivialCircuit::<<Dual<E1> as Engine>::Scalar>::default();
+    let (pp, _, z0_primary, z0_secondary) = trivial_snark::<E1>();
+
+    // reusing the generated keys yields the same parameters
+    let pp_reused = PublicParams::<E1>::setup_with_keys(
+      &c_primary,
+      &c_secondary,
+      pp.ck_primary.clone(),
+      pp.ck_secondary.clone(),
+    )
+    .unwrap();
+    assert_eq!(pp_reused.digest(), pp.digest());
+
+    let mut recursive_snark =
+      RecursiveSNARK::new(&pp_reused, &c_primary, &c_secondary, &z0_primary, &z0_secondary)
+        .unwrap();
+    for _ in 0..3 {
+      recursive_snark.prove_step(&pp_reused, &c_primary, &c_secondary).unwrap();
+    }
+    assert!(recursive_snark.verify(&pp_reused, 3, &z0_primary, &z0_secondary).is_ok());
+
+    // a key smaller than the primary circuit is rejected
+    let ck_small = Arc::new(<E1::CE as CommitmentEngineTrait<E1>>::setup(b"ck", 1));
+    let ((cons_primary, vars_primary, _), _) = pp.circuit_sizes();
+    assert_eq!(
+      PublicParams::<E1>::setup_with_keys(
+        &c_primary,
+        &c_secondary,
+        ck_small.clone(),
+        pp.ck_secondary.clone(),
+      )
+      .unwrap_err(),
+      NovaError::CommitmentKeyTooSmall {
+        needed: cons_primary.max(vars_primary),
+        have:   ck_small.length(),
+      }
+    );
+  }
+
+  #[test]
+  fn test_setup_with_keys() { test_setup_with_keys_with::<Bn256EngineIPA>(); }
+
+  fn test_pp_digest_cache_with<E1: CurveCycleEquipped>() {
+    let c_primary = TrivialCircuit::<E1::Scalar>::default();
+    let c_secondary = TrivialCircuit::<<Dual<E1> as Engine>::Scalar>::default();
+    let setup = |ck_hint: &CommitmentKeyHint<E1>| {
+      PublicParams::<E1>::setup(&c_primary, &c_secondary, ck_hint, &*default_ck_hint()).unwrap()
+    };
+    let mut pp = setup(&*default_ck_hint());
+
+    // repeated calls return the cached value, which matches a fresh computation
+    let digest = pp.digest();
+    assert_eq!(pp.digest(), digest);
+    assert_eq!(DigestComputer::<E1::Scalar, _>::new(&pp).digest().unwrap(), digest);
+
+    // swapping the keys resets the cache
+    let larger_ck_hint = |shape: &R1CSShape<E1>| 2 * shape.num_cons.max(shape.num_vars);
+    let pp_larger = setup(&larger_ck_hint);
+    pp.set_commitment_keys(pp_larger.ck_primary.clone(), pp_larger.ck_secondary.clone()).unwrap();
+    assert_ne!(pp.digest(), digest);
+    assert_eq!(pp.digest(), pp_larger.digest());
+    assert_eq!(DigestComputer::<E1::Scalar, _>::new(&pp).digest().unwrap(), pp.digest());
+
+    // a key that is too small is rejected and leaves the parameters untouched
+    let ck_small = Arc::new(<E1::CE as CommitmentEngineTrait<E1>>::setup(b"ck", 1));
+    assert!(pp.set_commitment_keys(ck_small, pp_larger.ck_secondary.clone()).is_err());
+    assert_eq!(pp.digest(), pp_larger.digest());
+  }
+
+  #[test]
+  fn test_pp_digest_cache() { test_pp_digest_cache_with::<Bn256EngineIPA>(); }
+
+  fn test_versioned_bytes_with<E1: CurveCycleEquipped>() {
+    let c_primary = TrivialCircuit::<E1::Scalar>::default();
+    let c_secondary = TrivialCircuit::<<Dual<E1> as Engine>::Scalar>::default();
+    let (pp, mut recursive_snark, z0_primary, z0_secondary) = trivial_snark::<E1>();
+    recursive_snark.prove_step(&pp, &c_primary, &c_secondary).unwrap();
+
+    // both round-trip through their versioned bytes
+    let pp_bytes = pp.to_bytes();
+    assert_eq!(pp_bytes[0], SERIALIZATION_VERSION);
+    let pp = PublicParams::<E1>::from_bytes(&pp_bytes).unwrap();
+    let snark_bytes = recursive_snark.to_bytes();
+    let recursive_snark = RecursiveSNARK::<E1>::from_bytes(&snark_bytes).unwrap();
+    recursive_snark.verify(&pp, 1, &z0_primary, &z0_secondary).unwrap();
+
+    // a bumped version header is rejected
+    let expected = NovaError::IncompatibleVersion {
+      found:    SERIALIZATION_VERSION + 1,
+      expected: SERIALIZATION_VERSION,
+    };
+    let mut pp_bytes = pp_bytes;
+    pp_bytes[0] += 1;
+    assert_eq!(PublicParams::<E1>::from_bytes(&pp_bytes).unwrap_err(), expected);
+    let mut snark_bytes = snark_bytes;
+    snark_bytes[0] += 1;
+    assert_eq!(RecursiveSNARK::<E1>::from_bytes(&snark_bytes).unwrap_err(), expected);
+  }
+
+  #[test]
+  fn test_versioned_bytes() { test_versioned_bytes_with::<Bn256EngineIPA>(); }
+
+  fn test_verify_returns_outputs_with<E1: CurveCycleEquipped>() {
+    let increment = E1::Scalar::from(2u64);
+    let c_primary = crate::testutil::CounterCircuit::new(increment);
+    let c_secondary = TrivialCircuit::<<Dual<E1> as Engine>::Scalar>::default();
+    let pp =
+      PublicParams::<E1>::setup(&c_primary, &c_secondary, &*default_ck_hint(), &*default_ck_hint())
+        .unwrap();
+    let z0_primary = vec![E1::Scalar::from(1u64)];
+    let z0_secondary = vec![<Dual<E1> as Engine>::Scalar::from(1u64)];
+    let mut recursive_snark =
+      RecursiveSNARK::new(&pp, &c_primary, &c_secondary, &z0_primary, &z0_secondary).unwrap();
+    let num_steps = 3;
+    for _ in 0..num_steps {
+      recursive_snark.prove_step(&pp, &c_primary, &c_secondary).unwrap();
+    }
+
+    let (zn_primary, zn_secondary) =
+      recursive_snark.verify(&pp, num_steps, &z0_primary, &z0_secondary).unwrap();
+    assert_eq!(recursive_snark.outputs(), (&zn_primary[..], &zn_secondary[..]));
+    assert_eq!(zn_primary, vec![z0_primary[0] + increment * E1::Scalar::from(num_steps as u64)]);
+    assert_eq!(zn_secondary, z0_secondary);
+  }
+
+  #[test]
+  fn test_verify_returns_outputs() { test_verify_returns_outputs_with::<Bn256EngineIPA>(); }
+
+  fn test_prove_step_unsatisfied_with<E1: CurveCycleEquipped>() {
+    let c_primary = UnsatisfiableCircuit::<E1::Scalar>::default();
+    let c_secondary = TrivialCircuit::<<Dual<E1> as Engine>::Scalar>::default();
+    let pp =
+      PublicParams::<E1>::setup(&c_primary, &c_secondary, &*default_ck_hint(), &*default_ck_hint())
+        .unwrap();
+    let z0_primary = vec![E1::Scalar::from(1u64)];
+    let z0_secondary = vec![<Dual<E1> as Engine>::Scalar::from(1u64)];
+
+    // proving does not check satisfiability, so every step goes through
+    let mut recursive_snark =
+      RecursiveSNARK::new(&pp, &c_primary, &c_secondary, &z0_primary, &z0_secondary).unwrap();
+    for _ in 0..2 {
+      recursive_snark.prove_step(&pp, &c_primary, &c_secondary).unwrap();
+    }
+
+    // but verification rejects the folded primary instance
+    assert!(matches!(
+      recursive_snark.verify(&pp, 2, &z0_primary, &z0_secondary),
+      Err(NovaError::UnSatCircuit {
+        which: CircuitKind::Primary,
+        check: UnSatCheck::Relation,
+        row:   Some(_),
+      })
+    ));
+  }
+
+  #[test]
+  fn test_prove_step_unsatisfied() { test_prove_step_unsatisfied_with::<Bn256EngineIPA>(); }
+
+  fn test_comm_T_with<E1: CurveCycleEquipped>() {
+    let c_primary = TrivialCircuit::<E1::Scalar>::default();
+    let c_secondary = TrivialCircuit::<<Dual<E1> as Engine>::Scalar>::default();
+    let (pp, mut recursive_snark, ..) = trivial_snark::<E1>();
+
+    // the first step folds nothing
+    recursive_snark.prove_step(&pp, &c_primary, &c_secondary).unwrap();
+    assert!(recursive_snark.comm_T().is_none());
+
+    let before = recursive_snark.clone();
+    recursive_snark.prove_step(&pp, &c_primary, &c_secondary).unwrap();
+    let (comm_T_primary, comm_T_secondary) = recursive_snark.comm_T().unwrap();
+
+    // the secondary fold is recomputed from the exposed `comm_T`
+    let r_U_secondary = NIFS::verify_with_comm_T(
+      &pp.ro_consts_secondary,
+      &scalar_as_base::<E1>(pp.digest()),
+      &before.r_U_secondary,
+      &before.l_u_secondary,
+      &comm_T_secondary.compress(),
+    )
+    .unwrap();
+    assert_eq!(r_U_secondary, recursive_snark.r_U_secondary);
+
+    // and the primary error commitment moved by `r * comm_T`, where `r` is the
+    // increment of `u`
+    let r = recursive_snark.r_U_primary.u - before.r_U_primary.u;
+    assert_eq!(recursive_snark.r_U_primary.comm_E, before.r_U_primary.comm_E + comm_T_primary * r);
+  }
+
+  #[test]
+  fn test_comm_T() { test_comm_T_with::<Bn256EngineIPA>(); }
+
+  fn test_checkpoint_resume_with<E1: CurveCycleEquipped>() {
+    let c_primary = TrivialCircuit::<E1::Scalar>::default();
+    let c_secondary = TrivialCircuit::<<Dual<E1> as Engine>::Scalar>::default();
+    let (pp, mut non_stop, z0_primary, z0_secondary) = trivial_snark::<E1>();
+    let mut resumed = non_stop.clone();
+
+    for _ in 0..3 {
+      resumed.prove_step(&pp, &c_primary, &c_secondary).unwrap();
+    }
+    let bytes = bincode::serialize(&resumed.checkpoint(&pp)).unwrap();
+    let checkpoint: RecursiveSNARKCheckpoint<E1> = bincode::deserialize(&bytes).unwrap();
+
+    // a checkpoint is rejected by different public parameters
+    let larger_ck_hint = |shape: &R1CSShape<E1>| 2 * shape.num_cons.max(shape.num_vars);
+    let other_pp =
+      PublicParams::<E1>::setup(&c_primary, &c_secondary, &larger_ck_hint, &*default_ck_hint())
+        .unwrap();
+    assert_eq!(
+      RecursiveSNARK::resume(&other_pp, checkpoint.clone()).unwrap_err(),
+      NovaError::CheckpointDigestMismatch
+    );
+
+    let mut resumed = RecursiveSNARK::resume(&pp, checkpoint).unwrap();
+    assert_eq!(resumed.num_steps(), 3);
+    for _ in 3..6 {
+      resumed.prove_step(&pp, &c_primary, &c_secondary).unwrap();
+    }
+    for _ in 0..6 {
+      non_stop.prove_step(&pp, &c_primary, &c_secondary).unwrap();
+    }
+
+    let outputs = resumed.verify(&pp, 6, &z0_primary, &z0_secondary).unwrap();
+    assert_eq!(outputs, non_stop.verify(&pp, 6, &z0_primary, &z0_secondary).unwrap());
+    assert_eq!(bincode::serialize(&resumed).unwrap(), bincode::serialize(&non_stop).unwrap());
+  }
+
+  #[test]
+  fn test_checkpoint_resume() { test_checkpoint_resume_with::<Bn256EngineIPA>(); }
+
+  fn test_num_steps_with<E1: CurveCycleEquipped>() {
+    let c_primary = TrivialCircuit::<E1::Scalar>::default();
+    let c_secondary = TrivialCircuit::<<Dual<E1> as Engine>::Scalar>::default();
+    let (pp, mut recursive_snark, ..) = trivial_snark::<E1>();
+    assert_eq!(recursive_snark.num_steps(), 0);
+
+    for i in 1..=4 {
+      let primary_instance = recursive_snark.primary_instance().clone();
+      recursive_snark.prove_step(&pp, &c_primary, &c_secondary).unwrap();
+      assert_eq!(recursive_snark.num_steps(), i);
+
+      // the running instances carry the two hashes of the augmented circuit
+      assert_eq!(recursive_snark.primary_instance().X.len(), 2);
+      assert_eq!(recursive_snark.secondary_instance().X.len(), 2);
+      // the first step is proven by the constructor, later ones fold into the
+      // primary running instance
+      assert_eq!(recursive_snark.primary_instance() == &primary_instance, i == 1);
+    }
+  }
+
+  #[test]
+  fn test_num_steps() { test_num_steps_with::<Bn256EngineIPA>(); }
+
+  fn test_step_arity_mismatch_with<E1: CurveCycleEquipped>() {
+    let c_primary = TruncatingCircuit::<E1::Scalar>::default();
+    let c_secondary = TrivialCircuit::<<Dual<E1> as Engine>::Scalar>::default();
+    let pp =
+      PublicParams::<E1>::setup(&c_primary, &c_secondary, &*default_ck_hint(), &*default_ck_hint())
+        .unwrap();
+
+    let z0_primary = vec![E1::Scalar::from(1u64); 2];
+    let z0_secondary = vec![<Dual<E1> as Engine>::Scalar::from(1u64)];
+    let res = RecursiveSNARK::new(&pp, &c_primary, &c_secondary, &z0_primary, &z0_secondary);
+    assert_eq!(res.unwrap_err(), NovaError::StepArityMismatch { expected: 2, got: 1 });
+  }
+
+  #[test]
+  fn test_step_arity_mismatch() { test_step_arity_mismatch_with::<Bn256EngineIPA>(); }
+
+  fn test_batch_verify_with<E1, S1, S2>()
+  where
+    E1: CurveCycleEquipped,
+    S1: RelaxedR1CSSNARKTrait<E1>,
+    S2: RelaxedR1CSSNARKTrait<Dual<E1>>, {
+    let c_primary = TrivialCircuit::<E1::Scalar>::default();
+    let c_secondary = TrivialCircuit::<<Dual<E1> as Engine>::Scalar>::default();
+    let pp =
+      PublicParams::<E1>::setup(&c_primary, &c_secondary, &*S1::ck_floor(), &*S2::ck_floor())
+        .unwrap();
+    let (pk, vk) = CompressedSNARK::<E1, S1, S2>::setup(&pp).unwrap();
+
+    let num_steps = 2;
+    let mut proofs = (0..10u64)
+      .map(|i| {
+        let z0_primary = vec![E1::Scalar::from(i)];
+        let z0_secondary = vec![<Dual<E1> as Engine>::Scalar::from(i)];
+        let mut recursive_snark =
+          RecursiveSNARK::new(&pp, &c_primary, &c_secondary, &z0_primary, &z0_secondary).unwrap();
+        for _ in 0..num_steps {
+          recursive_snark.prove_step(&pp, &c_primary, &c_secondary).unwrap();
+        }
+        let snark = CompressedSNARK::<E1, S1, S2>::prove(&pp, &pk, &recursive_snark).unwrap();
+        (snark, num_steps, z0_primary, z0_secondary)
+      })
+      .collect::<Vec<_>>();
+    CompressedSNARK::batch_verify(&vk, &proofs).unwrap();
+
+    // a single invalid proof fails the whole batch
+    proofs[7].0.zn_primary[0] += E1::Scalar::from(1u64);
+    assert_eq!(CompressedSNARK::batch_verify(&vk, &proofs), Err(NovaError::ProofVerifyError));
+  }
+
+  fn test_compressed_snark_with<E1, S1, S2>()
+  where
+    E1: CurveCycleEquipped,
+    S1: RelaxedR1CSSNARKTrait<E1>,
+    S2: RelaxedR1CSSNARKTrait<Dual<E1>>, {
+    let c_primary = testutil::CounterCircuit::new(E1::Scalar::from(2u64));
+    let c_secondary = TrivialCircuit::<<Dual<E1> as Engine>::Scalar>::default();
+    let pp =
+      PublicParams::<E1>::setup(&c_primary, &c_secondary, &*S1::ck_floor(), &*S2::ck_floor())
+        .unwrap();
+
+    let z0_primary = vec![E1::Scalar::from(1u64)];
+    let z0_secondary = vec![<Dual<E1> as Engine>::Scalar::from(1u64)];
+    let mut recursive_snark =
+      RecursiveSNARK::new(&pp, &c_primary, &c_secondary, &z0_primary, &z0_secondary).unwrap();
+    let num_steps = 4;
+    for _ in 0..num_steps {
+      recursive_snark.prove_step(&pp, &c_primary, &c_secondary).unwrap();
+    }
+    recursive_snark.verify(&pp, num_steps, &z0_primary, &z0_secondary).unwrap();
+
+    let (pk, vk) = CompressedSNARK::<E1, S1, S2>::setup(&pp).unwrap();
+    let snark = CompressedSNARK::<E1, S1, S2>::prove(&pp, &pk, &recursive_snark).unwrap();
+    let (zn_primary, zn_secondary) =
+      snark.verify(&vk, num_steps, &z0_primary, &z0_secondary).unwrap();
+    assert_eq!(zn_primary, vec![E1::Scalar::from(9u64)]);
+    assert_eq!(zn_secondary, z0_secondary);
+
+    // the reported sizes are those of the serialized proof and key
+    assert_eq!(snark.size_bytes(), bincode::serialize(&snark).unwrap().len());
+    assert_eq!(
+      CompressedSNARK::<E1, S1, S2>::vk_size_bytes(&vk),
+      bincode::serialize(&vk).unwrap().len()
+    );
+
+    // the claimed number of steps must match the folded instance
+    for wrong_num_steps in [num_steps - 1, num_steps + 1] {
+      assert_eq!(
+        snark.verify(&vk, wrong_num_steps, &z0_primary, &z0_secondary),
+        Err(NovaError::ProofVerifyError)
+      );
+    }
+  }
+
+  fn test_pp_digest_check_with<E1, S1, S2>()
+  where
+    E1: CurveCycleEquipped,
+    S1: RelaxedR1CSSNARKTrait<E1>,
+    S2: RelaxedR1CSSNARKTrait<Dual<E1>>, {
+    let c_primary = TrivialCircuit::<E1::Scalar>::default();
+    let c_secondary = TrivialCircuit::<<Dual<E1> as Engine>::Scalar>::default();
+    let setup = |ck_hint1: &CommitmentKeyHint<E1>| {
+      PublicParams::<E1>::setup(&c_primary, &c_secondary, ck_hint1, &*S2::ck_floor()).unwrap()
+    };
+    let pp = setup(&*S1::ck_floor());
+
+    // the digest survives a serialization round-trip
+    let bytes = bincode::serialize(&pp).unwrap();
+    let pp_deserialized: PublicParams<E1> = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(pp.digest(), pp_deserialized.digest());
+
+    let z0_primary = vec![E1::Scalar::from(1u64)];
+    let z0_secondary = vec![<Dual<E1> as Engine>::Scalar::from(1u64)];
+    let mut recursive_snark =
+      RecursiveSNARK::new(&pp, &c_primary, &c_secondary, &z0_primary, &z0_secondary).unwrap();
+    for _ in 0..2 {
+      recursive_snark.prove_step(&pp, &c_primary, &c_secondary).unwrap();
+    }
+    let (pk, vk) = CompressedSNARK::<E1, S1, S2>::setup(&pp).unwrap();
+    let snark = CompressedSNARK::<E1, S1, S2>::prove(&pp, &pk, &recursive_snark).unwrap();
+    assert_eq!(vk.pp_digest(), pp.digest());
+
+    // identical params generated afresh accept the proof
+    let (_, vk_regenerated) =
+      CompressedSNARK::<E1, S1, S2>::setup(&setup(&*S1::ck_floor())).unwrap();
+    snark.verify(&vk_regenerated, 2, &z0_primary, &z0_secondary).unwrap();
+
+    // modified params reject it on the digest
+    let larger_ck_hint = |shape: &R1CSShape<E1>| 2 * shape.num_cons.max(shape.num_vars);
+    let (_, vk_modified) = CompressedSNARK::<E1, S1, S2>::setup(&setup(&larger_ck_hint)).unwrap();
+    assert_ne!(vk_modified.pp_digest(), vk.pp_digest());
+    assert_eq!(
+      snark.verify(&vk_modified, 2, &z0_primary, &z0_secondary),
+      Err(NovaError::ProofVerifyError)
+    );
+  }
+
+  #[test]
+  fn test_pp_digest_check() {
+    type EE<E> = provider::ipa_pc::EvaluationEngine<E>;
+    type S<E> = spartan::snark::RelaxedR1CSSNARK<E, EE<E>>;
+
+    test_pp_digest_check_with::<Bn256EngineIPA, S<Bn256EngineIPA>, S<GrumpkinEngine>>();
+  }
+
+  #[test]
+  fn test_compressed_snark() {
+    type EE<E> = provider::ipa_pc::EvaluationEngine<E>;
+    type S<E> = spartan::snark::RelaxedR1CSSNARK<E, EE<E>>;
+
+    test_compressed_snark_with::<Bn256EngineIPA, S<Bn256EngineIPA>, S<GrumpkinEngine>>();
+  }
+
+  #[test]
+  fn test_batch_verify() {
+    type EE<E> = provider::ipa_pc::EvaluationEngine<E>;
+    type S<E> = spartan::snark::RelaxedR1CSSNARK<E, EE<E>>;
+
+    test_batch_verify_with::<Bn256EngineIPA, S<Bn256EngineIPA>, S<GrumpkinEngine>>();
+  }
+}
+
 // #[cfg(test)]
 // mod tests {
 //     use core::{fmt::Write, marker::PhantomData};
@@ -1,16 +1,22 @@
 //! This module implements a non-interactive folding scheme
 #![allow(non_snake_case)]
 
+use std::borrow::Cow;
+
 use serde::{Deserialize, Serialize};
 
 use crate::{
   constants::{NUM_CHALLENGE_BITS, NUM_FE_FOR_RO, NUM_FE_WITHOUT_IO_FOR_NOVA_FOLD},
   errors::NovaError,
   r1cs::{
-    R1CSInstance, R1CSResult, R1CSShape, R1CSWitness, RelaxedR1CSInstance, RelaxedR1CSWitness,
+    default_T, R1CSInstance, R1CSResult, R1CSShape, R1CSWitness, RelaxedR1CSInstance,
+    RelaxedR1CSWitness,
   },
   scalar_as_base,
-  traits::{commitment::CommitmentTrait, AbsorbInROTrait, Engine, ROConstants, ROTrait},
+  traits::{
+    commitment::CommitmentTrait, AbsorbInROTrait, Engine, ROConstants, ROTrait,
+    TranscriptEngineTrait,
+  },
   Commitment, CommitmentKey, CompressedCommitment,
 };
 
@@ -38,6 +44,11 @@ impl<E: Engine> NIFS<E> {
   /// In this particular setting, this means that if `U2` is absorbed in the
   /// RO, it implicitly absorbs `U1` as well. So the code below avoids
   /// absorbing `U1` in the RO.
+  ///
+  /// `(U2, W2)` may come from a smaller shape than `shape`, such as another
+  /// circuit whose shape `shape` pads with [`R1CSShape::pad`]: `W2` is then
+  /// zero-extended to `shape`. Their public IO arities must still match,
+  /// otherwise [`NovaError::ShapeMismatch`] is returned.
   #[allow(clippy::too_many_arguments)]
   #[tracing::instrument(skip_all, level = "trace", name = "NIFS::prove")]
   pub fn prove(
@@ -53,9 +64,12 @@ impl<E: Engine> NIFS<E> {
     // Check `U1` and `U2` have the same arity
     let io_arity = U1.X.len();
     if io_arity != U2.X.len() {
-      return Err(NovaError::InvalidInputLength);
+      return Err(NovaError::ShapeMismatch { expected: io_arity, got: U2.X.len() });
     }
 
+    // an incoming witness of a smaller shape is zero-extended to `S`
+    let W2 = if W2.W.len() < S.num_vars { Cow::Owned(W2.pad(S)) } else { Cow::Borrowed(W2) };
+
     // initialize a new RO
     let mut ro = E::RO::new(ro_consts.clone(), NUM_FE_WITHOUT_IO_FOR_NOVA_FOLD + io_arity);
 
@@ -67,19 +81,19 @@ impl<E: Engine> NIFS<E> {
     U2.absorb_in_ro(&mut ro);
 
     // compute a commitment to the cross-term
-    let (T, comm_T) = S.commit_T(ck, U1, W1, U2, W2)?;
+    let (T, comm_T) = S.commit_T(ck, U1, W1, U2, &W2)?;
 
     // append `comm_T` to the transcript and obtain a challenge
     comm_T.absorb_in_ro(&mut ro);
 
     // compute a challenge from the RO
-    let r = ro.squeeze(NUM_CHALLENGE_BITS);
+    let r = ro.squeeze(NUM_CHALLENGE_BITS)?;
 
     // fold the instance using `r` and `comm_T`
     let U = U1.fold(U2, &comm_T, &r);
 
     // fold the witness using `r` and `T`
-    let W = W1.fold(W2, &T, &r)?;
+    let W = W1.fold(&W2, &T, &r)?;
 
     // return the folded instance and witness
     Ok((Self { comm_T: comm_T.compress() }, (U, W), r))
@@ -105,6 +119,33 @@ impl<E: Engine> NIFS<E> {
     T: &mut Vec<E::Scalar>,
     ABC_Z_1: &mut R1CSResult<E>,
     ABC_Z_2: &mut R1CSResult<E>,
+  ) -> Result<(Self, E::Scalar), NovaError> {
+    let (nifs, r) =
+      Self::prove_instance_mut(ck, ro_consts, pp_digest, S, U1, W1, U2, W2, T, ABC_Z_1, ABC_Z_2)?;
+
+    // fold the witness using `r` and `T`
+    W1.fold_mut(W2, T, &r)?;
+
+    Ok((nifs, r))
+  }
+
+  /// Same as [`NIFS::prove_mut`], but only folds the instance `U1`. The
+  /// cross-term is left in `T`, and the caller must complete the fold with
+  /// `W1.fold_mut(W2, T, &r)`. This lets the witness fold run alongside
+  /// work that only needs the folded instance and `comm_T`.
+  #[allow(clippy::too_many_arguments)]
+  pub(crate) fn prove_instance_mut(
+    ck: &CommitmentKey<E>,
+    ro_consts: &ROConstants<E>,
+    pp_digest: &E::Scalar,
+    S: &R1CSShape<E>,
+    U1: &mut RelaxedR1CSInstance<E>,
+    W1: &RelaxedR1CSWitness<E>,
+    U2: &R1CSInstance<E>,
+    W2: &R1CSWitness<E>,
+    T: &mut Vec<E::Scalar>,
+    ABC_Z_1: &mut R1CSResult<E>,
+    ABC_Z_2: &mut R1CSResult<E>,
   ) -> Result<(Self, E::Scalar), NovaError> {
     // initialize a new RO
     let mut ro = E::RO::new(ro_consts.clone(), NUM_FE_FOR_RO);
@@ -123,18 +164,102 @@ impl<E: Engine> NIFS<E> {
     comm_T.absorb_in_ro(&mut ro);
 
     // compute a challenge from the RO
-    let r = ro.squeeze(NUM_CHALLENGE_BITS);
+    let r = ro.squeeze(NUM_CHALLENGE_BITS)?;
 
     // fold the instance using `r` and `comm_T`
     U1.fold_mut(U2, &comm_T, &r);
 
-    // fold the witness using `r` and `T`
-    W1.fold_mut(W2, T, &r)?;
-
     // return the commitment
     Ok((Self { comm_T: comm_T.compress() }, r))
   }
 
+  /// Folds each R1CS instance-witness tuple of `incoming` in turn into the
+  /// Relaxed R1CS instance-witness tuple `(U1, W1)`, and outputs one `NIFS`
+  /// per incoming tuple along with the final folded tuple and the challenges.
+  ///
+  /// All challenges come from a single transcript of the batch: it absorbs
+  /// `pp_digest`, `U1` and every incoming instance once, then for each fold
+  /// in turn the commitment to its cross-term before squeezing its challenge.
+  /// The cross-term of a fold depends on the instance folded so far, hence on
+  /// the previous challenge, so the commitments cannot all be absorbed before
+  /// the first squeeze. Each fold is the pairwise fold of [`NIFS::prove`]
+  /// under its challenge, and the batch is checked with [`NIFS::verify_many`].
+  #[allow(clippy::type_complexity)]
+  pub fn prove_many(
+    ck: &CommitmentKey<E>,
+    pp_digest: &E::Scalar,
+    S: &R1CSShape<E>,
+    U1: &RelaxedR1CSInstance<E>,
+    W1: &RelaxedR1CSWitness<E>,
+    incoming: &[(R1CSInstance<E>, R1CSWitness<E>)],
+  ) -> Result<(Vec<Self>, (RelaxedR1CSInstance<E>, RelaxedR1CSWitness<E>), Vec<E::Scalar>), NovaError>
+  {
+    if let Some((U2, _)) = incoming.iter().find(|(U2, _)| U2.X.len() != U1.X.len()) {
+      return Err(NovaError::ShapeMismatch { expected: U1.X.len(), got: U2.X.len() });
+    }
+
+    let mut transcript = Self::batch_transcript(pp_digest, U1, incoming.iter().map(|(U2, _)| U2));
+    let (mut U, mut W) = (U1.clone(), W1.clone());
+    let mut T = default_T::<E>(S.num_cons);
+    let mut ABC_Z_1 = R1CSResult::default(S.num_cons);
+    let mut ABC_Z_2 = R1CSResult::default(S.num_cons);
+
+    let mut nifs = Vec::with_capacity(incoming.len());
+    let mut challenges = Vec::with_capacity(incoming.len());
+    for (U2, W2) in incoming {
+      let comm_T = S.commit_T_into(ck, &U, &W, U2, W2, &mut T, &mut ABC_Z_1, &mut ABC_Z_2)?;
+      transcript.absorb(b"T", &comm_T);
+      let r = transcript.squeeze(b"r")?;
+
+      U.fold_mut(U2, &comm_T, &r);
+      W.fold_mut(W2, &T, &r)?;
+      nifs.push(Self { comm_T: comm_T.compress() });
+      challenges.push(r);
+    }
+
+    Ok((nifs, (U, W), challenges))
+  }
+
+  /// Verifies a batch of folds produced by [`NIFS::prove_many`], folding the
+  /// instances of `incoming` in turn into `U1` and returning the result.
+  pub fn verify_many(
+    nifs: &[Self],
+    pp_digest: &E::Scalar,
+    U1: &RelaxedR1CSInstance<E>,
+    incoming: &[R1CSInstance<E>],
+  ) -> Result<RelaxedR1CSInstance<E>, NovaError> {
+    if nifs.len() != incoming.len() {
+      return Err(NovaError::InvalidInputLength);
+    }
+    if let Some(U2) = incoming.iter().find(|U2| U2.X.len() != U1.X.len()) {
+      return Err(NovaError::ShapeMismatch { expected: U1.X.len(), got: U2.X.len() });
+    }
+
+    let mut transcript = Self::batch_transcript(pp_digest, U1, incoming.iter());
+    nifs.iter().zip(incoming).try_fold(U1.clone(), |U, (nifs, U2)| {
+      let comm_T = Commitment::<E>::decompress(&nifs.comm_T)?;
+      transcript.absorb(b"T", &comm_T);
+      let r = transcript.squeeze(b"r")?;
+      Ok(U.fold(U2, &comm_T, &r))
+    })
+  }
+
+  /// The transcript of a batch of folds, after absorbing everything known
+  /// before the first fold
+  fn batch_transcript<'a>(
+    pp_digest: &E::Scalar,
+    U1: &RelaxedR1CSInstance<E>,
+    incoming: impl Iterator<Item = &'a R1CSInstance<E>>,
+  ) -> E::TE {
+    let mut transcript = E::TE::new(b"NIFSBatch");
+    transcript.absorb(b"pp", pp_digest);
+    transcript.absorb(b"U1", U1);
+    for U2 in incoming {
+      transcript.absorb(b"U2", U2);
+    }
+    transcript
+  }
+
   /// Takes as input a relaxed R1CS instance `U1` and R1CS instance `U2`
   /// with the same shape and defined with respect to the same parameters,
   /// and outputs a folded instance `U` with the same shape,
@@ -146,6 +271,23 @@ impl<E: Engine> NIFS<E> {
     pp_digest: &E::Scalar,
     U1: &RelaxedR1CSInstance<E>,
     U2: &R1CSInstance<E>,
+  ) -> Result<RelaxedR1CSInstance<E>, NovaError> {
+    Self::verify_with_comm_T(ro_consts, pp_digest, U1, U2, &self.comm_T)
+  }
+
+  /// Same as [`NIFS::verify`], but takes the commitment to the cross-term
+  /// `comm_T` directly rather than a `NIFS` proof, for protocols that
+  /// transmit it on their own.
+  ///
+  /// This is the native counterpart of the fold the augmented circuit
+  /// enforces: it recomputes the challenge with `E::RO` and folds the
+  /// instances in plain Rust, so a fold can be checked offline.
+  pub fn verify_with_comm_T(
+    ro_consts: &ROConstants<E>,
+    pp_digest: &E::Scalar,
+    U1: &RelaxedR1CSInstance<E>,
+    U2: &R1CSInstance<E>,
+    comm_T: &CompressedCommitment<E>,
   ) -> Result<RelaxedR1CSInstance<E>, NovaError> {
     // initialize a new RO
     let mut ro = E::RO::new(ro_consts.clone(), NUM_FE_FOR_RO);
@@ -158,11 +300,11 @@ impl<E: Engine> NIFS<E> {
     U2.absorb_in_ro(&mut ro);
 
     // append `comm_T` to the transcript and obtain a challenge
-    let comm_T = Commitment::<E>::decompress(&self.comm_T)?;
+    let comm_T = Commitment::<E>::decompress(comm_T)?;
     comm_T.absorb_in_ro(&mut ro);
 
     // compute a challenge from the RO
-    let r = ro.squeeze(NUM_CHALLENGE_BITS);
+    let r = ro.squeeze(NUM_CHALLENGE_BITS)?;
 
     // fold the instance using `r` and `comm_T`
     let U = U1.fold(U2, &comm_T, &r);
@@ -170,6 +312,72 @@ impl<E: Engine> NIFS<E> {
     // return the folded instance
     Ok(U)
   }
+
+  /// Takes as input two Relaxed R1CS instance-witness tuples `(U1, W1)` and
+  /// `(U2, W2)` with the same structure `shape` and defined with respect to
+  /// the same `ck`, and outputs a folded Relaxed R1CS instance-witness tuple
+  /// `(U, W)`, with the guarantee that `W` satisfies `U` if and only if `W1`
+  /// satisfies `U1` and `W2` satisfies `U2`.
+  ///
+  /// Unlike [`NIFS::prove`], neither instance is assumed to be bound to the
+  /// other, so both are absorbed, and the challenge is derived with the
+  /// engine's transcript `E::TE` since this fold is only ever checked natively.
+  pub fn prove_relaxed(
+    ck: &CommitmentKey<E>,
+    pp_digest: &E::Scalar,
+    S: &R1CSShape<E>,
+    U1: &RelaxedR1CSInstance<E>,
+    W1: &RelaxedR1CSWitness<E>,
+    U2: &RelaxedR1CSInstance<E>,
+    W2: &RelaxedR1CSWitness<E>,
+  ) -> Result<(Self, (RelaxedR1CSInstance<E>, RelaxedR1CSWitness<E>)), NovaError> {
+    if U1.X.len() != U2.X.len() {
+      return Err(NovaError::ShapeMismatch { expected: U1.X.len(), got: U2.X.len() });
+    }
+
+    // compute a commitment to the cross-term
+    let (T, comm_T) = S.commit_T_relaxed(ck, U1, W1, U2, W2)?;
+
+    // compute a challenge binding both instances and the cross-term
+    let r = Self::challenge_relaxed(pp_digest, U1, U2, &comm_T)?;
+
+    // fold the instances and witnesses using `r` and `T`
+    let U = U1.fold_relaxed(U2, &comm_T, &r);
+    let W = W1.fold_relaxed(W2, &T, &r)?;
+
+    Ok((Self { comm_T: comm_T.compress() }, (U, W)))
+  }
+
+  /// Verifies a fold produced by [`NIFS::prove_relaxed`] and returns the
+  /// folded instance
+  pub fn verify_relaxed(
+    &self,
+    pp_digest: &E::Scalar,
+    U1: &RelaxedR1CSInstance<E>,
+    U2: &RelaxedR1CSInstance<E>,
+  ) -> Result<RelaxedR1CSInstance<E>, NovaError> {
+    if U1.X.len() != U2.X.len() {
+      return Err(NovaError::ShapeMismatch { expected: U1.X.len(), got: U2.X.len() });
+    }
+
+    let comm_T = Commitment::<E>::decompress(&self.comm_T)?;
+    let r = Self::challenge_relaxed(pp_digest, U1, U2, &comm_T)?;
+    Ok(U1.fold_relaxed(U2, &comm_T, &r))
+  }
+
+  fn challenge_relaxed(
+    pp_digest: &E::Scalar,
+    U1: &RelaxedR1CSInstance<E>,
+    U2: &RelaxedR1CSInstance<E>,
+    comm_T: &Commitment<E>,
+  ) -> Result<E::Scalar, NovaError> {
+    let mut transcript = E::TE::new(b"NIFSRelaxed");
+    transcript.absorb(b"pp", pp_digest);
+    transcript.absorb(b"U1", U1);
+    transcript.absorb(b"U2", U2);
+    transcript.absorb(b"T", comm_T);
+    transcript.squeeze(b"r")
+  }
 }
 
 #[cfg(test)]
@@ -186,7 +394,7 @@ mod tests {
       test_shape_cs::TestShapeCS,
     },
     provider::Bn256EngineKZG,
-    r1cs::commitment_key,
+    r1cs::{commitment_key, sparse::SparseMatrix},
     traits::{snark::default_ck_hint, Engine},
   };
 
@@ -283,6 +491,7 @@ mod tests {
     let U = res.unwrap();
 
     assert_eq!(U, _U);
+    assert_eq!(NIFS::verify_with_comm_T(ro_consts, pp_digest, &r_U, U1, &nifs.comm_T).unwrap(), U);
 
     // update the running witness and instance
     r_W = W;
@@ -300,6 +509,7 @@ mod tests {
     let U = res.unwrap();
 
     assert_eq!(U, _U);
+    assert_eq!(NIFS::verify_with_comm_T(ro_consts, pp_digest, &r_U, U2, &nifs.comm_T).unwrap(), U);
 
     // update the running witness and instance
     r_W = W;
@@ -359,12 +569,119 @@ mod tests {
     let mut csprng: OsRng = OsRng;
     let I = E::Scalar::random(&mut csprng); // the first input is picked randomly for the first instance
     let (O, U1, W1) = rand_inst_witness_generator(&ck, &I);
-    let (_O, U2, W2) = rand_inst_witness_generator(&ck, &O);
+    let (O2, U2, W2) = rand_inst_witness_generator(&ck, &O);
 
     // execute a sequence of folds
     execute_sequence(&ck, &ro_consts, &<E as Engine>::Scalar::ZERO, &S, &U1, &W1, &U2, &W2);
+
+    // fold a running instance with three incoming ones at once and pairwise
+    let (O3, U3, W3) = rand_inst_witness_generator(&ck, &O2);
+    let (_, U4, W4) = rand_inst_witness_generator(&ck, &O3);
+    let pp_digest = E::Scalar::ZERO;
+    let (r_U, r_W) = NIFS::prove(
+      &ck,
+      &ro_consts,
+      &pp_digest,
+      &S,
+      &RelaxedR1CSInstance::default(&ck, &S),
+      &RelaxedR1CSWitness::default(&S),
+      &U1,
+      &W1,
+    )
+    .unwrap()
+    .1;
+    let incoming = vec![(U2, W2), (U3, W3), (U4, W4)];
+
+    let (nifs, (U_many, W_many), challenges) =
+      NIFS::prove_many(&ck, &pp_digest, &S, &r_U, &r_W, &incoming).unwrap();
+
+    // each fold is the pairwise fold under the batch's challenge
+    let (U_pairwise, W_pairwise) =
+      incoming.iter().zip(&challenges).fold((r_U.clone(), r_W.clone()), |(U, W), ((U2, W2), r)| {
+        let (T, comm_T) = S.commit_T(&ck, &U, &W, U2, W2).unwrap();
+        (U.fold(U2, &comm_T, r), W.fold(W2, &T, r).unwrap())
+      });
+    assert_eq!(nifs.len(), incoming.len());
+    assert_eq!(U_many, U_pairwise);
+    assert_eq!(W_many, W_pairwise);
+    S.is_sat_relaxed(&ck, &U_many, &W_many).unwrap();
+
+    let incoming_U = incoming.into_iter().map(|(U, _)| U).collect::<Vec<_>>();
+    assert_eq!(NIFS::verify_many(&nifs, &pp_digest, &r_U, &incoming_U).unwrap(), U_many);
+
+    // the challenges bind the whole batch, so reordering it fails to verify
+    let mut reordered = incoming_U.clone();
+    reordered.swap(0, 1);
+    let mut reordered_nifs = nifs.clone();
+    reordered_nifs.swap(0, 1);
+    assert_ne!(NIFS::verify_many(&reordered_nifs, &pp_digest, &r_U, &reordered).unwrap(), U_many);
   }
 
   #[test]
   fn test_tiny_r1cs() { test_tiny_r1cs_with::<Bn256EngineKZG>(); }
+
+  fn test_fold_padded_shape_with<E: Engine>() {
+    // the tiny shape without its last constraint, and that shape padded
+    let tiny = crate::r1cs::tests::tiny_r1cs::<E>(3);
+    let truncate = |M: &SparseMatrix<E::Scalar>| {
+      let entries = M.iter().filter(|(row, ..)| *row < 3).collect::<Vec<_>>();
+      SparseMatrix::new(&entries, 3, M.num_cols())
+    };
+    let S2 =
+      R1CSShape::new(3, 3, 2, truncate(&tiny.A), truncate(&tiny.B), truncate(&tiny.C)).unwrap();
+    let S = S2.pad();
+    assert_ne!((S.num_cons, S.num_vars), (S2.num_cons, S2.num_vars));
+
+    let ck = commitment_key(&S, &*default_ck_hint());
+    let ro_consts = ROConstants::<E>::default();
+    let pp_digest = E::Scalar::ZERO;
+
+    // x = 2, so the satisfying witness is (x^2, x^3, x^3 + x) and y = x^3 + x + 5
+    let W2 = R1CSWitness::new(&S2, [4, 8, 10].map(E::Scalar::from).to_vec()).unwrap();
+    let U2 = R1CSInstance::new(&S2, W2.commit(&ck), vec![E::Scalar::from(2), E::Scalar::from(15)])
+      .unwrap();
+    S2.is_sat(&ck, &U2, &W2).unwrap();
+
+    // an instance of `S2` folds into a running instance of `S`
+    let U1 = RelaxedR1CSInstance::default(&ck, &S);
+    let W1 = RelaxedR1CSWitness::default(&S);
+    let (nifs, (U, W), _) =
+      NIFS::prove(&ck, &ro_consts, &pp_digest, &S, &U1, &W1, &U2, &W2).unwrap();
+    S.is_sat_relaxed(&ck, &U, &W).unwrap();
+    assert_eq!(nifs.verify(&ro_consts, &pp_digest, &U1, &U2).unwrap(), U);
+
+    // but not one with another public IO arity
+    let U2 = R1CSInstance { comm_W: U2.comm_W, X: vec![E::Scalar::ONE; 3] };
+    assert_eq!(
+      NIFS::prove(&ck, &ro_consts, &pp_digest, &S, &U1, &W1, &U2, &W2).unwrap_err(),
+      NovaError::ShapeMismatch { expected: 2, got: 3 }
+    );
+  }
+
+  #[test]
+  fn test_fold_padded_shape() { test_fold_padded_shape_with::<Bn256EngineKZG>(); }
+
+  fn test_fold_relaxed_with<E: Engine>() {
+    let S = crate::r1cs::tests::tiny_r1cs::<E>(4);
+    let ck = commitment_key(&S, &*default_ck_hint());
+    let pp_digest = E::Scalar::ZERO;
+    let mut rng = E::test_rng(0);
+    let (W1, U1) = S.random_witness_instance(&ck, &mut rng);
+    let (W2, U2) = S.random_witness_instance(&ck, &mut rng);
+
+    // two running instances fold into one satisfied instance
+    let (nifs, (U, W)) = NIFS::prove_relaxed(&ck, &pp_digest, &S, &U1, &W1, &U2, &W2).unwrap();
+    S.is_sat_relaxed(&ck, &U, &W).unwrap();
+    assert_eq!(nifs.verify_relaxed(&pp_digest, &U1, &U2).unwrap(), U);
+
+    // an unsatisfied one makes the folded instance unsatisfied
+    let mut W2_bad = W2.clone();
+    W2_bad.W[0] += E::Scalar::ONE;
+    let U2_bad = RelaxedR1CSInstance { comm_W: W2_bad.commit(&ck).0, ..U2 };
+    let (_, (U, W)) = NIFS::prove_relaxed(&ck, &pp_digest, &S, &U1, &W1, &U2_bad, &W2_bad).unwrap();
+    assert!(S.is_sat_relaxed(&ck, &U, &W).is_err());
+  }
+
+  #[test]
+  fn test_fold_relaxed() { test_fold_relaxed_with::<Bn256EngineKZG>(); }
 }
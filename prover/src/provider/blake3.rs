@@ -0,0 +1,135 @@
+//! This module provides a Blake3-based RO for native Fiat-Shamir, where
+//! circuit-friendliness is not required
+use core::marker::PhantomData;
+
+use ff::{PrimeField, PrimeFieldBits};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  errors::NovaError,
+  provider::poseidon::{check_num_absorbs, PoseidonConstantsCircuit, PoseidonROCircuit},
+  traits::ROTrait,
+};
+
+const PERSONA_TAG: &[u8] = b"NoRO";
+
+/// A Blake3-based RO to use outside circuits.
+///
+/// Field elements are absorbed by their little-endian byte representation and
+/// challenges are read from the Blake3 XOF. There is no in-circuit
+/// counterpart: `CircuitRO` points at [`PoseidonROCircuit`] only to satisfy
+/// [`ROTrait`], so this RO must not be used where the verifier circuit has to
+/// recompute its challenges. The Poseidon constants are accepted and ignored.
+#[derive(Debug, Clone)]
+pub struct Blake3RO<Base, Scalar>
+where
+  Base: PrimeField,
+  Scalar: PrimeField, {
+  hasher:      ::blake3::Hasher,
+  num_absorbs: usize,
+  absorbed:    usize,
+  squeezed:    bool,
+  _p:          PhantomData<(Base, Scalar)>,
+}
+
+impl<Base, Scalar> ROTrait<Base, Scalar> for Blake3RO<Base, Scalar>
+where
+  Base: PrimeField + PrimeFieldBits + Serialize + for<'de> Deserialize<'de>,
+  Scalar: PrimeField,
+{
+  type CircuitRO = PoseidonROCircuit<Base>;
+  type Constants = PoseidonConstantsCircuit<Base>;
+
+  fn new(_constants: PoseidonConstantsCircuit<Base>, num_absorbs: usize) -> Self {
+    let mut hasher = ::blake3::Hasher::new();
+    hasher.update(PERSONA_TAG);
+    hasher.update(&(num_absorbs as u64).to_le_bytes());
+    Self { hasher, num_absorbs, absorbed: 0, squeezed: false, _p: PhantomData }
+  }
+
+  /// Absorb a new number into the state of the oracle
+  fn absorb(&mut self, e: Base) {
+    assert!(!self.squeezed, "Cannot absorb after squeezing");
+    self.hasher.update(e.to_repr().as_ref());
+    self.absorbed += 1;
+  }
+
+  /// Compute a challenge by hashing the current state. Returns
+  /// [`NovaError::SpongeError`] if the number of absorbed elements differs
+  /// from the declared `num_absorbs`, as [`PoseidonRO`] does.
+  ///
+  /// [`PoseidonRO`]: crate::provider::poseidon::PoseidonRO
+  fn squeeze(&mut self, num_bits: usize) -> Result<Scalar, NovaError> {
+    if num_bits > Scalar::NUM_BITS as usize {
+      return Err(NovaError::InvalidNumBits);
+    }
+
+    // check if we have squeezed already
+    assert!(!self.squeezed, "Cannot squeeze again after squeezing");
+    self.squeezed = true;
+    check_num_absorbs(Some(self.num_absorbs), self.absorbed)?;
+
+    let mut output = vec![0u8; num_bits.div_ceil(8)];
+    self.hasher.finalize_xof().fill(&mut output);
+
+    // Only return `num_bits`
+    let mut res = Scalar::ZERO;
+    let mut coeff = Scalar::ONE;
+    for i in 0..num_bits {
+      if (output[i / 8] >> (i % 8)) & 1 == 1 {
+        res += coeff;
+      }
+      coeff += coeff;
+    }
+    Ok(res)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use ff::Field;
+  use rand::rngs::OsRng;
+
+  use super::*;
+  use crate::{
+    constants::NUM_CHALLENGE_BITS,
+    provider::{Bn256EngineKZG, GrumpkinEngine},
+    traits::Engine,
+  };
+
+  fn test_blake3_ro_with<E: Engine>() {
+    let mut csprng: OsRng = OsRng;
+    let inputs = (0..8).map(|_| E::Base::random(&mut csprng)).collect::<Vec<_>>();
+    let squeeze = |inputs: &[E::Base]| {
+      let mut ro: Blake3RO<E::Base, E::Scalar> =
+        Blake3RO::new(PoseidonConstantsCircuit::default(), inputs.len());
+      inputs.iter().for_each(|x| ro.absorb(*x));
+      ro.squeeze(NUM_CHALLENGE_BITS).unwrap()
+    };
+
+    // the same absorb sequence yields the same challenge
+    let c = squeeze(&inputs);
+    assert_eq!(c, squeeze(&inputs));
+
+    // a different absorb sequence diverges
+    let mut other = inputs.clone();
+    other.swap(0, 1);
+    assert_ne!(c, squeeze(&other));
+    assert_ne!(c, squeeze(&inputs[1..]));
+
+    // absorbing fewer elements than declared is an error rather than a panic
+    let mut ro: Blake3RO<E::Base, E::Scalar> =
+      Blake3RO::new(PoseidonConstantsCircuit::default(), inputs.len() + 1);
+    inputs.iter().for_each(|x| ro.absorb(*x));
+    assert_eq!(
+      ro.squeeze(NUM_CHALLENGE_BITS),
+      Err(NovaError::SpongeError("declared 9 absorbs, absorbed 8".to_string()))
+    );
+  }
+
+  #[test]
+  fn test_blake3_ro() {
+    test_blake3_ro_with::<Bn256EngineKZG>();
+    test_blake3_ro_with::<GrumpkinEngine>();
+  }
+}
@@ -34,14 +34,15 @@ use crate::{
     kzg_commitment::{KZGCommitmentEngine, KZGProverKey, KZGVerifierKey, UniversalKZGParam},
     pedersen::Commitment,
     traits::DlogGroup,
-    util::iterators::IndexedParallelIteratorExt as _,
+    util::{field::batch_invert, iterators::IndexedParallelIteratorExt as _},
   },
-  spartan::{math::Math, polys::univariate::UniPoly},
+  spartan::{math::Math, polys::univariate::UniPoly, powers},
   traits::{
     commitment::{CommitmentEngineTrait, Len},
     evaluation::EvaluationEngineTrait,
     Engine as NovaEngine, Group, TranscriptEngineTrait, TranscriptReprTrait,
   },
+  zip_with,
 };
 
 /// Provides an implementation of a polynomial evaluation argument
@@ -58,6 +59,20 @@ pub struct EvaluationArgument<E: Engine> {
   C_H:   E::G1Affine,
 }
 
+/// Provides an opening of a univariate KZG commitment at several points with a
+/// single proof, see [`EvaluationEngine::prove_batch`]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound(
+  serialize = "E::G1Affine: Serialize, E::Fr: Serialize",
+  deserialize = "E::G1Affine: Deserialize<'de>, E::Fr: Deserialize<'de>"
+))]
+pub struct BatchEvalProof<E: Engine> {
+  /// the claimed evaluations, one per opened point
+  pub evals: Vec<E::Fr>,
+  C_W:       E::G1Affine,
+  C_H:       E::G1Affine,
+}
+
 /// Provides an implementation of a polynomial evaluation engine using KZG
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct EvaluationEngine<E, NE> {
@@ -107,6 +122,38 @@ where
     transcript.squeeze(b"a").unwrap()
   }
 
+  // Compute the challenge rho batching the openings of C at the points z_i to
+  // the claimed values y_i
+  fn compute_batch_rho(
+    C: &Commitment<NE>,
+    points: &[E::Fr],
+    evals: &[E::Fr],
+    transcript: &mut impl TranscriptEngineTrait<NE>,
+  ) -> E::Fr {
+    transcript.absorb(b"C", &C.comm.to_affine());
+    transcript.absorb(b"z", &points);
+    transcript.absorb(b"y", &evals);
+    transcript.squeeze(b"rho").unwrap()
+  }
+
+  fn compute_batch_a(c_w: &E::G1Affine, transcript: &mut impl TranscriptEngineTrait<NE>) -> E::Fr {
+    transcript.absorb(b"C_W", c_w);
+    transcript.squeeze(b"a").unwrap()
+  }
+
+  // Compute the coefficients rho^i / (a - z_i) of the batched opening
+  fn compute_batch_coefficients(
+    points: &[E::Fr],
+    rho: E::Fr,
+    a: E::Fr,
+  ) -> Result<Vec<E::Fr>, NovaError> {
+    let inverses = batch_invert(points.iter().map(|z| a - z).collect())?;
+    Ok(
+      zip_with!(into_iter, (powers(&rho, points.len()), inverses), |rho_i, inv| rho_i * inv)
+        .collect(),
+    )
+  }
+
   fn compute_pi_polynomials(hat_P: &[E::Fr], point: &[E::Fr]) -> Vec<Vec<E::Fr>> {
     let mut polys: Vec<Vec<E::Fr>> = Vec::new();
     polys.push(hat_P.to_vec());
@@ -347,6 +394,95 @@ where
   }
 }
 
+impl<E, NE> EvaluationEngine<E, NE>
+where
+  E: MultiMillerLoop,
+  NE: NovaEngine<GE = E::G1, Scalar = E::Fr, CE = KZGCommitmentEngine<E>>,
+  E::Fr: Serialize + DeserializeOwned,
+  E::G1Affine: Serialize + DeserializeOwned,
+  E::G2Affine: Serialize + DeserializeOwned,
+  E::G1: DlogGroup<ScalarExt = E::Fr, AffineExt = E::G1Affine>,
+  <E::G1 as Group>::Base: TranscriptReprTrait<E::G1>,
+  E::Fr: PrimeFieldBits + TranscriptReprTrait<E::G1>,
+  E::G1Affine: TranscriptReprTrait<E::G1>,
+{
+  /// Opens the commitment `C` to the univariate polynomial with coefficients
+  /// `poly` at each of `points` with a single proof.
+  ///
+  /// The per-point quotients (P(x) - P(z_i)) / (x - z_i) are folded into one
+  /// commitment by a random linear combination in a challenge rho, which is
+  /// then opened at a second challenge a, as in Shplonk.
+  pub fn prove_batch(
+    ck: &UniversalKZGParam<E>,
+    transcript: &mut <NE as NovaEngine>::TE,
+    C: &Commitment<NE>,
+    poly: &[E::Fr],
+    points: &[E::Fr],
+  ) -> Result<BatchEvalProof<E>, NovaError> {
+    if points.is_empty() {
+      return Err(NovaError::InvalidInputLength);
+    }
+
+    let P = UniPoly::new(poly.to_vec());
+    let evals = points.iter().map(|z| P.evaluate(z)).collect::<Vec<_>>();
+    let rho = Self::compute_batch_rho(C, points, &evals, transcript);
+
+    // W(x) = sum_i rho^i * (P(x) - P(z_i)) / (x - z_i)
+    let W: UniPoly<E::Fr> = points.par_iter().map(|z| P.divide_minus_u(*z)).rlc(&rho);
+    let C_W = <NE::CE as CommitmentEngineTrait<NE>>::commit(ck, &W.coeffs).comm.to_affine();
+
+    let a = Self::compute_batch_a(&C_W, transcript);
+
+    // L(x) = sum_i rho^i / (a - z_i) * (P(x) - P(z_i)) - W(x) vanishes at a
+    let c = Self::compute_batch_coefficients(points, rho, a)?;
+    let mut L = P.clone();
+    L *= &c.iter().sum::<E::Fr>();
+    L -= &UniPoly::new(vec![zip_with!(iter, (c, evals), |c_i, y_i| *c_i * y_i).sum::<E::Fr>()]);
+    L -= &W;
+
+    let H = L.divide_minus_u(a);
+    let C_H = <NE::CE as CommitmentEngineTrait<NE>>::commit(ck, &H.coeffs).comm.to_affine();
+
+    Ok(BatchEvalProof { evals, C_W, C_H })
+  }
+
+  /// Checks a [`BatchEvalProof`] that `C` opens to `pi.evals` at `points`
+  pub fn verify_batch(
+    vk: &KZGVerifierKey<E>,
+    transcript: &mut <NE as NovaEngine>::TE,
+    C: &Commitment<NE>,
+    points: &[E::Fr],
+    pi: &BatchEvalProof<E>,
+  ) -> Result<(), NovaError> {
+    if points.is_empty() || pi.evals.len() != points.len() {
+      return Err(NovaError::ProofVerifyError);
+    }
+
+    let rho = Self::compute_batch_rho(C, points, &pi.evals, transcript);
+    let a = Self::compute_batch_a(&pi.C_W, transcript);
+    let c =
+      Self::compute_batch_coefficients(points, rho, a).map_err(|_| NovaError::ProofVerifyError)?;
+
+    // C_L = sum_i rho^i / (a - z_i) * (C - y_i * g) - C_W
+    let c_sum = c.iter().sum::<E::Fr>();
+    let y_sum = zip_with!(iter, (c, pi.evals), |c_i, y_i| *c_i * y_i).sum::<E::Fr>();
+    let C_L = C.comm * c_sum - vk.g * y_sum - pi.C_W.to_curve();
+
+    let pairing_inputs: Vec<(E::G1Affine, E::G2Prepared)> =
+      vec![(pi.C_H, vk.beta_h.into()), ((pi.C_H * (-a) - C_L).to_affine(), vk.h.into())];
+
+    #[allow(clippy::map_identity)]
+    let pairing_input_refs = pairing_inputs.iter().map(|(a, b)| (a, b)).collect::<Vec<_>>();
+
+    let pairing_result = E::multi_miller_loop(pairing_input_refs.as_slice()).final_exponentiation();
+    let successful: bool = pairing_result.is_identity().into();
+    if !successful {
+      return Err(NovaError::ProofVerifyError);
+    }
+    Ok(())
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use bincode::Options;
@@ -531,6 +667,26 @@ mod tests {
     test_commitment_to_k_polynomial_correctness(&ck, &C, &poly, &point, &eval);
   }
 
+  #[test]
+  fn test_kzg_commit_blinded() {
+    let ck: CommitmentKey<NE> =
+      <KZGCommitmentEngine<E> as CommitmentEngineTrait<NE>>::setup(b"test", 8);
+    let v = (0..8u64).map(Fr::from).collect::<Vec<_>>();
+    let r = Fr::from(7);
+
+    // the blind is committed with the generator stored in the key
+    let comm: Commitment<NE> = KZGCommitmentEngine::commit_blinded(&ck, &v, r);
+    let unblinded: Commitment<NE> = KZGCommitmentEngine::commit(&ck, &v);
+    assert_eq!(comm.comm, unblinded.comm + ck.blinding_g * r);
+    assert!(KZGCommitmentEngine::verify_open(&ck, &comm, &v, r).is_ok());
+
+    // the blinding generator survives a serialization round-trip
+    let ck_deserialized: CommitmentKey<NE> =
+      bincode::deserialize(&bincode::serialize(&ck).unwrap()).unwrap();
+    assert_eq!(ck_deserialized.blinding_g, ck.blinding_g);
+    assert_eq!(KZGCommitmentEngine::commit_blinded(&ck_deserialized, &v, r), comm);
+  }
+
   #[test]
   fn test_hyperkzg_shplonk_pcs() {
     let n = 8;
@@ -847,6 +1003,39 @@ mod tests {
     }
   }
 
+  #[test]
+  fn test_hyperkzg_batch_open() {
+    let poly = (1..=8).map(Fr::from).collect::<Vec<_>>();
+    let points = [Fr::from(3), Fr::from(7), Fr::from(11), Fr::from(19)];
+
+    let ck: CommitmentKey<NE> =
+      <KZGCommitmentEngine<E> as CommitmentEngineTrait<NE>>::setup(b"test", poly.len());
+    let ck = Arc::new(ck);
+    let (_pk, vk): (KZGProverKey<E>, KZGVerifierKey<E>) =
+      EvaluationEngine::<E, NE>::setup(ck.clone());
+    let C: Commitment<NE> = KZGCommitmentEngine::commit(&ck, &poly);
+
+    let mut prover_transcript = Keccak256Transcript::new(b"TestBatchEval");
+    let proof =
+      EvaluationEngine::<E, NE>::prove_batch(&ck, &mut prover_transcript, &C, &poly, &points)
+        .unwrap();
+    assert_eq!(proof.evals.len(), points.len());
+    for (eval, point) in proof.evals.iter().zip_eq(points.iter()) {
+      assert_eq!(*eval, UniPoly::new(poly.clone()).evaluate(point));
+    }
+
+    let verify = |proof: &BatchEvalProof<E>| {
+      let mut verifier_transcript = Keccak256Transcript::<NE>::new(b"TestBatchEval");
+      EvaluationEngine::<E, NE>::verify_batch(&vk, &mut verifier_transcript, &C, &points, proof)
+    };
+    assert!(verify(&proof).is_ok());
+
+    // a single wrong claimed evaluation is rejected
+    let mut tampered = proof.clone();
+    tampered.evals[2] += Fr::ONE;
+    assert!(verify(&tampered).is_err());
+  }
+
   /// Compute powers of q : (1, q, q^2, ..., q^(k-1))
   fn batch_challenge_powers(q: Fr, k: usize) -> Vec<Fr> { powers(&q, k) }
 }
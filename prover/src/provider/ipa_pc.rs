@@ -347,8 +347,25 @@ where
 
 #[cfg(test)]
 mod test {
-  use crate::provider::{
-    ipa_pc::EvaluationEngine, util::test_utils::prove_verify_from_num_vars, GrumpkinEngine,
+  use std::sync::Arc;
+
+  use ff::Field;
+  use rand::{rngs::StdRng, SeedableRng};
+
+  use crate::{
+    errors::{NovaError, PCSError},
+    provider::{
+      ipa_pc::{EvaluationEngine, InnerProductArgument},
+      pedersen::CommitmentKeyExtTrait,
+      traits::DlogGroup,
+      util::test_utils::{prove_verify_from_num_vars, random_poly_with_eval},
+      Bn256EngineIPA, GrumpkinEngine,
+    },
+    traits::{
+      commitment::CommitmentEngineTrait, evaluation::EvaluationEngineTrait, Engine,
+      TranscriptEngineTrait,
+    },
+    CommitmentKey,
   };
 
   #[test]
@@ -357,4 +374,52 @@ mod test {
       prove_verify_from_num_vars::<_, EvaluationEngine<GrumpkinEngine>>(num_vars);
     }
   }
+
+  fn test_tampered_proof_with<E>()
+  where
+    E: Engine,
+    E::GE: DlogGroup,
+    CommitmentKey<E>: CommitmentKeyExtTrait<E>, {
+    let num_vars = 5;
+    let mut rng = StdRng::seed_from_u64(num_vars as u64);
+    let (poly, point, eval) = random_poly_with_eval::<E, StdRng>(num_vars, &mut rng);
+
+    let ck = Arc::new(E::CE::setup(b"test", 1 << num_vars));
+    let comm = E::CE::commit(&ck, poly.evaluations());
+    let (pk, vk) = EvaluationEngine::<E>::setup(ck.clone());
+
+    let mut transcript = E::TE::new(b"TestEval");
+    let proof = EvaluationEngine::<E>::prove(
+      &ck,
+      &pk,
+      &mut transcript,
+      &comm,
+      poly.evaluations(),
+      &point,
+      &eval,
+    )
+    .unwrap();
+
+    let verify = |proof: &InnerProductArgument<E>| {
+      let mut transcript = E::TE::new(b"TestEval");
+      EvaluationEngine::<E>::verify(&vk, &mut transcript, &comm, &point, &eval, proof)
+    };
+    verify(&proof).unwrap();
+
+    let invalid = Err(NovaError::PCSError(PCSError::InvalidPCS));
+
+    let mut tampered = proof.clone();
+    tampered.a_hat += E::Scalar::ONE;
+    assert_eq!(verify(&tampered), invalid);
+
+    let mut tampered = proof;
+    tampered.L_vec.swap(0, 1);
+    assert_eq!(verify(&tampered), invalid);
+  }
+
+  #[test]
+  fn test_tampered_proof() {
+    test_tampered_proof_with::<GrumpkinEngine>();
+    test_tampered_proof_with::<Bn256EngineIPA>();
+  }
 }
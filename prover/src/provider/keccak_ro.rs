@@ -0,0 +1,152 @@
+//! This module provides a Keccak256-based RO for native Fiat-Shamir that an
+//! EVM verifier can recompute, where circuit-friendliness is not required
+use core::marker::PhantomData;
+
+use ff::{PrimeField, PrimeFieldBits};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+use crate::{
+  errors::NovaError,
+  provider::poseidon::{check_num_absorbs, PoseidonConstantsCircuit, PoseidonROCircuit},
+  traits::ROTrait,
+};
+
+// the width of an EVM word, which every absorbed field element is encoded to
+const WORD_SIZE: usize = 32;
+
+/// A Keccak256-based RO to use outside circuits.
+///
+/// Field elements are absorbed as 32-byte big-endian words and a challenge is
+/// the low `num_bits` bits of the keccak256 of their concatenation, read as a
+/// big-endian integer, i.e. what a Solidity verifier computes as
+/// `uint256(keccak256(abi.encodePacked(e_1, ..., e_n))) & ((1 << num_bits) - 1)`.
+/// As with [`Blake3RO`](super::blake3::Blake3RO), `CircuitRO` points at
+/// [`PoseidonROCircuit`] only to satisfy [`ROTrait`], so this RO must not be
+/// used where the verifier circuit has to recompute its challenges.
+#[derive(Debug, Clone)]
+pub struct Keccak256RO<Base, Scalar>
+where
+  Base: PrimeField,
+  Scalar: PrimeField, {
+  hasher:      Keccak256,
+  num_absorbs: usize,
+  absorbed:    usize,
+  squeezed:    bool,
+  _p:          PhantomData<(Base, Scalar)>,
+}
+
+impl<Base, Scalar> ROTrait<Base, Scalar> for Keccak256RO<Base, Scalar>
+where
+  Base: PrimeField + PrimeFieldBits + Serialize + for<'de> Deserialize<'de>,
+  Scalar: PrimeField,
+{
+  type CircuitRO = PoseidonROCircuit<Base>;
+  type Constants = PoseidonConstantsCircuit<Base>;
+
+  fn new(_constants: PoseidonConstantsCircuit<Base>, num_absorbs: usize) -> Self {
+    Self { hasher: Keccak256::new(), num_absorbs, absorbed: 0, squeezed: false, _p: PhantomData }
+  }
+
+  /// Absorb a new number into the state of the oracle
+  fn absorb(&mut self, e: Base) {
+    assert!(!self.squeezed, "Cannot absorb after squeezing");
+    let repr = e.to_repr();
+    let mut word = [0u8; WORD_SIZE];
+    // field representations are little-endian, EVM words are big-endian
+    for (byte, repr_byte) in word.iter_mut().rev().zip(repr.as_ref()) {
+      *byte = *repr_byte;
+    }
+    self.hasher.update(word);
+    self.absorbed += 1;
+  }
+
+  /// Compute a challenge by hashing the current state. Returns
+  /// [`NovaError::SpongeError`] if the oracle was already squeezed, or if the
+  /// number of absorbed elements differs from the declared `num_absorbs`.
+  fn squeeze(&mut self, num_bits: usize) -> Result<Scalar, NovaError> {
+    if num_bits > Scalar::NUM_BITS as usize {
+      return Err(NovaError::InvalidNumBits);
+    }
+
+    // check if we have squeezed already
+    if self.squeezed {
+      return Err(NovaError::SpongeError("cannot squeeze again after squeezing".to_string()));
+    }
+    self.squeezed = true;
+    check_num_absorbs(Some(self.num_absorbs), self.absorbed)?;
+
+    let output = self.hasher.clone().finalize();
+
+    // Only return the `num_bits` low-order bits of the big-endian output
+    let mut res = Scalar::ZERO;
+    let mut coeff = Scalar::ONE;
+    for i in 0..num_bits {
+      if (output[WORD_SIZE - 1 - i / 8] >> (i % 8)) & 1 == 1 {
+        res += coeff;
+      }
+      coeff += coeff;
+    }
+    Ok(res)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    constants::NUM_CHALLENGE_BITS,
+    provider::{Bn256EngineKZG, GrumpkinEngine},
+    traits::Engine,
+  };
+
+  fn squeeze<E: Engine>(inputs: &[u64], num_bits: usize) -> E::Scalar {
+    let mut ro: Keccak256RO<E::Base, E::Scalar> =
+      Keccak256RO::new(PoseidonConstantsCircuit::default(), inputs.len());
+    inputs.iter().for_each(|x| ro.absorb(E::Base::from(*x)));
+    ro.squeeze(num_bits).unwrap()
+  }
+
+  fn test_keccak_ro_with<E: Engine>() {
+    // keccak256(abi.encodePacked(uint256(1), uint256(2))) =
+    // 0xe90b7bceb6e7df5418fb78d8ee546e97c83a08bbccc01a0644d599ccd2a7c2e0
+    assert_eq!(
+      squeeze::<E>(&[1, 2], NUM_CHALLENGE_BITS),
+      E::Scalar::from_u128(0xc83a08bbccc01a0644d599ccd2a7c2e0)
+    );
+    assert_eq!(squeeze::<E>(&[1, 2], 16), E::Scalar::from(0xc2e0));
+
+    // a different absorb sequence diverges
+    assert_ne!(
+      squeeze::<E>(&[1, 2], NUM_CHALLENGE_BITS),
+      squeeze::<E>(&[2, 1], NUM_CHALLENGE_BITS)
+    );
+
+    // squeezing twice is an error rather than a panic
+    let mut ro: Keccak256RO<E::Base, E::Scalar> =
+      Keccak256RO::new(PoseidonConstantsCircuit::default(), 2);
+    ro.absorb(E::Base::from(1));
+    ro.absorb(E::Base::from(2));
+    assert!(ro.squeeze(NUM_CHALLENGE_BITS).is_ok());
+    assert_eq!(
+      ro.squeeze(NUM_CHALLENGE_BITS),
+      Err(NovaError::SpongeError("cannot squeeze again after squeezing".to_string()))
+    );
+
+    // so is squeezing after fewer absorbs than declared
+    let mut ro: Keccak256RO<E::Base, E::Scalar> =
+      Keccak256RO::new(PoseidonConstantsCircuit::default(), 3);
+    ro.absorb(E::Base::from(1));
+    ro.absorb(E::Base::from(2));
+    assert_eq!(
+      ro.squeeze(NUM_CHALLENGE_BITS),
+      Err(NovaError::SpongeError("declared 3 absorbs, absorbed 2".to_string()))
+    );
+  }
+
+  #[test]
+  fn test_keccak_ro() {
+    test_keccak_ro_with::<Bn256EngineKZG>();
+    test_keccak_ro_with::<GrumpkinEngine>();
+  }
+}
@@ -12,9 +12,14 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
   digest::SimpleDigestible,
+  errors::NovaError,
   fast_serde,
   fast_serde::{FastSerde, SerdeByteError, SerdeByteTypes},
-  provider::{pedersen::Commitment, traits::DlogGroup, util::fb_msm},
+  provider::{
+    pedersen::{Commitment, BLINDING_LABEL},
+    traits::DlogGroup,
+    util::fb_msm,
+  },
   traits::{
     commitment::{CommitmentEngineTrait, Len},
     Engine as NovaEngine, Group, TranscriptReprTrait,
@@ -25,7 +30,7 @@ use crate::{
 #[derive(Debug, Clone, Eq, Serialize, Deserialize)]
 #[serde(bound(
   serialize = "E::G1Affine: Serialize, E::G2Affine: Serialize",
-  deserialize = "E::G1Affine: Deserialize<'de>, E::G2Affine: Deserialize<'de>"
+  deserialize = "E::G1Affine: Deserialize<'de>, E::G2Affine: Deserialize<'de>, E::G1: Group"
 ))]
 pub struct UniversalKZGParam<E: Engine> {
   /// Group elements of the form `{ β^i G }`, where `i` ranges from 0 to
@@ -34,6 +39,16 @@ pub struct UniversalKZGParam<E: Engine> {
   /// Group elements of the form `{ β^i H }`, where `i` ranges from 0 to
   /// `degree`.
   pub powers_of_h: Vec<E::G2Affine>,
+  /// The G1 generator blinding factors are committed with, hashed to the
+  /// curve so that its discrete logarithm with respect to `powers_of_g` is
+  /// unknown
+  #[serde(skip, default = "blinding_generator::<E>")]
+  pub blinding_g:  E::G1Affine,
+}
+
+fn blinding_generator<E: Engine>() -> E::G1Affine
+where E::G1: Group {
+  <E::G1 as Group>::hash_to_group(BLINDING_LABEL).to_affine()
 }
 
 impl<E: Engine> PartialEq for UniversalKZGParam<E> {
@@ -127,6 +142,7 @@ impl<E: Engine> UniversalKZGParam<E> {
 
 impl<E: Engine> FastSerde for UniversalKZGParam<E>
 where
+  E::G1: Group,
   E::G1Affine: SerdeObject,
   E::G2Affine: SerdeObject,
 {
@@ -179,12 +195,14 @@ where
       .map(|bytes| E::G2Affine::from_raw_bytes(bytes).ok_or(SerdeByteError::G2DecodeError))
       .collect::<Result<Vec<_>, _>>()?;
 
-    Ok(Self { powers_of_g, powers_of_h })
+    Ok(Self { powers_of_g, powers_of_h, blinding_g: blinding_generator::<E>() })
   }
 }
 
 impl<E: Engine> UniversalKZGParam<E>
-where E::Fr: PrimeFieldBits
+where
+  E::G1: Group,
+  E::Fr: PrimeFieldBits,
 {
   /// Build SRS for testing.
   /// WARNING: THIS FUNCTION IS FOR TESTING PURPOSE ONLY.
@@ -224,7 +242,7 @@ where E::Fr: PrimeFieldBits
       || E::G2::batch_normalize(&powers_of_h_projective, &mut powers_of_h),
     );
 
-    Self { powers_of_g, powers_of_h }
+    Self { powers_of_g, powers_of_h, blinding_g: blinding_generator::<E>() }
   }
 }
 
@@ -280,10 +298,41 @@ where
     UniversalKZGParam::gen_srs_for_testing(rng, n.next_power_of_two())
   }
 
+  fn trim(ck: &Self::CommitmentKey, n: usize) -> Result<Self::CommitmentKey, NovaError> {
+    if n > ck.powers_of_g.len() {
+      return Err(NovaError::CommitmentKeyTooSmall { needed: n, have: ck.powers_of_g.len() });
+    }
+    // the G2 powers are not commitment bases, and the verifier needs them all
+    Ok(UniversalKZGParam {
+      powers_of_g: ck.powers_of_g[..n].to_vec(),
+      powers_of_h: ck.powers_of_h.clone(),
+      blinding_g:  ck.blinding_g,
+    })
+  }
+
   fn commit(ck: &Self::CommitmentKey, v: &[<E::G1 as Group>::Scalar]) -> Self::Commitment {
     assert!(ck.length() >= v.len());
     Commitment { comm: E::G1::vartime_multiscalar_mul(v, &ck.powers_of_g[..v.len()]) }
   }
+
+  fn commit_sparse(
+    ck: &Self::CommitmentKey,
+    indices: &[usize],
+    values: &[<E::G1 as Group>::Scalar],
+  ) -> Self::Commitment {
+    assert_eq!(indices.len(), values.len());
+    let bases = indices.iter().map(|i| ck.powers_of_g[*i]).collect::<Vec<_>>();
+    Commitment { comm: E::G1::vartime_multiscalar_mul(values, &bases) }
+  }
+
+  fn commit_blinded(
+    ck: &Self::CommitmentKey,
+    v: &[<E::G1 as Group>::Scalar],
+    r: <E::G1 as Group>::Scalar,
+  ) -> Self::Commitment {
+    let comm: Commitment<NE> = Self::commit(ck, v);
+    Commitment { comm: comm.comm + ck.blinding_g * r }
+  }
 }
 
 impl<E: Engine, NE: NovaEngine<GE = E::G1, Scalar = E::Fr>> From<Commitment<NE>>
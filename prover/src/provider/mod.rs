@@ -5,10 +5,18 @@
 pub mod hyperkzg;
 pub mod ipa_pc;
 
+// public modules providing native-only ROs for Fiat-Shamir
+pub mod blake3;
+pub mod keccak_ro;
+// public module providing a Poseidon-based transcript for Spartan
+pub mod poseidon_transcript;
+
 // crate-public modules, made crate-public mostly for tests
 pub(crate) mod bn256_grumpkin;
+pub(crate) mod pasta;
 mod pedersen;
 pub(crate) mod poseidon;
+pub(crate) mod secp_secq;
 pub(crate) mod traits;
 // a non-hiding variant of kzg
 mod kzg_commitment;
@@ -19,14 +27,18 @@ mod keccak;
 mod tests;
 
 use halo2curves::bn256::Bn256;
+pub use poseidon::{poseidon_hash2, PoseidonArity, PoseidonROSameField};
+pub use util::msm::MsmConfig;
 
 use self::kzg_commitment::KZGCommitmentEngine;
 use crate::{
   provider::{
     bn256_grumpkin::{bn256, grumpkin},
     keccak::Keccak256Transcript,
+    pasta::{pallas, vesta},
     pedersen::CommitmentEngine as PedersenCommitmentEngine,
     poseidon::{PoseidonRO, PoseidonROCircuit},
+    secp_secq::{secp256k1, secq256k1},
   },
   traits::{CurveCycleEquipped, Engine},
 };
@@ -89,6 +101,66 @@ impl Engine for Bn256EngineKZG {
   type TE = Keccak256Transcript<Self>;
 }
 
+/// An implementation of the Nova `Engine` trait with Pallas curve and Pedersen
+/// commitment scheme
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PallasEngine;
+
+/// An implementation of the Nova `Engine` trait with Vesta curve and Pedersen
+/// commitment scheme
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct VestaEngine;
+
+impl Engine for PallasEngine {
+  type Base = pallas::Base;
+  type CE = PedersenCommitmentEngine<Self>;
+  type GE = pallas::Point;
+  type RO = PoseidonRO<Self::Base, Self::Scalar>;
+  type ROCircuit = PoseidonROCircuit<Self::Base>;
+  type Scalar = pallas::Scalar;
+  type TE = Keccak256Transcript<Self>;
+}
+
+impl Engine for VestaEngine {
+  type Base = vesta::Base;
+  type CE = PedersenCommitmentEngine<Self>;
+  type GE = vesta::Point;
+  type RO = PoseidonRO<Self::Base, Self::Scalar>;
+  type ROCircuit = PoseidonROCircuit<Self::Base>;
+  type Scalar = vesta::Scalar;
+  type TE = Keccak256Transcript<Self>;
+}
+
+/// An implementation of the Nova `Engine` trait with Secp256k1 curve and
+/// Pedersen commitment scheme
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Secp256k1Engine;
+
+/// An implementation of the Nova `Engine` trait with Secq256k1 curve and
+/// Pedersen commitment scheme
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Secq256k1Engine;
+
+impl Engine for Secp256k1Engine {
+  type Base = secp256k1::Base;
+  type CE = PedersenCommitmentEngine<Self>;
+  type GE = secp256k1::Point;
+  type RO = PoseidonRO<Self::Base, Self::Scalar>;
+  type ROCircuit = PoseidonROCircuit<Self::Base>;
+  type Scalar = secp256k1::Scalar;
+  type TE = Keccak256Transcript<Self>;
+}
+
+impl Engine for Secq256k1Engine {
+  type Base = secq256k1::Base;
+  type CE = PedersenCommitmentEngine<Self>;
+  type GE = secq256k1::Point;
+  type RO = PoseidonRO<Self::Base, Self::Scalar>;
+  type ROCircuit = PoseidonROCircuit<Self::Base>;
+  type Scalar = secq256k1::Scalar;
+  type TE = Keccak256Transcript<Self>;
+}
+
 impl CurveCycleEquipped for Bn256EngineIPA {
   type Secondary = GrumpkinEngine;
 }
@@ -101,21 +173,43 @@ impl CurveCycleEquipped for Bn256EngineZM {
   type Secondary = GrumpkinEngine;
 }
 
+impl CurveCycleEquipped for PallasEngine {
+  type Secondary = VestaEngine;
+}
+
+impl CurveCycleEquipped for Secp256k1Engine {
+  type Secondary = Secq256k1Engine;
+}
+
 #[cfg(test)]
 mod test {
   use std::io::Read;
 
   use digest::{ExtendableOutput, Update};
-  use group::{ff::Field, Curve, Group};
+  use group::{
+    ff::{Field, PrimeField},
+    Curve, Group,
+  };
   use halo2curves::{CurveAffine, CurveExt};
   use itertools::Itertools as _;
   use rand_core::OsRng;
   use sha3::Shake256;
 
-  use crate::provider::{
-    bn256_grumpkin::{bn256, grumpkin},
-    traits::DlogGroup,
-    util::msm::cpu_best_msm,
+  use crate::{
+    constants::NUM_CHALLENGE_BITS,
+    errors::NovaError,
+    provider::{
+      bn256_grumpkin::{bn256, grumpkin},
+      traits::DlogGroup,
+      util::msm::cpu_best_msm,
+      Bn256EngineIPA, Bn256EngineKZG, GrumpkinEngine, PallasEngine, Secp256k1Engine, VestaEngine,
+    },
+    traits::{
+      self,
+      commitment::{CommitmentEngineTrait, CommitmentTrait, Len},
+      AbsorbInROTrait, CurveCycleEquipped, Dual, Engine, ROConstants, ROTrait,
+    },
+    Commitment,
   };
 
   macro_rules! impl_cycle_pair_test {
@@ -167,4 +261,211 @@ mod test {
   fn test_bn256_from_label() {
     impl_cycle_pair_test!(bn256);
   }
+
+  fn test_hash_to_curve_with<E: Engine>()
+  where E::GE: DlogGroup {
+    let (A, B, ..) = <E::GE as traits::Group>::group_params();
+    let points = (0..64u64).map(|i| E::hash_to_curve(&i.to_le_bytes())).collect::<Vec<_>>();
+
+    // every output is a finite point satisfying y^2 = x^3 + Ax + B
+    for point in &points {
+      let (x, y, is_infinity) = point.to_coordinates();
+      assert!(!is_infinity);
+      assert_eq!(y.square(), x.square() * x + A * x + B);
+    }
+
+    // the map is deterministic, and distinct inputs give distinct points
+    assert_eq!(E::hash_to_curve(&0u64.to_le_bytes()), points[0]);
+    assert!(points.iter().tuple_combinations().all(|(p, q)| p != q));
+  }
+
+  #[test]
+  fn test_hash_to_curve() {
+    test_hash_to_curve_with::<Bn256EngineKZG>();
+    test_hash_to_curve_with::<GrumpkinEngine>();
+    test_hash_to_curve_with::<PallasEngine>();
+    test_hash_to_curve_with::<VestaEngine>();
+    test_hash_to_curve_with::<Secp256k1Engine>();
+  }
+
+  fn test_commitment_subgroup_with<E: Engine>() {
+    let ck = E::CE::setup(b"test", 8);
+    let mut rng = E::test_rng(0);
+    let v = (0..8).map(|_| E::Scalar::random(&mut rng)).collect::<Vec<_>>();
+
+    // the supported curves have no cofactor, so every commitment passes
+    assert_eq!(E::COFACTOR, 1);
+    assert!(E::CE::commit(&ck, &v).is_in_correct_subgroup());
+    assert!(Commitment::<E>::default().is_in_correct_subgroup());
+  }
+
+  #[test]
+  fn test_commitment_subgroup() {
+    test_commitment_subgroup_with::<Bn256EngineIPA>();
+    test_commitment_subgroup_with::<Bn256EngineKZG>();
+    test_commitment_subgroup_with::<GrumpkinEngine>();
+    test_commitment_subgroup_with::<PallasEngine>();
+    test_commitment_subgroup_with::<Secp256k1Engine>();
+  }
+
+  fn test_absorb_identity_with<E: Engine>() {
+    let squeeze = |absorb: &dyn Fn(&mut E::RO)| {
+      let mut ro = E::RO::new(ROConstants::<E>::default(), 3);
+      absorb(&mut ro);
+      ro.squeeze(NUM_CHALLENGE_BITS).unwrap()
+    };
+    let absorb_coordinates = |x: u64, y: u64, is_infinity: u64| {
+      squeeze(&|ro| [x, y, is_infinity].iter().for_each(|e| ro.absorb(E::Base::from(*e))))
+    };
+
+    // the identity absorbs as zero coordinates flagged as infinity, unlike a
+    // finite point with zero coordinates
+    let identity = squeeze(&|ro| Commitment::<E>::default().absorb_in_ro(ro));
+    assert_eq!(identity, absorb_coordinates(0, 0, 1));
+    assert_ne!(identity, absorb_coordinates(0, 0, 0));
+  }
+
+  #[test]
+  fn test_absorb_identity() {
+    test_absorb_identity_with::<Bn256EngineKZG>();
+    test_absorb_identity_with::<GrumpkinEngine>();
+    test_absorb_identity_with::<PallasEngine>();
+  }
+
+  fn reduce<F: Field + From<u64>>(bytes: &[u8]) -> F {
+    bytes.iter().fold(F::ZERO, |acc, byte| acc * F::from(256) + F::from(u64::from(*byte)))
+  }
+
+  fn test_modulus_bytes_with<E: Engine>() {
+    // the moduli reduce to zero in their fields, and one less to minus one
+    let scalar_modulus = E::scalar_modulus_bytes();
+    let base_modulus = E::base_modulus_bytes();
+    assert_eq!(reduce::<E::Scalar>(&scalar_modulus), E::Scalar::ZERO);
+    assert_eq!(reduce::<E::Base>(&base_modulus), E::Base::ZERO);
+
+    let mut scalar_modulus_minus_one = scalar_modulus.clone();
+    *scalar_modulus_minus_one.last_mut().unwrap() -= 1;
+    assert_eq!(reduce::<E::Scalar>(&scalar_modulus_minus_one), -E::Scalar::ONE);
+    assert_eq!(scalar_modulus.len(), (E::Scalar::NUM_BITS as usize).div_ceil(8));
+  }
+
+  #[test]
+  fn test_modulus_bytes() {
+    test_modulus_bytes_with::<Bn256EngineKZG>();
+    test_modulus_bytes_with::<GrumpkinEngine>();
+    test_modulus_bytes_with::<PallasEngine>();
+    test_modulus_bytes_with::<Secp256k1Engine>();
+  }
+
+  fn test_base_to_scalar_with<E: Engine>() {
+    // small values carry over unchanged
+    assert_eq!(E::base_to_scalar(E::Base::ZERO), Some(E::Scalar::ZERO));
+    assert_eq!(E::base_to_scalar(E::Base::from(42)), Some(E::Scalar::from(42)));
+
+    // on these curves the base modulus exceeds the scalar one, so the scalar
+    // modulus is a base element that does not fit while one less still does
+    let scalar_modulus = reduce::<E::Base>(&E::scalar_modulus_bytes());
+    assert_eq!(E::base_to_scalar(scalar_modulus), None);
+    assert_eq!(E::base_to_scalar(-E::Base::ONE), None);
+    assert_eq!(E::base_to_scalar(scalar_modulus - E::Base::ONE), Some(-E::Scalar::ONE));
+  }
+
+  #[test]
+  fn test_base_to_scalar() {
+    test_base_to_scalar_with::<Bn256EngineKZG>();
+    test_base_to_scalar_with::<VestaEngine>();
+    test_base_to_scalar_with::<Secp256k1Engine>();
+  }
+
+  fn test_trim_commitment_key_with<E: Engine>() {
+    let ck = <E::CE as CommitmentEngineTrait<E>>::setup(b"test", 16);
+    let n = 10;
+    let ck_trimmed = <E::CE as CommitmentEngineTrait<E>>::trim(&ck, n).unwrap();
+    assert_eq!(ck_trimmed.length(), n);
+
+    // vectors of length up to n commit identically under both keys
+    for len in [n, n - 3] {
+      let v = (0..len).map(|_| E::Scalar::random(OsRng)).collect::<Vec<_>>();
+      assert_eq!(
+        <E::CE as CommitmentEngineTrait<E>>::commit(&ck_trimmed, &v),
+        <E::CE as CommitmentEngineTrait<E>>::commit(&ck, &v)
+      );
+    }
+
+    // a key cannot grow
+    assert_eq!(
+      <E::CE as CommitmentEngineTrait<E>>::trim(&ck, ck.length() + 1).unwrap_err(),
+      NovaError::CommitmentKeyTooSmall { needed: ck.length() + 1, have: ck.length() }
+    );
+  }
+
+  #[test]
+  fn test_trim_commitment_key() {
+    test_trim_commitment_key_with::<Bn256EngineIPA>();
+    test_trim_commitment_key_with::<Bn256EngineKZG>();
+    test_trim_commitment_key_with::<PallasEngine>();
+  }
+
+  fn test_commit_sparse_with<E: Engine>() {
+    let n = 200;
+    let ck = <E::CE as CommitmentEngineTrait<E>>::setup(b"test", n);
+
+    // a vector with 5% of its entries set
+    let indices = (0..n).step_by(20).collect::<Vec<_>>();
+    let values = indices.iter().map(|_| E::Scalar::random(OsRng)).collect::<Vec<_>>();
+    let mut v = vec![E::Scalar::ZERO; n];
+    for (i, value) in indices.iter().zip(&values) {
+      v[*i] = *value;
+    }
+
+    assert_eq!(
+      <E::CE as CommitmentEngineTrait<E>>::commit_sparse(&ck, &indices, &values),
+      <E::CE as CommitmentEngineTrait<E>>::commit(&ck, &v)
+    );
+  }
+
+  #[test]
+  fn test_commit_sparse() {
+    test_commit_sparse_with::<Bn256EngineIPA>();
+    test_commit_sparse_with::<Bn256EngineKZG>();
+    test_commit_sparse_with::<PallasEngine>();
+  }
+
+  fn test_test_rng_with<E: Engine>() {
+    let draw = |seed: u64| {
+      let mut rng = E::test_rng(seed);
+      (0..16).map(|_| E::Scalar::random(&mut rng)).collect::<Vec<_>>()
+    };
+
+    // the same seed reproduces the same sequence, another seed does not
+    assert_eq!(draw(42), draw(42));
+    assert_ne!(draw(42), draw(43));
+  }
+
+  #[test]
+  fn test_test_rng() {
+    test_test_rng_with::<Bn256EngineKZG>();
+    test_test_rng_with::<GrumpkinEngine>();
+    test_test_rng_with::<PallasEngine>();
+  }
+
+  fn test_is_cycle_with<E: CurveCycleEquipped>() {
+    assert!(E::is_cycle_with::<Dual<E>>());
+    assert!(<Dual<E>>::is_cycle_with::<E>());
+    // an engine is not a cycle with itself
+    assert!(!E::is_cycle_with::<E>());
+  }
+
+  #[test]
+  fn test_is_cycle() {
+    test_is_cycle_with::<Bn256EngineIPA>();
+    test_is_cycle_with::<Bn256EngineKZG>();
+    test_is_cycle_with::<PallasEngine>();
+    test_is_cycle_with::<Secp256k1Engine>();
+
+    // engines of different cycles do not pair
+    assert!(!Bn256EngineKZG::is_cycle_with::<VestaEngine>());
+    assert!(!PallasEngine::is_cycle_with::<GrumpkinEngine>());
+    assert!(!Secp256k1Engine::is_cycle_with::<PallasEngine>());
+  }
 }
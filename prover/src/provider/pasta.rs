@@ -0,0 +1,250 @@
+//! This module implements the Nova traits for `pallas::Point`, `pallas::Scalar`,
+//! `vesta::Point`, `vesta::Scalar`.
+use std::io::Read;
+
+use digest::{ExtendableOutput, Update};
+use ff::{Field, FromUniformBytes, PrimeField};
+use group::{prime::PrimeCurveAffine, Curve, Group as AnotherGroup};
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+use grumpkin_msm::pasta::{pallas as pallas_msm, vesta as vesta_msm};
+use halo2curves::{CurveAffine, CurveExt};
+use num_bigint::BigInt;
+use num_traits::Num;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use sha3::Shake256;
+
+use crate::{
+  provider::{
+    traits::DlogGroup,
+    util::msm::{cpu_best_msm, cpu_serial_msm},
+    MsmConfig,
+  },
+  traits::{Group, PrimeFieldExt, TranscriptReprTrait},
+};
+
+/// Re-exports that give access to the standard aliases used in the code base,
+/// for pallas
+pub mod pallas {
+  pub use pasta_curves::pallas::{Affine, Base, Point, Scalar};
+
+  pub use super::PallasCompressedElementWrapper as Compressed;
+}
+
+/// Re-exports that give access to the standard aliases used in the code base,
+/// for vesta
+pub mod vesta {
+  pub use pasta_curves::vesta::{Affine, Base, Point, Scalar};
+
+  pub use super::VestaCompressedElementWrapper as Compressed;
+}
+
+/// A wrapper for compressed group elements of pallas
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PallasCompressedElementWrapper([u8; 32]);
+
+/// A wrapper for compressed group elements of vesta
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct VestaCompressedElementWrapper([u8; 32]);
+
+// The pasta curves do not ship a serializable compressed point type, so this
+// mirrors `impl_traits!` with the compressed representation wrapped above.
+macro_rules! impl_pasta_traits {
+  ($name:ident, $order_str:literal, $base_str:literal, $large_msm_method:ident) => {
+    impl From<[u8; 32]> for $name::Compressed {
+      fn from(repr: [u8; 32]) -> Self { Self(repr) }
+    }
+
+    impl From<$name::Compressed> for [u8; 32] {
+      fn from(compressed: $name::Compressed) -> Self { compressed.0 }
+    }
+
+    impl Group for $name::Point {
+      type Base = $name::Base;
+      type Scalar = $name::Scalar;
+
+      fn group_params() -> (Self::Base, Self::Base, BigInt, BigInt) {
+        let A = $name::Point::a();
+        let B = $name::Point::b();
+        let order = BigInt::from_str_radix($order_str, 16).unwrap();
+        let base = BigInt::from_str_radix($base_str, 16).unwrap();
+
+        (A, B, order, base)
+      }
+
+      fn hash_to_group(bytes: &[u8]) -> Self {
+        $name::Point::hash_to_curve($crate::constants::HASH_TO_CURVE_DOMAIN)(bytes)
+      }
+    }
+
+    impl DlogGroup for $name::Point {
+      type AffineExt = $name::Affine;
+      type Compressed = $name::Compressed;
+      type ScalarExt = $name::Scalar;
+
+      fn vartime_multiscalar_mul(scalars: &[Self::ScalarExt], bases: &[Self::AffineExt]) -> Self {
+        #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+        if scalars.len() >= 128 {
+          $large_msm_method(bases, scalars)
+        } else {
+          cpu_best_msm(bases, scalars)
+        }
+        #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+        cpu_best_msm(bases, scalars)
+      }
+
+      fn vartime_multiscalar_mul_with(
+        msm: MsmConfig,
+        scalars: &[Self::ScalarExt],
+        bases: &[Self::AffineExt],
+      ) -> Self {
+        match msm {
+          MsmConfig::Auto => Self::vartime_multiscalar_mul(scalars, bases),
+          MsmConfig::Rayon => cpu_best_msm(bases, scalars),
+          MsmConfig::Serial => cpu_serial_msm(bases, scalars),
+        }
+      }
+
+      fn from_label(label: &'static [u8], n: usize) -> Vec<Self::Affine> {
+        let mut shake = Shake256::default();
+        shake.update(label);
+        let mut reader = shake.finalize_xof();
+        let mut uniform_bytes_vec = Vec::new();
+        for _ in 0..n {
+          let mut uniform_bytes = [0u8; 32];
+          reader.read_exact(&mut uniform_bytes).unwrap();
+          uniform_bytes_vec.push(uniform_bytes);
+        }
+        Self::hash_to_affine(&uniform_bytes_vec)
+      }
+
+      fn hash_to_affine(uniform_bytes: &[[u8; 32]]) -> Vec<Self::Affine> {
+        let gens_proj: Vec<$name::Point> = uniform_bytes
+          .par_iter()
+          .map(|bytes| {
+            let hash = $name::Point::hash_to_curve("from_uniform_bytes");
+            hash(bytes)
+          })
+          .collect();
+
+        let mut gens = vec![$name::Affine::identity(); uniform_bytes.len()];
+        <Self as Curve>::batch_normalize(&gens_proj, &mut gens);
+        gens
+      }
+
+      fn to_coordinates(&self) -> (Self::Base, Self::Base, bool) {
+        let coordinates = self.to_affine().coordinates();
+        if coordinates.is_some().unwrap_u8() == 1 && ($name::Point::identity() != *self) {
+          (*coordinates.unwrap().x(), *coordinates.unwrap().y(), false)
+        } else {
+          (Self::Base::ZERO, Self::Base::ZERO, true)
+        }
+      }
+    }
+
+    impl PrimeFieldExt for $name::Scalar {
+      fn from_uniform(bytes: &[u8]) -> Self {
+        let bytes_arr: [u8; 64] = bytes.try_into().unwrap();
+        $name::Scalar::from_uniform_bytes(&bytes_arr)
+      }
+    }
+
+    impl<G: DlogGroup> TranscriptReprTrait<G> for $name::Compressed {
+      fn to_transcript_bytes(&self) -> Vec<u8> { self.0.to_vec() }
+    }
+
+    impl<G: Group> TranscriptReprTrait<G> for $name::Scalar {
+      fn to_transcript_bytes(&self) -> Vec<u8> { self.to_repr().to_vec() }
+    }
+
+    impl<G: DlogGroup> TranscriptReprTrait<G> for $name::Affine {
+      fn to_transcript_bytes(&self) -> Vec<u8> {
+        let (x, y, is_infinity_byte) = {
+          let coordinates = self.coordinates();
+          if coordinates.is_some().unwrap_u8() == 1 && ($name::Affine::identity() != *self) {
+            let c = coordinates.unwrap();
+            (*c.x(), *c.y(), u8::from(false))
+          } else {
+            ($name::Base::ZERO, $name::Base::ZERO, u8::from(false))
+          }
+        };
+
+        x.to_repr()
+          .into_iter()
+          .chain(y.to_repr().into_iter())
+          .chain(std::iter::once(is_infinity_byte))
+          .collect()
+      }
+    }
+  };
+}
+
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+impl_pasta_traits!(
+  pallas,
+  "40000000000000000000000000000000224698fc0994a8dd8c46eb2100000001",
+  "40000000000000000000000000000000224698fc094cf91b992d30ed00000001",
+  pallas_msm
+);
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+impl_pasta_traits!(
+  pallas,
+  "40000000000000000000000000000000224698fc0994a8dd8c46eb2100000001",
+  "40000000000000000000000000000000224698fc094cf91b992d30ed00000001",
+  cpu_best_msm
+);
+
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+impl_pasta_traits!(
+  vesta,
+  "40000000000000000000000000000000224698fc094cf91b992d30ed00000001",
+  "40000000000000000000000000000000224698fc0994a8dd8c46eb2100000001",
+  vesta_msm
+);
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+impl_pasta_traits!(
+  vesta,
+  "40000000000000000000000000000000224698fc094cf91b992d30ed00000001",
+  "40000000000000000000000000000000224698fc0994a8dd8c46eb2100000001",
+  cpu_best_msm
+);
+
+#[cfg(test)]
+mod tests {
+  use ff::Field;
+  use rand::thread_rng;
+
+  use crate::provider::{
+    pasta::{pallas, vesta},
+    traits::DlogGroup,
+    util::msm::cpu_best_msm,
+  };
+
+  #[test]
+  fn test_pallas_msm_correctness() {
+    let npoints = 1usize << 16;
+    let points = pallas::Point::from_label(b"test", npoints);
+
+    let mut rng = thread_rng();
+    let scalars = (0..npoints).map(|_| pallas::Scalar::random(&mut rng)).collect::<Vec<_>>();
+
+    let cpu_msm = cpu_best_msm(&points, &scalars);
+    let gpu_msm = pallas::Point::vartime_multiscalar_mul(&scalars, &points);
+
+    assert_eq!(cpu_msm, gpu_msm);
+  }
+
+  #[test]
+  fn test_vesta_msm_correctness() {
+    let npoints = 1usize << 16;
+    let points = vesta::Point::from_label(b"test", npoints);
+
+    let mut rng = thread_rng();
+    let scalars = (0..npoints).map(|_| vesta::Scalar::random(&mut rng)).collect::<Vec<_>>();
+
+    let cpu_msm = cpu_best_msm(&points, &scalars);
+    let gpu_msm = vesta::Point::vartime_multiscalar_mul(&scalars, &points);
+
+    assert_eq!(cpu_msm, gpu_msm);
+  }
+}
@@ -4,8 +4,13 @@ use core::{
   marker::PhantomData,
   ops::{Add, Mul, MulAssign},
 };
-use std::io::Cursor;
+use std::{
+  fs,
+  io::{Cursor, Read},
+  path::Path,
+};
 
+use digest::{ExtendableOutput, Update};
 use ff::Field;
 use group::{
   prime::{PrimeCurve, PrimeCurveAffine},
@@ -14,12 +19,13 @@ use group::{
 use halo2curves::serde::SerdeObject;
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use sha3::Shake256;
 
 use crate::{
   errors::NovaError,
   fast_serde,
   fast_serde::{FastSerde, SerdeByteError, SerdeByteTypes},
-  provider::traits::DlogGroup,
+  provider::{traits::DlogGroup, MsmConfig},
   traits::{
     commitment::{CommitmentEngineTrait, CommitmentTrait, Len},
     AbsorbInROTrait, Engine, ROTrait, TranscriptReprTrait,
@@ -27,13 +33,120 @@ use crate::{
   zip_with,
 };
 
+// number of scalars `commit_iter` buffers per multiexponentiation
+const COMMIT_ITER_CHUNK: usize = 1 << 12;
+
+// hashed to the curve to derive the generator blinding factors are committed with
+pub(crate) const BLINDING_LABEL: &[u8] = b"nova_blinding_generator";
+
 /// A type that holds commitment generators
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CommitmentKey<E>
 where
   E: Engine,
   E::GE: DlogGroup<ScalarExt = E::Scalar>, {
-  pub ck: Vec<<E::GE as PrimeCurve>::Affine>,
+  pub ck:  Vec<<E::GE as PrimeCurve>::Affine>,
+  /// The generator blinding factors are committed with, hashed to the curve
+  /// so that its discrete logarithm with respect to `ck` is unknown
+  #[serde(skip, default = "blinding_generator::<E>")]
+  pub h:   <E::GE as PrimeCurve>::Affine,
+  #[serde(skip)]
+  pub msm: MsmConfig,
+}
+
+fn blinding_generator<E>() -> <E::GE as PrimeCurve>::Affine
+where
+  E: Engine,
+  E::GE: DlogGroup<ScalarExt = E::Scalar>, {
+  E::hash_to_curve(BLINDING_LABEL).to_affine()
+}
+
+impl<E> CommitmentKey<E>
+where
+  E: Engine,
+  E::GE: DlogGroup<ScalarExt = E::Scalar>,
+{
+  /// Returns the key with its commitments computed by the `msm` backend
+  pub fn with_msm_config(self, msm: MsmConfig) -> Self { Self { msm, ..self } }
+
+  /// Returns a key with the first `n` generators of `self`, e.g. to serialize
+  /// only what a circuit needs, or [`NovaError::CommitmentKeyTooSmall`] if
+  /// `self` has fewer than `n`
+  pub fn trim(&self, n: usize) -> Result<Self, NovaError> {
+    if n > self.ck.len() {
+      return Err(NovaError::CommitmentKeyTooSmall { needed: n, have: self.ck.len() });
+    }
+    Ok(Self { ck: self.ck[..n].to_vec(), h: self.h, msm: self.msm })
+  }
+
+  /// Samples a key of `n` generators, rounded up to the next power of two,
+  /// from the XOF of `label` followed by `seed`, so that the same `(label,
+  /// seed)` pair yields the same key on any machine.
+  /// `CommitmentEngine::setup(label, n)` is the special case where no seed is
+  /// absorbed after the label.
+  pub fn setup_from_seed(label: &'static [u8], n: usize, seed: [u8; 32]) -> Self {
+    Self::from_xof(&[label, &seed], n)
+  }
+
+  fn from_xof(inputs: &[&[u8]], n: usize) -> Self {
+    let mut shake = Shake256::default();
+    inputs.iter().for_each(|input| shake.update(input));
+    let mut reader = shake.finalize_xof();
+    let uniform_bytes = (0..n.next_power_of_two())
+      .map(|_| {
+        let mut uniform_bytes = [0u8; 32];
+        reader.read_exact(&mut uniform_bytes).unwrap();
+        uniform_bytes
+      })
+      .collect::<Vec<_>>();
+    Self {
+      ck:  E::GE::hash_to_affine(&uniform_bytes),
+      h:   blinding_generator::<E>(),
+      msm: MsmConfig::default(),
+    }
+  }
+
+  /// Produces the generators of `CommitmentEngine::setup(label, n)` in chunks
+  /// of at most `chunk` elements, so that a large key can be written out
+  /// without holding every generator in memory at once.
+  pub fn setup_streaming(
+    label: &'static [u8],
+    n: usize,
+    chunk: usize,
+  ) -> impl Iterator<Item = Vec<<E::GE as PrimeCurve>::Affine>> {
+    assert!(chunk > 0, "chunk size must be positive");
+    let n = n.next_power_of_two();
+    let mut shake = Shake256::default();
+    shake.update(label);
+    let mut reader = shake.finalize_xof();
+    (0..n).step_by(chunk).map(move |start| {
+      let uniform_bytes = (start..n.min(start + chunk))
+        .map(|_| {
+          let mut uniform_bytes = [0u8; 32];
+          reader.read_exact(&mut uniform_bytes).unwrap();
+          uniform_bytes
+        })
+        .collect::<Vec<_>>();
+      E::GE::hash_to_affine(&uniform_bytes)
+    })
+  }
+}
+
+impl<E> CommitmentKey<E>
+where
+  E: Engine,
+  E::GE: DlogGroup<ScalarExt = E::Scalar>,
+  <E::GE as PrimeCurve>::Affine: SerdeObject,
+{
+  /// Writes the key to `path` in its `FastSerde` byte format
+  pub fn save_to(&self, path: impl AsRef<Path>) -> Result<(), SerdeByteError> {
+    Ok(fs::write(path, self.to_bytes())?)
+  }
+
+  /// Reads a key previously written with [`CommitmentKey::save_to`]
+  pub fn load_from(path: impl AsRef<Path>) -> Result<Self, SerdeByteError> {
+    Self::from_bytes(&fs::read(path)?)
+  }
 }
 
 impl<E> Len for CommitmentKey<E>
@@ -87,7 +200,7 @@ where
       })
       .collect::<Result<Vec<_>, _>>()?;
 
-    Ok(Self { ck })
+    Ok(Self { ck, h: blinding_generator::<E>(), msm: MsmConfig::default() })
   }
 }
 
@@ -150,6 +263,9 @@ where
   }
 }
 
+/// Absorbs the affine coordinates followed by an `is_infinity` flag, matching
+/// `AllocatedPoint` in circuit. The identity has both coordinates set to zero,
+/// so the flag keeps it from colliding with a finite point.
 impl<E> AbsorbInROTrait<E> for Commitment<E>
 where
   E: Engine,
@@ -224,12 +340,55 @@ where
   type CommitmentKey = CommitmentKey<E>;
 
   fn setup(label: &'static [u8], n: usize) -> Self::CommitmentKey {
-    Self::CommitmentKey { ck: E::GE::from_label(label, n.next_power_of_two()) }
+    Self::CommitmentKey::from_xof(&[label], n)
+  }
+
+  fn trim(ck: &Self::CommitmentKey, n: usize) -> Result<Self::CommitmentKey, NovaError> {
+    ck.trim(n)
   }
 
   fn commit(ck: &Self::CommitmentKey, v: &[E::Scalar]) -> Self::Commitment {
     assert!(ck.ck.len() >= v.len());
-    Commitment { comm: E::GE::vartime_multiscalar_mul(v, &ck.ck[..v.len()]) }
+    Commitment { comm: E::GE::vartime_multiscalar_mul_with(ck.msm, v, &ck.ck[..v.len()]) }
+  }
+
+  fn commit_iter(
+    ck: &Self::CommitmentKey,
+    mut v: impl ExactSizeIterator<Item = E::Scalar>,
+  ) -> Self::Commitment {
+    assert!(ck.ck.len() >= v.len());
+    // commit chunk by chunk so that only one chunk of scalars is held at a time
+    let mut chunk = Vec::with_capacity(COMMIT_ITER_CHUNK.min(v.len()));
+    let mut comm = E::GE::identity();
+    for bases in ck.ck[..v.len()].chunks(COMMIT_ITER_CHUNK) {
+      chunk.clear();
+      chunk.extend(v.by_ref().take(bases.len()));
+      comm += E::GE::vartime_multiscalar_mul_with(ck.msm, &chunk, &bases[..chunk.len()]);
+    }
+    Commitment { comm }
+  }
+
+  fn commit_blinded(ck: &Self::CommitmentKey, v: &[E::Scalar], r: E::Scalar) -> Self::Commitment {
+    Commitment { comm: Self::commit(ck, v).comm + ck.h * r }
+  }
+
+  fn commit_sparse(
+    ck: &Self::CommitmentKey,
+    indices: &[usize],
+    values: &[E::Scalar],
+  ) -> Self::Commitment {
+    assert_eq!(indices.len(), values.len());
+    let bases = indices.iter().map(|i| ck.ck[*i]).collect::<Vec<_>>();
+    Commitment { comm: E::GE::vartime_multiscalar_mul_with(ck.msm, values, &bases) }
+  }
+
+  fn batch_commit(ck: &Self::CommitmentKey, vs: &[&[E::Scalar]]) -> Vec<Self::Commitment> {
+    assert!(vs.iter().all(|v| ck.ck.len() >= v.len()));
+    vs.par_iter()
+      .map(|v| Commitment {
+        comm: E::GE::vartime_multiscalar_mul_with(ck.msm, v, &ck.ck[..v.len()]),
+      })
+      .collect()
   }
 }
 
@@ -268,13 +427,13 @@ where
   E::GE: DlogGroup<ScalarExt = E::Scalar>,
 {
   fn split_at(mut self, n: usize) -> (Self, Self) {
-    let right = self.ck.split_off(n);
-    (self, Self { ck: right })
+    let right = Self { ck: self.ck.split_off(n), h: self.h, msm: self.msm };
+    (self, right)
   }
 
   fn combine(&self, other: &Self) -> Self {
     let ck = { self.ck.iter().cloned().chain(other.ck.iter().cloned()).collect::<Vec<_>>() };
-    Self { ck }
+    Self { ck, h: self.h, msm: self.msm }
   }
 
   // combines the left and right halves of `self` using `w1` and `w2` as the
@@ -288,7 +447,7 @@ where
     let mut ck_affine = vec![<E::GE as PrimeCurve>::Affine::identity(); L.ck.len()];
     E::GE::batch_normalize(&ck_curve, &mut ck_affine);
 
-    Self { ck: ck_affine }
+    Self { ck: ck_affine, h: L.h, msm: L.msm }
   }
 
   /// Scales each element in `self` by `r`
@@ -305,6 +464,252 @@ where
       .collect::<Result<Vec<E::GE>, NovaError>>()?;
     let mut ck = vec![<E::GE as PrimeCurve>::Affine::identity(); d.len()];
     E::GE::batch_normalize(&d, &mut ck);
-    Ok(Self { ck })
+    Ok(Self { ck, h: blinding_generator::<E>(), msm: MsmConfig::default() })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use rand::rngs::OsRng;
+
+  use super::*;
+  use crate::{
+    constants::NUM_CHALLENGE_BITS,
+    provider::{Bn256EngineIPA, GrumpkinEngine},
+    traits::ROConstants,
+  };
+
+  fn test_batch_commit_with<E: Engine<CE = CommitmentEngine<E>>>()
+  where E::GE: DlogGroup<ScalarExt = E::Scalar> {
+    let n = 64;
+    let ck = CommitmentEngine::<E>::setup(b"test", n);
+    let vs = (0..8)
+      .map(|i| (0..n - i).map(|_| E::Scalar::random(OsRng)).collect::<Vec<_>>())
+      .collect::<Vec<_>>();
+    let vs = vs.iter().map(|v| v.as_slice()).collect::<Vec<_>>();
+
+    let expected = vs.iter().map(|v| CommitmentEngine::<E>::commit(&ck, v)).collect::<Vec<_>>();
+    assert_eq!(CommitmentEngine::<E>::batch_commit(&ck, &vs), expected);
+  }
+
+  #[test]
+  fn test_batch_commit() {
+    test_batch_commit_with::<Bn256EngineIPA>();
+    test_batch_commit_with::<GrumpkinEngine>();
+  }
+
+  fn test_setup_streaming_with<E: Engine<CE = CommitmentEngine<E>>>()
+  where
+    E::GE: DlogGroup<ScalarExt = E::Scalar>,
+    <E::GE as PrimeCurve>::Affine: SerdeObject, {
+    let n = 100;
+    let eager = CommitmentEngine::<E>::setup(b"test", n);
+    for chunk in [1, 7, 64, 128, 1000] {
+      let chunks = CommitmentKey::<E>::setup_streaming(b"test", n, chunk).collect::<Vec<_>>();
+      assert!(chunks.iter().all(|c| c.len() <= chunk));
+      let streamed = CommitmentKey::<E> {
+        ck:  chunks.into_iter().flatten().collect(),
+        h:   eager.h,
+        msm: MsmConfig::default(),
+      };
+      assert_eq!(streamed.to_bytes(), eager.to_bytes());
+    }
+  }
+
+  fn test_commit_iter_with<E: Engine<CE = CommitmentEngine<E>>>()
+  where E::GE: DlogGroup<ScalarExt = E::Scalar> {
+    let n = 1000;
+    let ck = CommitmentEngine::<E>::setup(b"test", n);
+    let v = (0..n).map(|_| E::Scalar::random(OsRng)).collect::<Vec<_>>();
+
+    let expected = CommitmentEngine::<E>::commit(&ck, &v);
+    assert_eq!(CommitmentEngine::<E>::commit_iter(&ck, v.iter().copied()), expected);
+    assert_eq!(
+      CommitmentEngine::<E>::commit_iter(&ck, v[..10].iter().copied()),
+      CommitmentEngine::<E>::commit(&ck, &v[..10])
+    );
+
+    // a source spanning several chunks is summed back to the same commitment
+    let n = COMMIT_ITER_CHUNK + 1;
+    let ck = CommitmentEngine::<E>::setup(b"test", n);
+    let v = (0..n).map(|_| E::Scalar::random(OsRng)).collect::<Vec<_>>();
+    assert_eq!(
+      CommitmentEngine::<E>::commit_iter(&ck, v.iter().copied()),
+      CommitmentEngine::<E>::commit(&ck, &v)
+    );
+  }
+
+  #[test]
+  fn test_commit_iter() {
+    test_commit_iter_with::<Bn256EngineIPA>();
+    test_commit_iter_with::<GrumpkinEngine>();
+  }
+
+  fn test_commit_blinded_with<E: Engine<CE = CommitmentEngine<E>>>()
+  where E::GE: DlogGroup<ScalarExt = E::Scalar> {
+    let ck = CommitmentEngine::<E>::setup(b"test", 8);
+    let v = (0..8).map(|_| E::Scalar::random(OsRng)).collect::<Vec<_>>();
+    let (r1, r2) = (E::Scalar::random(OsRng), E::Scalar::random(OsRng));
+
+    // different blinds hide the same vector behind different commitments
+    let comm1 = CommitmentEngine::<E>::commit_blinded(&ck, &v, r1);
+    let comm2 = CommitmentEngine::<E>::commit_blinded(&ck, &v, r2);
+    assert_ne!(comm1, comm2);
+    let comm = CommitmentEngine::<E>::commit(&ck, &v);
+    assert_ne!(comm1, comm);
+    assert_eq!(CommitmentEngine::<E>::commit_blinded(&ck, &v, E::Scalar::ZERO), comm);
+
+    // only the committed vector and blind open the commitment
+    assert!(CommitmentEngine::<E>::verify_open(&ck, &comm1, &v, r1).is_ok());
+    assert_eq!(
+      CommitmentEngine::<E>::verify_open(&ck, &comm1, &v, r2),
+      Err(NovaError::InvalidCommitmentOpening)
+    );
+    let mut w = v.clone();
+    w[0] += E::Scalar::ONE;
+    assert_eq!(
+      CommitmentEngine::<E>::verify_open(&ck, &comm1, &w, r1),
+      Err(NovaError::InvalidCommitmentOpening)
+    );
+
+    // a vector longer than the key is rejected rather than panicking
+    let long = [v.clone(), vec![E::Scalar::ONE]].concat();
+    assert_eq!(
+      CommitmentEngine::<E>::try_commit_blinded(&ck, &long, r1),
+      Err(NovaError::CommitmentKeyTooSmall { needed: 9, have: 8 })
+    );
+    assert_eq!(
+      CommitmentEngine::<E>::verify_open(&ck, &comm1, &long, r1),
+      Err(NovaError::CommitmentKeyTooSmall { needed: 9, have: 8 })
+    );
+    assert_eq!(CommitmentEngine::<E>::try_commit_blinded(&ck, &v, r1), Ok(comm1));
+
+    // the blinding generator survives a serialization round-trip
+    let ck_deserialized: CommitmentKey<E> =
+      bincode::deserialize(&bincode::serialize(&ck).unwrap()).unwrap();
+    assert_eq!(ck_deserialized, ck);
+  }
+
+  #[test]
+  fn test_commit_blinded() {
+    test_commit_blinded_with::<Bn256EngineIPA>();
+    test_commit_blinded_with::<GrumpkinEngine>();
+  }
+
+  fn test_setup_from_seed_with<E: Engine<CE = CommitmentEngine<E>>>()
+  where
+    E::GE: DlogGroup<ScalarExt = E::Scalar>,
+    <E::GE as PrimeCurve>::Affine: SerdeObject, {
+    let n = 100;
+    let ck = CommitmentKey::<E>::setup_from_seed(b"test", n, [1u8; 32]);
+    assert_eq!(ck.length(), n.next_power_of_two());
+
+    // the same seed reproduces the key byte for byte, a different one does not
+    let same = CommitmentKey::<E>::setup_from_seed(b"test", n, [1u8; 32]);
+    assert_eq!(ck.to_bytes(), same.to_bytes());
+    let other = CommitmentKey::<E>::setup_from_seed(b"test", n, [2u8; 32]);
+    assert_ne!(ck.to_bytes(), other.to_bytes());
+
+    // the label-based setup does not absorb a seed, and still matches `from_label`
+    let unseeded = CommitmentEngine::<E>::setup(b"test", n);
+    assert_ne!(ck.to_bytes(), unseeded.to_bytes());
+    assert_eq!(unseeded.ck, E::GE::from_label(b"test", n.next_power_of_two()));
+  }
+
+  #[test]
+  fn test_setup_from_seed() {
+    test_setup_from_seed_with::<Bn256EngineIPA>();
+    test_setup_from_seed_with::<GrumpkinEngine>();
+  }
+
+  fn test_msm_config_with<E: Engine<CE = CommitmentEngine<E>>>()
+  where E::GE: DlogGroup<ScalarExt = E::Scalar> {
+    let ck = CommitmentEngine::<E>::setup(b"test", 256);
+    // cover inputs on both sides of the size threshold of the `Auto` backend
+    for n in [3, 200] {
+      let v = (0..n).map(|_| E::Scalar::random(OsRng)).collect::<Vec<_>>();
+      let commitments = [MsmConfig::Auto, MsmConfig::Rayon, MsmConfig::Serial].map(|msm| {
+        let ck = ck.clone().with_msm_config(msm);
+        assert_eq!(ck.msm, msm);
+        CommitmentEngine::<E>::commit(&ck, &v)
+      });
+      assert!(commitments.iter().all(|c| *c == commitments[0]));
+    }
+  }
+
+  #[test]
+  fn test_msm_config() {
+    test_msm_config_with::<Bn256EngineIPA>();
+    test_msm_config_with::<GrumpkinEngine>();
+  }
+
+  fn test_absorb_commitment_with<E: Engine<CE = CommitmentEngine<E>>>()
+  where E::GE: DlogGroup<ScalarExt = E::Scalar> {
+    let ck = CommitmentEngine::<E>::setup(b"test", 8);
+    let squeeze = |c: &Commitment<E>| {
+      let mut ro = E::RO::new(ROConstants::<E>::default(), 3);
+      c.absorb_in_ro(&mut ro);
+      ro.squeeze(NUM_CHALLENGE_BITS).unwrap()
+    };
+
+    let v = (0..8).map(|_| E::Scalar::random(OsRng)).collect::<Vec<_>>();
+    let w = (0..8).map(|_| E::Scalar::random(OsRng)).collect::<Vec<_>>();
+    let c = CommitmentEngine::<E>::commit(&ck, &v);
+
+    // equal commitments absorb to the same challenge, distinct ones differ
+    assert_eq!(squeeze(&c), squeeze(&CommitmentEngine::<E>::commit(&ck, &v)));
+    assert_ne!(squeeze(&c), squeeze(&CommitmentEngine::<E>::commit(&ck, &w)));
+
+    // the commitment is absorbed as (x, y, is_infinity)
+    let (x, y, _) = c.to_coordinates();
+    let mut ro = E::RO::new(ROConstants::<E>::default(), 3);
+    [x, y, E::Base::ZERO].into_iter().for_each(|e| ro.absorb(e));
+    assert_eq!(squeeze(&c), ro.squeeze(NUM_CHALLENGE_BITS).unwrap());
+
+    // the point at infinity is distinguished by its flag
+    let mut ro = E::RO::new(ROConstants::<E>::default(), 3);
+    [E::Base::ZERO, E::Base::ZERO, E::Base::ZERO].into_iter().for_each(|e| ro.absorb(e));
+    assert_ne!(squeeze(&Commitment::default()), ro.squeeze(NUM_CHALLENGE_BITS).unwrap());
+  }
+
+  #[test]
+  fn test_absorb_commitment() {
+    test_absorb_commitment_with::<Bn256EngineIPA>();
+    test_absorb_commitment_with::<GrumpkinEngine>();
+  }
+
+  #[test]
+  fn test_setup_streaming() {
+    test_setup_streaming_with::<Bn256EngineIPA>();
+    test_setup_streaming_with::<GrumpkinEngine>();
+  }
+
+  #[test]
+  fn test_commit_key_too_small() {
+    type E = GrumpkinEngine;
+    let ck = CommitmentEngine::<E>::setup(b"test", 8);
+    let v = vec![<E as Engine>::Scalar::ONE; 9];
+    assert_eq!(
+      CommitmentEngine::<E>::try_commit(&ck, &v),
+      Err(NovaError::CommitmentKeyTooSmall { needed: 9, have: 8 })
+    );
+    assert!(CommitmentEngine::<E>::try_commit(&ck, &v[..8]).is_ok());
+  }
+
+  #[test]
+  fn test_ck_save_load() {
+    type E = Bn256EngineIPA;
+    let ck = CommitmentEngine::<E>::setup(b"test", 64);
+    let path = std::env::temp_dir().join(format!("edge_ck_save_load_{}", std::process::id()));
+
+    ck.save_to(&path).unwrap();
+    assert_eq!(CommitmentKey::<E>::load_from(&path).unwrap(), ck);
+
+    // a truncated file is rejected rather than panicking
+    let bytes = fs::read(&path).unwrap();
+    fs::write(&path, &bytes[..bytes.len() - 10]).unwrap();
+    assert!(CommitmentKey::<E>::load_from(&path).is_err());
+
+    fs::remove_file(&path).unwrap();
   }
 }
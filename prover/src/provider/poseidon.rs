@@ -1,5 +1,10 @@
 //! Poseidon Constants and Poseidon-based RO used in Nova
-use core::marker::PhantomData;
+use core::{
+  any::{Any, TypeId},
+  fmt::Debug,
+  marker::PhantomData,
+};
+use std::sync::Mutex;
 
 use bellpepper_core::{
   boolean::{AllocatedBit, Boolean},
@@ -7,111 +12,558 @@ use bellpepper_core::{
   ConstraintSystem, SynthesisError,
 };
 use ff::{PrimeField, PrimeFieldBits};
-use generic_array::typenum::U24;
+use generic_array::typenum::{U2, U24};
 use neptune::{
   circuit2::Elt,
-  poseidon::PoseidonConstants,
+  poseidon::{Poseidon, PoseidonConstants},
   sponge::{
     api::{IOPattern, SpongeAPI, SpongeOp},
     circuit::SpongeCircuit,
     vanilla::{Mode::Simplex, Sponge, SpongeTrait},
   },
-  Strength,
+  Arity, Strength,
 };
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use subtle::{Choice, ConditionallySelectable};
+
+use crate::{
+  errors::NovaError,
+  traits::{ROCircuitTrait, ROTrait},
+};
 
-use crate::traits::{ROCircuitTrait, ROTrait};
+/// The arity `A` of the Poseidon permutation behind the sponge, i.e. the size
+/// of its state minus the capacity element. Nova uses `U24` by default.
+pub trait PoseidonArity<F: PrimeField>: Arity<F> + Debug + Eq + Send + Sync + 'static {}
 
-/// All Poseidon Constants that are used in Nova
+impl<F: PrimeField, A: Arity<F> + Debug + Eq + Send + Sync + 'static> PoseidonArity<F> for A {}
+
+/// Poseidon constants generated so far, keyed by the type of their field, their
+/// arity and their strength, since generating them is expensive
+#[allow(clippy::type_complexity)]
+static CONSTANTS_CACHE: Lazy<Mutex<Vec<(TypeId, TypeId, Strength, Box<dyn Any + Send + Sync>)>>> =
+  Lazy::new(|| Mutex::new(Vec::new()));
+
+/// All Poseidon Constants that are used in Nova, for a sponge of arity `A`
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-pub struct PoseidonConstantsCircuit<Scalar: PrimeField>(PoseidonConstants<Scalar, U24>);
+#[serde(bound(
+  serialize = "PoseidonConstants<Scalar, A>: Serialize",
+  deserialize = "PoseidonConstants<Scalar, A>: Deserialize<'de>"
+))]
+pub struct PoseidonConstantsCircuit<Scalar: PrimeField, A: PoseidonArity<Scalar> = U24>(
+  pub(crate) PoseidonConstants<Scalar, A>,
+);
 
-impl<Scalar: PrimeField> Default for PoseidonConstantsCircuit<Scalar> {
+impl<Scalar: PrimeField, A: PoseidonArity<Scalar>> Default for PoseidonConstantsCircuit<Scalar, A> {
   /// Generate Poseidon constants
-  fn default() -> Self { Self(Sponge::<Scalar, U24>::api_constants(Strength::Standard)) }
+  fn default() -> Self { Self::with_strength(Strength::Standard) }
+}
+
+impl<Scalar: PrimeField, A: PoseidonArity<Scalar>> PoseidonConstantsCircuit<Scalar, A> {
+  /// Generate Poseidon constants for the given security `strength`.
+  ///
+  /// The same constants must be handed to both [`PoseidonRO`] and
+  /// [`PoseidonROCircuit`] so that native and in-circuit hashing agree.
+  /// Constants are generated once per field, arity and strength, and cloned
+  /// from a process-wide cache afterwards.
+  pub fn with_strength(strength: Strength) -> Self {
+    let (field, arity) = (TypeId::of::<Scalar>(), TypeId::of::<A>());
+    let mut cache = CONSTANTS_CACHE.lock().unwrap();
+    let cached = cache.iter().find(|(f, a, s, _)| (*f, *a, *s) == (field, arity, strength));
+    if let Some((.., constants)) = cached {
+      let constants = constants.downcast_ref::<PoseidonConstants<Scalar, A>>();
+      return Self(constants.expect("cache entries are keyed by their type").clone());
+    }
+
+    let constants = Sponge::<Scalar, A>::api_constants(strength);
+    cache.push((field, arity, strength, Box::new(constants.clone())));
+    Self(constants)
+  }
+
+  /// Returns the security strength these constants were generated with
+  pub fn strength(&self) -> Strength { self.0.strength }
+}
+
+/// Hash exactly two elements with an arity-2 Poseidon, the native counterpart
+/// of [`gadgets::poseidon_hash2`](crate::gadgets::poseidon_hash2)
+pub fn poseidon_hash2<F: PrimeField>(constants: &PoseidonConstants<F, U2>, a: F, b: F) -> F {
+  Poseidon::new_with_preimage(&[a, b], constants).hash()
+}
+
+// number of bytes packed into one field element, small enough to fit the
+// capacity of every field we support
+const BYTES_PER_ELEMENT: usize = 31;
+
+/// Append the length of `bytes` and `bytes` packed little-endian into field
+/// elements to `out`
+pub(crate) fn pack_bytes<F: PrimeField>(out: &mut Vec<F>, bytes: &[u8]) {
+  out.push(F::from(bytes.len() as u64));
+  out.extend(bytes.chunks(BYTES_PER_ELEMENT).map(|chunk| {
+    chunk.iter().rev().fold(F::ZERO, |acc, byte| acc * F::from(256) + F::from(u64::from(*byte)))
+  }));
+}
+
+/// A snapshot of the absorbed state of a [`PoseidonRO`], used to checkpoint an
+/// oracle and restore it later with the same constants.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ROState<Base: PrimeField> {
+  state:       Vec<Base>,
+  num_absorbs: Option<usize>,
+  domain:      Option<u64>,
+  squeezed:    bool,
 }
 
 /// A Poseidon-based RO to use outside circuits
-#[derive(Debug)]
-pub struct PoseidonRO<Base, Scalar>
+#[derive(Debug, Clone)]
+pub struct PoseidonRO<Base, Scalar, A = U24>
 where
   Base: PrimeField,
-  Scalar: PrimeField, {
+  Scalar: PrimeField,
+  A: PoseidonArity<Base>, {
   state:       Vec<Base>,
-  constants:   PoseidonConstantsCircuit<Base>,
-  num_absorbs: usize,
+  constants:   PoseidonConstantsCircuit<Base, A>,
+  num_absorbs: Option<usize>,
+  domain:      Option<u64>,
   squeezed:    bool,
   _p:          PhantomData<Scalar>,
 }
 
-impl<Base, Scalar> ROTrait<Base, Scalar> for PoseidonRO<Base, Scalar>
+impl<Base, Scalar, A> PoseidonRO<Base, Scalar, A>
 where
-  Base: PrimeField + PrimeFieldBits + Serialize + for<'de> Deserialize<'de>,
+  Base: PrimeField,
   Scalar: PrimeField,
+  A: PoseidonArity<Base>,
 {
-  type CircuitRO = PoseidonROCircuit<Base>;
-  type Constants = PoseidonConstantsCircuit<Base>;
+  /// Initialize the oracle with an optional domain separation tag.
+  ///
+  /// When set, the tag is absorbed as the very first element and is counted
+  /// in `num_absorbs`, so callers must pass the number of user elements plus
+  /// one.
+  pub fn new_with_domain(
+    constants: PoseidonConstantsCircuit<Base, A>,
+    num_absorbs: usize,
+    domain: Option<u64>,
+  ) -> Self {
+    let num_absorbs = Some(num_absorbs);
+    Self { state: Vec::new(), constants, num_absorbs, domain, squeezed: false, _p: PhantomData }
+  }
 
-  fn new(constants: PoseidonConstantsCircuit<Base>, num_absorbs: usize) -> Self {
-    Self { state: Vec::new(), constants, num_absorbs, squeezed: false, _p: PhantomData }
+  /// Initialize the oracle without declaring the number of absorbs up front.
+  /// The sponge's IO pattern is derived from the number of elements absorbed
+  /// when squeezing, so the challenge equals that of an oracle that declared
+  /// the same count.
+  pub fn new_inferred(constants: PoseidonConstantsCircuit<Base, A>, domain: Option<u64>) -> Self {
+    Self {
+      state: Vec::new(),
+      constants,
+      num_absorbs: None,
+      domain,
+      squeezed: false,
+      _p: PhantomData,
+    }
   }
 
-  /// Absorb a new number into the state of the oracle
-  fn absorb(&mut self, e: Base) {
+  /// Clear the absorbed state so the oracle can be reused, keeping the
+  /// constants, `num_absorbs` and domain tag.
+  pub fn reset(&mut self) {
+    self.state.clear();
+    self.squeezed = false;
+  }
+
+  /// Absorb `bytes`, packed into field elements: their length, then each run
+  /// of 31 bytes read as a little-endian integer. The declared `num_absorbs`,
+  /// if any, grows by the number of elements this adds, so callers need not
+  /// account for them.
+  pub fn absorb_bytes(&mut self, bytes: &[u8]) {
     assert!(!self.squeezed, "Cannot absorb after squeezing");
-    self.state.push(e);
+    let len = self.state.len();
+    pack_bytes(&mut self.state, bytes);
+    #[cfg(feature = "transcript-trace")]
+    for e in &self.state[len..] {
+      tracing::trace!(value = ?e, "poseidon ro absorb");
+    }
+    if let Some(num_absorbs) = self.num_absorbs.as_mut() {
+      *num_absorbs += self.state.len() - len;
+    }
   }
 
-  /// Compute a challenge by hashing the current state
-  fn squeeze(&mut self, num_bits: usize) -> Scalar {
+  /// The IO pattern of the sponge [`ROTrait::squeeze`] runs, so that other
+  /// implementations of the oracle can reproduce its challenges: a single
+  /// `Absorb` of every element, then a `Squeeze` of one element.
+  ///
+  /// The elements are absorbed in this order: the domain tag if any, then each
+  /// element passed to `absorb`, or packed by `absorb_bytes`, in call order.
+  /// Their count is the declared `num_absorbs`, or for an oracle created with
+  /// [`PoseidonRO::new_inferred`] the number absorbed so far.
+  pub fn io_pattern(&self) -> IOPattern {
+    let num_absorbs =
+      self.num_absorbs.unwrap_or(usize::from(self.domain.is_some()) + self.state.len());
+    sponge_io_pattern(num_absorbs, 1)
+  }
+
+  /// Export the absorbed state of the oracle, see [`ROState`].
+  pub fn export_state(&self) -> ROState<Base> {
+    ROState {
+      state:       self.state.clone(),
+      num_absorbs: self.num_absorbs,
+      domain:      self.domain,
+      squeezed:    self.squeezed,
+    }
+  }
+
+  /// Restore an oracle from a previously exported state. The `constants` must
+  /// be the ones the original oracle was created with.
+  pub fn from_state(constants: PoseidonConstantsCircuit<Base, A>, state: ROState<Base>) -> Self {
+    let ROState { state, num_absorbs, domain, squeezed } = state;
+    Self { state, constants, num_absorbs, domain, squeezed, _p: PhantomData }
+  }
+}
+
+impl<Base, Scalar, A> PoseidonRO<Base, Scalar, A>
+where
+  Base: PrimeField + PrimeFieldBits,
+  Scalar: PrimeField,
+  A: PoseidonArity<Base>,
+{
+  /// Compute `count` challenges of `num_bits` each by hashing the current
+  /// state once and squeezing `count` elements out of the same sponge.
+  ///
+  /// `num_bits` may be at most `Base::NUM_BITS`; when it exceeds
+  /// `Scalar::CAPACITY` the challenge is reduced modulo the scalar field.
+  pub fn squeeze_many(&mut self, num_bits: usize, count: usize) -> Result<Vec<Scalar>, NovaError> {
+    self.squeeze_with_widths(&vec![num_bits; count])
+  }
+
+  /// Compute one challenge per entry of `widths` by hashing the current state
+  /// once and squeezing `widths.len()` elements out of the same sponge, the
+  /// `i`-th truncated to `widths[i]` bits, e.g. a short challenge and a
+  /// full-field one from the same transcript state.
+  ///
+  /// Each width may be at most `Base::NUM_BITS`, as in
+  /// [`PoseidonRO::squeeze_many`].
+  pub fn squeeze_with_widths(&mut self, widths: &[usize]) -> Result<Vec<Scalar>, NovaError> {
+    if widths.iter().any(|num_bits| *num_bits > Base::NUM_BITS as usize) {
+      return Err(NovaError::InvalidNumBits);
+    }
+
+    // Only return `widths[i]` bits of the `i`-th output
+    let hash = self.squeeze_elements(widths.len())?;
+    Ok(hash.iter().zip(widths).map(|(h, num_bits)| to_scalar(h, *num_bits)).collect())
+  }
+
+  /// Compute a challenge as the full first hash output reduced into `Scalar`,
+  /// rather than truncated to a number of bits. Its low-order bits match
+  /// [`PoseidonRO::squeeze_many`] whenever `Base` fits in `Scalar`.
+  pub fn squeeze_field(&mut self) -> Result<Scalar, NovaError> {
+    let hash = self.squeeze_elements(1)?;
+    Ok(to_scalar(&hash[0], Base::NUM_BITS as usize))
+  }
+
+  /// Compute a challenge as the 64 low-order bits of the hash, read directly
+  /// into an integer rather than a field element. It has the value of
+  /// [`ROTrait::squeeze`] with 64 bits.
+  pub fn squeeze_u64(&mut self) -> Result<u64, NovaError> { Ok(self.squeeze_u128()? as u64) }
+
+  /// Same as [`PoseidonRO::squeeze_u64`], with the 128 low-order bits
+  pub fn squeeze_u128(&mut self) -> Result<u128, NovaError> {
+    let hash = self.squeeze_elements(1)?;
+    let bits = hash[0].to_le_bits();
+    Ok(bits[..128].iter().rev().fold(0u128, |acc, bit| (acc << 1) | u128::from(*bit)))
+  }
+
+  /// Hash the current state once and squeeze `count` elements out of the
+  /// same sponge. Returns [`NovaError::SpongeError`] if the number of
+  /// absorbed elements differs from the declared `num_absorbs`.
+  fn squeeze_elements(&mut self, count: usize) -> Result<Vec<Base>, NovaError> {
     // check if we have squeezed already
     assert!(!self.squeezed, "Cannot squeeze again after squeezing");
     self.squeezed = true;
 
+    let input = self.domain.map(Base::from).into_iter().chain(self.state.iter().copied());
+    let input = input.collect::<Vec<Base>>();
+    check_num_absorbs(self.num_absorbs, input.len())?;
+
     let mut sponge = Sponge::new_with_constants(&self.constants.0, Simplex);
     let acc = &mut ();
-    let parameter =
-      IOPattern(vec![SpongeOp::Absorb(self.num_absorbs as u32), SpongeOp::Squeeze(1u32)]);
+    let parameter = sponge_io_pattern(input.len(), count);
 
     sponge.start(parameter, None, acc);
-    assert_eq!(self.num_absorbs, self.state.len());
-    SpongeAPI::absorb(&mut sponge, self.num_absorbs as u32, &self.state, acc);
-    let hash = SpongeAPI::squeeze(&mut sponge, 1, acc);
-    sponge.finish(acc).unwrap();
+    SpongeAPI::absorb(&mut sponge, input.len() as u32, &input, acc);
+    let hash = SpongeAPI::squeeze(&mut sponge, count as u32, acc);
+    sponge.finish(acc).map_err(|err| NovaError::SpongeError(format!("{err:?}")))?;
+    #[cfg(feature = "transcript-trace")]
+    tracing::trace!(outputs = ?hash, "poseidon ro squeeze");
+    Ok(hash)
+  }
+}
 
-    // Only return `num_bits`
-    let bits = hash[0].to_le_bits();
-    let mut res = Scalar::ZERO;
-    let mut coeff = Scalar::ONE;
-    for bit in bits[..num_bits].into_iter() {
-      if *bit {
-        res += coeff;
-      }
-      coeff += coeff;
+/// The IO pattern of a sponge absorbing `num_absorbs` elements at once, then
+/// squeezing `count` elements
+fn sponge_io_pattern(num_absorbs: usize, count: usize) -> IOPattern {
+  IOPattern(vec![SpongeOp::Absorb(num_absorbs as u32), SpongeOp::Squeeze(count as u32)])
+}
+
+/// Check that an oracle absorbed as many elements as it declared, if any, so
+/// that the sponge's IO pattern matches the operations performed on it
+pub(crate) fn check_num_absorbs(
+  num_absorbs: Option<usize>,
+  absorbed: usize,
+) -> Result<(), NovaError> {
+  match num_absorbs {
+    Some(num_absorbs) if num_absorbs != absorbed =>
+      Err(NovaError::SpongeError(format!("declared {num_absorbs} absorbs, absorbed {absorbed}"))),
+    _ => Ok(()),
+  }
+}
+
+/// Reconstruct a scalar from the `num_bits` low-order bits of `h`, without
+/// branching on them when the `constant-time` feature is enabled
+fn to_scalar<Base: PrimeFieldBits, Scalar: PrimeField>(h: &Base, num_bits: usize) -> Scalar {
+  #[cfg(not(feature = "constant-time"))]
+  {
+    bits_to_scalar(h, num_bits)
+  }
+  #[cfg(feature = "constant-time")]
+  {
+    bits_to_scalar_ct(h, num_bits)
+  }
+}
+
+/// Reconstruct a scalar from the `num_bits` low-order bits of `h`
+#[cfg_attr(feature = "constant-time", allow(dead_code))]
+fn bits_to_scalar<Base: PrimeFieldBits, Scalar: PrimeField>(h: &Base, num_bits: usize) -> Scalar {
+  let bits = h.to_le_bits();
+  let mut res = Scalar::ZERO;
+  let mut coeff = Scalar::ONE;
+  for bit in bits[..num_bits].into_iter() {
+    if *bit {
+      res += coeff;
     }
-    res
+    coeff += coeff;
+  }
+  res
+}
+
+/// Same as [`bits_to_scalar`], but each addend is picked with a conditional
+/// select rather than a branch on the bit
+#[cfg_attr(not(feature = "constant-time"), allow(dead_code))]
+fn bits_to_scalar_ct<Base: PrimeFieldBits, Scalar: PrimeField>(
+  h: &Base,
+  num_bits: usize,
+) -> Scalar {
+  let bits = h.to_le_bits();
+  let mut res = Scalar::ZERO;
+  let mut coeff = Scalar::ONE;
+  for bit in bits[..num_bits].into_iter() {
+    res = Scalar::conditional_select(&res, &(res + coeff), Choice::from(*bit as u8));
+    coeff += coeff;
+  }
+  res
+}
+
+impl<Base, Scalar, A> ROTrait<Base, Scalar> for PoseidonRO<Base, Scalar, A>
+where
+  Base: PrimeField + PrimeFieldBits + Serialize + for<'de> Deserialize<'de>,
+  Scalar: PrimeField,
+  A: PoseidonArity<Base>,
+{
+  type CircuitRO = PoseidonROCircuit<Base, A>;
+  type Constants = PoseidonConstantsCircuit<Base, A>;
+
+  fn new(constants: PoseidonConstantsCircuit<Base, A>, num_absorbs: usize) -> Self {
+    Self::new_with_domain(constants, num_absorbs, None)
+  }
+
+  /// Absorb a new number into the state of the oracle
+  fn absorb(&mut self, e: Base) {
+    assert!(!self.squeezed, "Cannot absorb after squeezing");
+    #[cfg(feature = "transcript-trace")]
+    tracing::trace!(value = ?e, "poseidon ro absorb");
+    self.state.push(e);
+  }
+
+  /// Compute a challenge by hashing the current state
+  fn squeeze(&mut self, num_bits: usize) -> Result<Scalar, NovaError> {
+    Ok(self.squeeze_many(num_bits, 1)?.swap_remove(0))
+  }
+}
+
+/// A Poseidon-based RO to use outside circuits that absorbs and squeezes in
+/// the same field `F`, for protocols such as non-recursive Spartan that only
+/// have one field.
+///
+/// A challenge is the full hash output, so unlike [`PoseidonRO`] it is not
+/// truncated to a number of bits and reinterpreted in another field.
+#[derive(Debug, Clone)]
+pub struct PoseidonROSameField<F: PrimeField, A: PoseidonArity<F> = U24> {
+  ro: PoseidonRO<F, F, A>,
+}
+
+impl<F: PrimeField + PrimeFieldBits, A: PoseidonArity<F>> PoseidonROSameField<F, A> {
+  /// Initialize the oracle for `num_absorbs` absorbed elements
+  pub fn new(constants: PoseidonConstantsCircuit<F, A>, num_absorbs: usize) -> Self {
+    Self { ro: PoseidonRO::new_with_domain(constants, num_absorbs, None) }
   }
+
+  /// Absorb a new element into the state of the oracle
+  pub fn absorb(&mut self, e: F) {
+    assert!(!self.ro.squeezed, "Cannot absorb after squeezing");
+    self.ro.state.push(e);
+  }
+
+  /// Compute a challenge by hashing the current state
+  pub fn squeeze(&mut self) -> Result<F, NovaError> { Ok(self.ro.squeeze_elements(1)?[0]) }
 }
 
 /// A Poseidon-based RO gadget to use inside the verifier circuit.
-#[derive(Debug)]
-pub struct PoseidonROCircuit<Scalar: PrimeField> {
+#[derive(Debug, Clone)]
+pub struct PoseidonROCircuit<Scalar: PrimeField, A: PoseidonArity<Scalar> = U24> {
   // Internal state
   state:       Vec<AllocatedNum<Scalar>>,
-  constants:   PoseidonConstantsCircuit<Scalar>,
-  num_absorbs: usize,
+  constants:   PoseidonConstantsCircuit<Scalar, A>,
+  num_absorbs: Option<usize>,
+  domain:      Option<u64>,
   squeezed:    bool,
 }
 
-impl<Scalar> ROCircuitTrait<Scalar> for PoseidonROCircuit<Scalar>
-where Scalar: PrimeField + PrimeFieldBits + Serialize + for<'de> Deserialize<'de>
+impl<Scalar: PrimeField, A: PoseidonArity<Scalar>> PoseidonROCircuit<Scalar, A> {
+  /// Initialize the gadget with an optional domain separation tag, see
+  /// [`PoseidonRO::new_with_domain`].
+  pub fn new_with_domain(
+    constants: PoseidonConstantsCircuit<Scalar, A>,
+    num_absorbs: usize,
+    domain: Option<u64>,
+  ) -> Self {
+    Self { state: Vec::new(), constants, num_absorbs: Some(num_absorbs), domain, squeezed: false }
+  }
+
+  /// Initialize the gadget without declaring the number of absorbs up front,
+  /// see [`PoseidonRO::new_inferred`].
+  pub fn new_inferred(constants: PoseidonConstantsCircuit<Scalar, A>, domain: Option<u64>) -> Self {
+    Self { state: Vec::new(), constants, num_absorbs: None, domain, squeezed: false }
+  }
+
+  /// Clear the absorbed state so the gadget can be reused, see
+  /// [`PoseidonRO::reset`]. The gadget only holds references to allocated
+  /// variables, so no constraints from prior rounds are carried over.
+  pub fn reset(&mut self) {
+    self.state.clear();
+    self.squeezed = false;
+  }
+}
+
+impl<Scalar: PrimeField + PrimeFieldBits, A: PoseidonArity<Scalar>> PoseidonROCircuit<Scalar, A> {
+  /// Compute `count` challenges of `num_bits` each with a single sponge
+  /// invocation, see [`PoseidonRO::squeeze_many`].
+  pub fn squeeze_many<CS: ConstraintSystem<Scalar>>(
+    &mut self,
+    cs: CS,
+    num_bits: usize,
+    count: usize,
+  ) -> Result<Vec<Vec<AllocatedBit>>, SynthesisError> {
+    self.squeeze_with_widths(cs, &vec![num_bits; count])
+  }
+
+  /// Compute one challenge per entry of `widths` with a single sponge
+  /// invocation, see [`PoseidonRO::squeeze_with_widths`].
+  pub fn squeeze_with_widths<CS: ConstraintSystem<Scalar>>(
+    &mut self,
+    mut cs: CS,
+    widths: &[usize],
+  ) -> Result<Vec<Vec<AllocatedBit>>, SynthesisError> {
+    if let Some(num_bits) = widths.iter().find(|num_bits| **num_bits > Scalar::NUM_BITS as usize) {
+      return Err(SynthesisError::IncompatibleLengthVector(format!(
+        "{num_bits} > {}",
+        Scalar::NUM_BITS
+      )));
+    }
+
+    let mut ns = cs.namespace(|| "ns");
+    let hash = self.squeeze_elements(&mut ns, widths.len())?;
+
+    // return each hash as a vector of bits, truncated
+    hash
+      .iter()
+      .zip(widths)
+      .enumerate()
+      .map(|(i, (h, num_bits))| {
+        let h =
+          Elt::ensure_allocated(h, &mut ns.namespace(|| format!("ensure allocated {i}")), true)?;
+        Ok(
+          h.to_bits_le_strict(ns.namespace(|| format!("poseidon hash {i} to boolean")))?
+            .iter()
+            .map(|boolean| match boolean {
+              Boolean::Is(ref x) => x.clone(),
+              _ => panic!("Wrong type of input. We should have never reached there"),
+            })
+            .collect::<Vec<AllocatedBit>>()[..*num_bits]
+            .into(),
+        )
+      })
+      .collect()
+  }
+
+  /// Compute a challenge as the full first hash output, see
+  /// [`PoseidonRO::squeeze_field`].
+  pub fn squeeze_field<CS: ConstraintSystem<Scalar>>(
+    &mut self,
+    mut cs: CS,
+  ) -> Result<AllocatedNum<Scalar>, SynthesisError> {
+    let mut ns = cs.namespace(|| "ns");
+    let hash = self.squeeze_elements(&mut ns, 1)?;
+    Elt::ensure_allocated(&hash[0], &mut ns.namespace(|| "ensure allocated"), true)
+  }
+
+  /// Hash the current state once and squeeze `count` elements out of the
+  /// same sponge, failing like [`PoseidonRO`] on a mismatched IO pattern
+  fn squeeze_elements<CS: ConstraintSystem<Scalar>>(
+    &mut self,
+    cs: &mut CS,
+    count: usize,
+  ) -> Result<Vec<Elt<Scalar>>, SynthesisError> {
+    // check if we have squeezed already
+    assert!(!self.squeezed, "Cannot squeeze again after squeezing");
+    self.squeezed = true;
+
+    let input = self
+      .domain
+      .map(|domain| Elt::num_from_fr::<CS>(Scalar::from(domain)))
+      .into_iter()
+      .chain(self.state.iter().cloned().map(Elt::Allocated))
+      .collect::<Vec<Elt<Scalar>>>();
+    check_num_absorbs(self.num_absorbs, input.len()).map_err(sponge_synthesis_error)?;
+
+    let mut sponge = SpongeCircuit::new_with_constants(&self.constants.0, Simplex);
+    let parameter = sponge_io_pattern(input.len(), count);
+
+    sponge.start(parameter, None, cs);
+    SpongeAPI::absorb(&mut sponge, input.len() as u32, &input, cs);
+
+    let output = SpongeAPI::squeeze(&mut sponge, count as u32, cs);
+    sponge
+      .finish(cs)
+      .map_err(|err| sponge_synthesis_error(NovaError::SpongeError(format!("{err:?}"))))?;
+    Ok(output)
+  }
+}
+
+/// Surface a [`NovaError::SpongeError`] from the gadget, whose squeezes have to
+/// return a `SynthesisError`
+fn sponge_synthesis_error(err: NovaError) -> SynthesisError {
+  SynthesisError::IncompatibleLengthVector(err.to_string())
+}
+
+impl<Scalar, A> ROCircuitTrait<Scalar> for PoseidonROCircuit<Scalar, A>
+where
+  Scalar: PrimeField + PrimeFieldBits + Serialize + for<'de> Deserialize<'de>,
+  A: PoseidonArity<Scalar>,
 {
-  type Constants = PoseidonConstantsCircuit<Scalar>;
-  type NativeRO<T: PrimeField> = PoseidonRO<Scalar, T>;
+  type Constants = PoseidonConstantsCircuit<Scalar, A>;
+  type NativeRO<T: PrimeField> = PoseidonRO<Scalar, T, A>;
 
   /// Initialize the internal state and set the poseidon constants
-  fn new(constants: PoseidonConstantsCircuit<Scalar>, num_absorbs: usize) -> Self {
-    Self { state: Vec::new(), constants, num_absorbs, squeezed: false }
+  fn new(constants: PoseidonConstantsCircuit<Scalar, A>, num_absorbs: usize) -> Self {
+    Self::new_with_domain(constants, num_absorbs, None)
   }
 
   /// Absorb a new number into the state of the oracle
@@ -123,79 +575,36 @@ where Scalar: PrimeField + PrimeFieldBits + Serialize + for<'de> Deserialize<'de
   /// Compute a challenge by hashing the current state
   fn squeeze<CS: ConstraintSystem<Scalar>>(
     &mut self,
-    mut cs: CS,
+    cs: CS,
     num_bits: usize,
   ) -> Result<Vec<AllocatedBit>, SynthesisError> {
-    // check if we have squeezed already
-    assert!(!self.squeezed, "Cannot squeeze again after squeezing");
-    self.squeezed = true;
-    let parameter =
-      IOPattern(vec![SpongeOp::Absorb(self.num_absorbs as u32), SpongeOp::Squeeze(1u32)]);
-    let mut ns = cs.namespace(|| "ns");
-
-    let hash = {
-      let mut sponge = SpongeCircuit::new_with_constants(&self.constants.0, Simplex);
-      let acc = &mut ns;
-      assert_eq!(self.num_absorbs, self.state.len());
-
-      sponge.start(parameter, None, acc);
-      SpongeAPI::absorb(
-        &mut sponge,
-        self.num_absorbs as u32,
-        &(0..self.state.len())
-          .map(|i| Elt::Allocated(self.state[i].clone()))
-          .collect::<Vec<Elt<Scalar>>>(),
-        acc,
-      );
-
-      let output = SpongeAPI::squeeze(&mut sponge, 1, acc);
-      sponge.finish(acc).unwrap();
-      output
-    };
-
-    let hash = Elt::ensure_allocated(&hash[0], &mut ns.namespace(|| "ensure allocated"), true)?;
-
-    // return the hash as a vector of bits, truncated
-    Ok(
-      hash
-        .to_bits_le_strict(ns.namespace(|| "poseidon hash to boolean"))?
-        .iter()
-        .map(|boolean| match boolean {
-          Boolean::Is(ref x) => x.clone(),
-          _ => panic!("Wrong type of input. We should have never reached there"),
-        })
-        .collect::<Vec<AllocatedBit>>()[..num_bits]
-        .into(),
-    )
+    Ok(self.squeeze_many(cs, num_bits, 1)?.swap_remove(0))
   }
 }
 
 #[cfg(test)]
 mod tests {
   use ff::Field;
+  use generic_array::typenum::U8;
   use rand::rngs::OsRng;
 
   use super::*;
   use crate::{
     bellpepper::solver::SatisfyingAssignment,
     constants::NUM_CHALLENGE_BITS,
-    gadgets::le_bits_to_num,
-    provider::{Bn256EngineKZG, GrumpkinEngine},
+    gadgets::{alloc_one, le_bits_to_num},
+    provider::{
+      Bn256EngineKZG, GrumpkinEngine, PallasEngine, Secp256k1Engine, Secq256k1Engine, VestaEngine,
+    },
     traits::Engine,
   };
 
-  fn test_poseidon_ro_with<E: Engine>()
-  where
-    // we can print the field elements we get from E's Base & Scalar fields,
-    // and compare their byte representations
-    <<E as Engine>::Base as PrimeField>::Repr: std::fmt::Debug,
-    <<E as Engine>::Scalar as PrimeField>::Repr: std::fmt::Debug,
-    <<E as Engine>::Base as PrimeField>::Repr:
-      PartialEq<<<E as Engine>::Scalar as PrimeField>::Repr>, {
+  fn test_poseidon_ro_with<E: Engine>(strength: Strength) {
     // Check that the number computed inside the circuit is equal to the number
     // computed outside the circuit
     let mut csprng: OsRng = OsRng;
-    let constants = PoseidonConstantsCircuit::<E::Scalar>::default();
+    let constants = PoseidonConstantsCircuit::<E::Scalar>::with_strength(strength);
+    assert_eq!(constants.strength(), strength);
     let num_absorbs = 32;
     let mut ro: PoseidonRO<E::Scalar, E::Base> = PoseidonRO::new(constants.clone(), num_absorbs);
     let mut ro_gadget: PoseidonROCircuit<E::Scalar> =
@@ -208,15 +617,614 @@ mod tests {
       num_gadget.inputize(&mut cs.namespace(|| format!("input {i}"))).unwrap();
       ro_gadget.absorb(&num_gadget);
     }
-    let num = ro.squeeze(NUM_CHALLENGE_BITS);
+    let num = ro.squeeze(NUM_CHALLENGE_BITS).unwrap();
     let num2_bits = ro_gadget.squeeze(&mut cs, NUM_CHALLENGE_BITS).unwrap();
     let num2 = le_bits_to_num(&mut cs, &num2_bits).unwrap();
-    assert_eq!(num.to_repr(), num2.get_value().unwrap().to_repr());
+    assert_eq!(num.to_repr().as_ref(), num2.get_value().unwrap().to_repr().as_ref());
   }
 
   #[test]
   fn test_poseidon_ro() {
-    test_poseidon_ro_with::<Bn256EngineKZG>();
-    test_poseidon_ro_with::<GrumpkinEngine>();
+    test_poseidon_ro_with::<Bn256EngineKZG>(Strength::Standard);
+    test_poseidon_ro_with::<GrumpkinEngine>(Strength::Standard);
+    test_poseidon_ro_with::<PallasEngine>(Strength::Standard);
+    test_poseidon_ro_with::<VestaEngine>(Strength::Standard);
+    test_poseidon_ro_with::<Secp256k1Engine>(Strength::Standard);
+    test_poseidon_ro_with::<Secq256k1Engine>(Strength::Standard);
+  }
+
+  // `OsRng` is unavailable on `wasm32-unknown-unknown`, so the inputs are fixed
+  #[cfg(target_arch = "wasm32")]
+  #[test]
+  fn test_poseidon_ro_wasm() {
+    type E = Bn256EngineKZG;
+    let constants = PoseidonConstantsCircuit::<<E as Engine>::Scalar>::default();
+    let num_absorbs = 4;
+    let mut ro: PoseidonRO<<E as Engine>::Scalar, <E as Engine>::Base> =
+      PoseidonRO::new(constants.clone(), num_absorbs);
+    let mut ro_gadget: PoseidonROCircuit<<E as Engine>::Scalar> =
+      PoseidonROCircuit::new(constants, num_absorbs);
+    let mut cs = SatisfyingAssignment::<E>::new();
+    for i in 0..num_absorbs {
+      let num = <E as Engine>::Scalar::from(i as u64);
+      ro.absorb(num);
+      let num_gadget = AllocatedNum::alloc_infallible(cs.namespace(|| format!("data {i}")), || num);
+      ro_gadget.absorb(&num_gadget);
+    }
+    let num = ro.squeeze(NUM_CHALLENGE_BITS).unwrap();
+    let num2_bits = ro_gadget.squeeze(&mut cs, NUM_CHALLENGE_BITS).unwrap();
+    let num2 = le_bits_to_num(&mut cs, &num2_bits).unwrap();
+    assert_eq!(num.to_repr().as_ref(), num2.get_value().unwrap().to_repr().as_ref());
+  }
+
+  fn test_bits_to_scalar_ct_with<E: Engine>() {
+    let mut csprng: OsRng = OsRng;
+    for _ in 0..16 {
+      let h = E::Scalar::random(&mut csprng);
+      for num_bits in [0, 1, NUM_CHALLENGE_BITS, E::Scalar::NUM_BITS as usize] {
+        assert_eq!(
+          bits_to_scalar::<E::Scalar, E::Base>(&h, num_bits),
+          bits_to_scalar_ct::<E::Scalar, E::Base>(&h, num_bits)
+        );
+      }
+    }
+  }
+
+  #[test]
+  fn test_bits_to_scalar_ct() {
+    test_bits_to_scalar_ct_with::<Bn256EngineKZG>();
+    test_bits_to_scalar_ct_with::<GrumpkinEngine>();
+  }
+
+  fn test_poseidon_ro_squeeze_field_with<E: Engine>() {
+    let mut csprng: OsRng = OsRng;
+    let constants = PoseidonConstantsCircuit::<E::Scalar>::default();
+    let inputs = (0..8).map(|_| E::Scalar::random(&mut csprng)).collect::<Vec<_>>();
+    let native_ro = || {
+      let mut ro: PoseidonRO<E::Scalar, E::Base> = PoseidonRO::new(constants.clone(), inputs.len());
+      inputs.iter().for_each(|x| ro.absorb(*x));
+      ro
+    };
+
+    let mut cs = SatisfyingAssignment::<E>::new();
+    let mut ro_gadget: PoseidonROCircuit<E::Scalar> =
+      PoseidonROCircuit::new(constants.clone(), inputs.len());
+    for (i, x) in inputs.iter().enumerate() {
+      let num_gadget = AllocatedNum::alloc_infallible(cs.namespace(|| format!("data {i}")), || *x);
+      ro_gadget.absorb(&num_gadget);
+    }
+    let full = ro_gadget.squeeze_field(&mut cs).unwrap().get_value().unwrap();
+
+    // the hash is over `E::Scalar`, which fits in `E::Base` for these engines,
+    // so neither side reduces it
+    let native_full = native_ro().squeeze_field().unwrap();
+    assert_eq!(full.to_repr().as_ref(), native_full.to_repr().as_ref());
+
+    // truncating the full output gives back the usual challenge
+    let challenge = native_ro().squeeze(NUM_CHALLENGE_BITS).unwrap();
+    assert_eq!(challenge, bits_to_scalar::<E::Base, E::Base>(&native_full, NUM_CHALLENGE_BITS));
+  }
+
+  #[test]
+  fn test_poseidon_ro_squeeze_field() {
+    test_poseidon_ro_squeeze_field_with::<Bn256EngineKZG>();
+    test_poseidon_ro_squeeze_field_with::<VestaEngine>();
+  }
+
+  fn test_poseidon_ro_domain_with<E: Engine>() {
+    let mut csprng: OsRng = OsRng;
+    let constants = PoseidonConstantsCircuit::<E::Scalar>::default();
+    let inputs = (0..8).map(|_| E::Scalar::random(&mut csprng)).collect::<Vec<_>>();
+    let num_absorbs = inputs.len() + 1;
+
+    let squeeze_native = |domain| {
+      let mut ro: PoseidonRO<E::Scalar, E::Base> =
+        PoseidonRO::new_with_domain(constants.clone(), num_absorbs, Some(domain));
+      inputs.iter().for_each(|x| ro.absorb(*x));
+      ro.squeeze(NUM_CHALLENGE_BITS).unwrap()
+    };
+    let squeeze_circuit = |domain| {
+      let mut cs = SatisfyingAssignment::<E>::new();
+      let mut ro_gadget: PoseidonROCircuit<E::Scalar> =
+        PoseidonROCircuit::new_with_domain(constants.clone(), num_absorbs, Some(domain));
+      for (i, x) in inputs.iter().enumerate() {
+        let num_gadget =
+          AllocatedNum::alloc_infallible(cs.namespace(|| format!("data {i}")), || *x);
+        ro_gadget.absorb(&num_gadget);
+      }
+      let bits = ro_gadget.squeeze(&mut cs, NUM_CHALLENGE_BITS).unwrap();
+      le_bits_to_num(&mut cs, &bits).unwrap().get_value().unwrap()
+    };
+
+    let (c1, c2) = (squeeze_native(1), squeeze_native(2));
+    assert_ne!(c1, c2);
+    assert_eq!(c1.to_repr().as_ref(), squeeze_circuit(1).to_repr().as_ref());
+    assert_eq!(c2.to_repr().as_ref(), squeeze_circuit(2).to_repr().as_ref());
+  }
+
+  #[test]
+  fn test_poseidon_ro_domain() {
+    test_poseidon_ro_domain_with::<Bn256EngineKZG>();
+    test_poseidon_ro_domain_with::<GrumpkinEngine>();
+  }
+
+  fn test_poseidon_ro_reset_with<E: Engine>() {
+    let mut csprng: OsRng = OsRng;
+    let constants = PoseidonConstantsCircuit::<E::Scalar>::default();
+    let num_absorbs = 4;
+    let rounds = (0..2)
+      .map(|_| (0..num_absorbs).map(|_| E::Scalar::random(&mut csprng)).collect::<Vec<_>>())
+      .collect::<Vec<_>>();
+
+    let mut ro: PoseidonRO<E::Scalar, E::Base> = PoseidonRO::new(constants.clone(), num_absorbs);
+    let mut ro_gadget: PoseidonROCircuit<E::Scalar> =
+      PoseidonROCircuit::new(constants.clone(), num_absorbs);
+    let mut cs = SatisfyingAssignment::<E>::new();
+    for (round, inputs) in rounds.iter().enumerate() {
+      let mut fresh: PoseidonRO<E::Scalar, E::Base> =
+        PoseidonRO::new(constants.clone(), num_absorbs);
+      for (i, x) in inputs.iter().enumerate() {
+        ro.absorb(*x);
+        fresh.absorb(*x);
+        let num_gadget =
+          AllocatedNum::alloc_infallible(cs.namespace(|| format!("data {round} {i}")), || *x);
+        ro_gadget.absorb(&num_gadget);
+      }
+      let num = ro.squeeze(NUM_CHALLENGE_BITS).unwrap();
+      assert_eq!(num, fresh.squeeze(NUM_CHALLENGE_BITS).unwrap());
+
+      let bits = ro_gadget.squeeze(cs.namespace(|| format!("squeeze {round}")), NUM_CHALLENGE_BITS);
+      let num2 = le_bits_to_num(cs.namespace(|| format!("num {round}")), &bits.unwrap()).unwrap();
+      assert_eq!(num.to_repr().as_ref(), num2.get_value().unwrap().to_repr().as_ref());
+
+      ro.reset();
+      ro_gadget.reset();
+    }
+  }
+
+  #[test]
+  fn test_poseidon_ro_reset() {
+    test_poseidon_ro_reset_with::<Bn256EngineKZG>();
+    test_poseidon_ro_reset_with::<GrumpkinEngine>();
+  }
+
+  fn test_poseidon_ro_squeeze_many_with<E: Engine>() {
+    let mut csprng: OsRng = OsRng;
+    let constants = PoseidonConstantsCircuit::<E::Scalar>::default();
+    let num_absorbs = 8;
+    let count = 3;
+    let mut ro: PoseidonRO<E::Scalar, E::Base> = PoseidonRO::new(constants.clone(), num_absorbs);
+    let mut ro_gadget: PoseidonROCircuit<E::Scalar> =
+      PoseidonROCircuit::new(constants, num_absorbs);
+    let mut cs = SatisfyingAssignment::<E>::new();
+    for i in 0..num_absorbs {
+      let num = E::Scalar::random(&mut csprng);
+      ro.absorb(num);
+      let num_gadget = AllocatedNum::alloc_infallible(cs.namespace(|| format!("data {i}")), || num);
+      ro_gadget.absorb(&num_gadget);
+    }
+    let nums = ro.squeeze_many(NUM_CHALLENGE_BITS, count).unwrap();
+    let nums2_bits = ro_gadget.squeeze_many(&mut cs, NUM_CHALLENGE_BITS, count).unwrap();
+    assert_eq!(nums.len(), count);
+    assert_eq!(nums2_bits.len(), count);
+    assert_ne!(nums[0], nums[1]);
+    for (i, (num, num2_bits)) in nums.iter().zip(nums2_bits.iter()).enumerate() {
+      let num2 = le_bits_to_num(cs.namespace(|| format!("num {i}")), num2_bits).unwrap();
+      assert_eq!(num.to_repr().as_ref(), num2.get_value().unwrap().to_repr().as_ref());
+    }
+  }
+
+  #[test]
+  fn test_poseidon_ro_squeeze_many() {
+    test_poseidon_ro_squeeze_many_with::<Bn256EngineKZG>();
+    test_poseidon_ro_squeeze_many_with::<GrumpkinEngine>();
+  }
+
+  fn test_poseidon_ro_squeeze_with_widths_with<E: Engine>() {
+    let mut csprng: OsRng = OsRng;
+    let constants = PoseidonConstantsCircuit::<E::Scalar>::default();
+    let num_absorbs = 8;
+    let widths = [128, 250, 1];
+    let mut ro: PoseidonRO<E::Scalar, E::Base> = PoseidonRO::new(constants.clone(), num_absorbs);
+    let mut ro_gadget: PoseidonROCircuit<E::Scalar> =
+      PoseidonROCircuit::new(constants, num_absorbs);
+    let mut cs = SatisfyingAssignment::<E>::new();
+    for i in 0..num_absorbs {
+      let num = E::Scalar::random(&mut csprng);
+      ro.absorb(num);
+      let num_gadget = AllocatedNum::alloc_infallible(cs.namespace(|| format!("data {i}")), || num);
+      ro_gadget.absorb(&num_gadget);
+    }
+    let nums = ro.squeeze_with_widths(&widths).unwrap();
+    let nums2_bits = ro_gadget.squeeze_with_widths(&mut cs, &widths).unwrap();
+    assert_eq!(nums.len(), widths.len());
+    for (i, ((num, num2_bits), num_bits)) in nums.iter().zip(&nums2_bits).zip(widths).enumerate() {
+      assert_eq!(num2_bits.len(), num_bits);
+      let num2 = le_bits_to_num(cs.namespace(|| format!("num {i}")), num2_bits).unwrap();
+      assert_eq!(num.to_repr().as_ref(), num2.get_value().unwrap().to_repr().as_ref());
+    }
+  }
+
+  fn test_poseidon_ro_io_pattern_with<E: Engine>() {
+    let mut csprng: OsRng = OsRng;
+    let domain = 7u64;
+    let elements = (0..4).map(|_| E::Scalar::random(&mut csprng)).collect::<Vec<_>>();
+    let is_pattern = |pattern: &IOPattern, num_absorbs: u32| match pattern.0[..] {
+      [SpongeOp::Absorb(n), SpongeOp::Squeeze(1)] => n == num_absorbs,
+      _ => false,
+    };
+
+    // the pattern counts the domain tag along with the absorbed elements
+    let constants = PoseidonConstantsCircuit::<E::Scalar>::default();
+    let mut ro: PoseidonRO<E::Scalar, E::Base> =
+      PoseidonRO::new_with_domain(constants.clone(), elements.len() + 1, Some(domain));
+    elements.iter().for_each(|e| ro.absorb(*e));
+    let pattern = ro.io_pattern();
+    assert!(is_pattern(&pattern, elements.len() as u32 + 1));
+
+    // an inferred oracle's pattern follows what it absorbed
+    let mut inferred: PoseidonRO<E::Scalar, E::Base> =
+      PoseidonRO::new_inferred(constants.clone(), Some(domain));
+    assert!(is_pattern(&inferred.io_pattern(), 1));
+    elements.iter().for_each(|e| inferred.absorb(*e));
+    assert!(is_pattern(&inferred.io_pattern(), elements.len() as u32 + 1));
+
+    // a sponge run with the pattern over the documented order reproduces the
+    // challenge
+    let input = std::iter::once(E::Scalar::from(domain)).chain(elements).collect::<Vec<_>>();
+    let mut sponge = Sponge::new_with_constants(&constants.0, Simplex);
+    let acc = &mut ();
+    sponge.start(pattern, None, acc);
+    SpongeAPI::absorb(&mut sponge, input.len() as u32, &input, acc);
+    let hash = SpongeAPI::squeeze(&mut sponge, 1, acc);
+    sponge.finish(acc).unwrap();
+    assert_eq!(
+      E::Base::from_u128(ro.squeeze_u128().unwrap()),
+      to_scalar::<_, E::Base>(&hash[0], 128)
+    );
+  }
+
+  #[test]
+  fn test_poseidon_ro_io_pattern() {
+    test_poseidon_ro_io_pattern_with::<Bn256EngineKZG>();
+    test_poseidon_ro_io_pattern_with::<PallasEngine>();
+  }
+
+  fn test_poseidon_ro_squeeze_int_with<E: Engine>() {
+    let mut csprng: OsRng = OsRng;
+    let num_absorbs = 4;
+    let mut ro: PoseidonRO<E::Scalar, E::Base> =
+      PoseidonRO::new(PoseidonConstantsCircuit::default(), num_absorbs);
+    (0..num_absorbs).for_each(|_| ro.absorb(E::Scalar::random(&mut csprng)));
+
+    // the integers are the low bits of the field challenges
+    assert_eq!(E::Base::from(ro.clone().squeeze_u64().unwrap()), ro.clone().squeeze(64).unwrap());
+    assert_eq!(
+      E::Base::from_u128(ro.clone().squeeze_u128().unwrap()),
+      ro.clone().squeeze(128).unwrap()
+    );
+    assert_eq!(ro.clone().squeeze_u64().unwrap(), ro.squeeze_u128().unwrap() as u64);
+  }
+
+  #[test]
+  fn test_poseidon_ro_squeeze_int() {
+    test_poseidon_ro_squeeze_int_with::<Bn256EngineKZG>();
+    test_poseidon_ro_squeeze_int_with::<PallasEngine>();
+  }
+
+  #[test]
+  fn test_poseidon_ro_squeeze_with_widths() {
+    test_poseidon_ro_squeeze_with_widths_with::<Bn256EngineKZG>();
+    test_poseidon_ro_squeeze_with_widths_with::<GrumpkinEngine>();
+  }
+
+  fn test_poseidon_ro_inferred_with<E: Engine>() {
+    let mut csprng: OsRng = OsRng;
+    let constants = PoseidonConstantsCircuit::<E::Scalar>::default();
+
+    for num_absorbs in [1, 5, 17] {
+      let inputs = (0..num_absorbs).map(|_| E::Scalar::random(&mut csprng)).collect::<Vec<_>>();
+
+      let mut ro: PoseidonRO<E::Scalar, E::Base> = PoseidonRO::new(constants.clone(), num_absorbs);
+      let mut ro_inferred: PoseidonRO<E::Scalar, E::Base> =
+        PoseidonRO::new_inferred(constants.clone(), None);
+      let mut ro_gadget: PoseidonROCircuit<E::Scalar> =
+        PoseidonROCircuit::new_inferred(constants.clone(), None);
+      let mut cs = SatisfyingAssignment::<E>::new();
+      for (i, x) in inputs.iter().enumerate() {
+        ro.absorb(*x);
+        ro_inferred.absorb(*x);
+        let num_gadget =
+          AllocatedNum::alloc_infallible(cs.namespace(|| format!("data {i}")), || *x);
+        ro_gadget.absorb(&num_gadget);
+      }
+
+      // the inferred oracles agree with each other and with the declared one
+      let num = ro.squeeze(NUM_CHALLENGE_BITS).unwrap();
+      let num_inferred = ro_inferred.squeeze(NUM_CHALLENGE_BITS).unwrap();
+      let bits = ro_gadget.squeeze(&mut cs, NUM_CHALLENGE_BITS).unwrap();
+      let num_gadget = le_bits_to_num(&mut cs, &bits).unwrap();
+      assert_eq!(num, num_inferred);
+      assert_eq!(num.to_repr().as_ref(), num_gadget.get_value().unwrap().to_repr().as_ref());
+    }
+  }
+
+  #[test]
+  fn test_poseidon_ro_inferred() {
+    test_poseidon_ro_inferred_with::<Bn256EngineKZG>();
+    test_poseidon_ro_inferred_with::<GrumpkinEngine>();
+  }
+
+  fn test_poseidon_ro_same_field_with<E: Engine>() {
+    let mut csprng: OsRng = OsRng;
+    let constants = PoseidonConstantsCircuit::<E::Scalar>::default();
+    let inputs = (0..8).map(|_| E::Scalar::random(&mut csprng)).collect::<Vec<_>>();
+    let squeeze = |inputs: &[E::Scalar]| {
+      let mut ro = PoseidonROSameField::new(constants.clone(), inputs.len());
+      inputs.iter().for_each(|x| ro.absorb(*x));
+      ro.squeeze().unwrap()
+    };
+
+    // the same absorb sequence yields the same challenge, a different one diverges
+    let c = squeeze(&inputs);
+    assert_eq!(c, squeeze(&inputs));
+    assert_ne!(c, squeeze(&inputs[1..]));
+
+    // the challenge is a canonical element of the field that agrees with the full
+    // output of the two-field oracle over the same field
+    assert_eq!(E::Scalar::from_repr(c.to_repr()).unwrap(), c);
+    let mut ro: PoseidonRO<E::Scalar, E::Scalar> = PoseidonRO::new(constants.clone(), inputs.len());
+    inputs.iter().for_each(|x| ro.absorb(*x));
+    assert_eq!(ro.squeeze_field().unwrap(), c);
+  }
+
+  #[test]
+  fn test_poseidon_ro_same_field() {
+    test_poseidon_ro_same_field_with::<Bn256EngineKZG>();
+    test_poseidon_ro_same_field_with::<GrumpkinEngine>();
+  }
+
+  fn test_poseidon_ro_clone_with<E: Engine>() {
+    let mut csprng: OsRng = OsRng;
+    let constants = PoseidonConstantsCircuit::<E::Scalar>::default();
+    let inputs = (0..5).map(|_| E::Scalar::random(&mut csprng)).collect::<Vec<_>>();
+
+    let mut ro: PoseidonRO<E::Scalar, E::Base> = PoseidonRO::new(constants.clone(), 4);
+    let mut ro_gadget: PoseidonROCircuit<E::Scalar> = PoseidonROCircuit::new(constants, 4);
+    let mut cs = SatisfyingAssignment::<E>::new();
+    let nums = inputs
+      .iter()
+      .enumerate()
+      .map(|(i, x)| AllocatedNum::alloc_infallible(cs.namespace(|| format!("data {i}")), || *x))
+      .collect::<Vec<_>>();
+    for (x, num) in inputs.iter().zip(&nums).take(3) {
+      ro.absorb(*x);
+      ro_gadget.absorb(num);
+    }
+
+    // the clones continue independently of the originals
+    let mut ro_fork = ro.clone();
+    let mut ro_gadget_fork = ro_gadget.clone();
+    ro.absorb(inputs[3]);
+    ro_fork.absorb(inputs[4]);
+    ro_gadget.absorb(&nums[3]);
+    ro_gadget_fork.absorb(&nums[4]);
+
+    let mut squeeze_gadget = |ro: &mut PoseidonROCircuit<E::Scalar>, name: &str| {
+      let bits =
+        ro.squeeze(cs.namespace(|| format!("squeeze {name}")), NUM_CHALLENGE_BITS).unwrap();
+      le_bits_to_num(cs.namespace(|| format!("num {name}")), &bits).unwrap().get_value().unwrap()
+    };
+    let num_gadget = squeeze_gadget(&mut ro_gadget, "original");
+    let num_gadget_fork = squeeze_gadget(&mut ro_gadget_fork, "fork");
+    let num = ro.squeeze(NUM_CHALLENGE_BITS).unwrap();
+    let num_fork = ro_fork.squeeze(NUM_CHALLENGE_BITS).unwrap();
+
+    assert_ne!(num, num_fork);
+    assert_eq!(num.to_repr().as_ref(), num_gadget.to_repr().as_ref());
+    assert_eq!(num_fork.to_repr().as_ref(), num_gadget_fork.to_repr().as_ref());
+  }
+
+  #[test]
+  fn test_poseidon_ro_clone() {
+    test_poseidon_ro_clone_with::<Bn256EngineKZG>();
+    test_poseidon_ro_clone_with::<GrumpkinEngine>();
+  }
+
+  fn test_poseidon_ro_sponge_error_with<E: Engine>() {
+    let constants = PoseidonConstantsCircuit::<E::Scalar>::default();
+    let inputs = [E::Scalar::from(1u64), E::Scalar::from(2u64)];
+
+    // declaring one more absorb than performed mismatches the IO pattern
+    let mut ro: PoseidonRO<E::Scalar, E::Base> =
+      PoseidonRO::new(constants.clone(), inputs.len() + 1);
+    let mut ro_gadget: PoseidonROCircuit<E::Scalar> =
+      PoseidonROCircuit::new(constants, inputs.len() + 1);
+    let mut cs = SatisfyingAssignment::<E>::new();
+    for (i, x) in inputs.iter().enumerate() {
+      ro.absorb(*x);
+      let num_gadget = AllocatedNum::alloc_infallible(cs.namespace(|| format!("data {i}")), || *x);
+      ro_gadget.absorb(&num_gadget);
+    }
+
+    assert!(matches!(ro.squeeze(NUM_CHALLENGE_BITS), Err(NovaError::SpongeError(_))));
+    assert!(ro_gadget.squeeze(&mut cs, NUM_CHALLENGE_BITS).is_err());
+  }
+
+  #[test]
+  fn test_poseidon_ro_sponge_error() {
+    test_poseidon_ro_sponge_error_with::<Bn256EngineKZG>();
+    test_poseidon_ro_sponge_error_with::<GrumpkinEngine>();
+  }
+
+  fn test_poseidon_ro_state_roundtrip_with<E: Engine>() {
+    let mut csprng: OsRng = OsRng;
+    let constants = PoseidonConstantsCircuit::<E::Scalar>::default();
+    let num_absorbs = 8;
+    let inputs = (0..num_absorbs).map(|_| E::Scalar::random(&mut csprng)).collect::<Vec<_>>();
+
+    let mut ro: PoseidonRO<E::Scalar, E::Base> = PoseidonRO::new(constants.clone(), num_absorbs);
+    let mut ro_checkpointed: PoseidonRO<E::Scalar, E::Base> =
+      PoseidonRO::new(constants.clone(), num_absorbs);
+    let (first, second) = inputs.split_at(num_absorbs / 2);
+    first.iter().for_each(|x| {
+      ro.absorb(*x);
+      ro_checkpointed.absorb(*x);
+    });
+
+    let bytes = bincode::serialize(&ro_checkpointed.export_state()).unwrap();
+    let state: ROState<E::Scalar> = bincode::deserialize(&bytes).unwrap();
+    let mut ro_restored: PoseidonRO<E::Scalar, E::Base> = PoseidonRO::from_state(constants, state);
+    second.iter().for_each(|x| {
+      ro.absorb(*x);
+      ro_restored.absorb(*x);
+    });
+
+    let num = ro.squeeze(NUM_CHALLENGE_BITS).unwrap();
+    let num2 = ro_restored.squeeze(NUM_CHALLENGE_BITS).unwrap();
+    assert_eq!(num.to_repr().as_ref(), num2.to_repr().as_ref());
+  }
+
+  #[test]
+  fn test_poseidon_ro_state_roundtrip() {
+    test_poseidon_ro_state_roundtrip_with::<Bn256EngineKZG>();
+    test_poseidon_ro_state_roundtrip_with::<GrumpkinEngine>();
+  }
+
+  fn test_poseidon_ro_num_bits_bound_with<E: Engine>() {
+    let constants = PoseidonConstantsCircuit::<E::Scalar>::default();
+    let field_bits = E::Scalar::NUM_BITS as usize;
+    let squeeze = |num_bits| {
+      let mut ro: PoseidonRO<E::Scalar, E::Base> = PoseidonRO::new(constants.clone(), 1);
+      ro.absorb(E::Scalar::ONE);
+      ro.squeeze(num_bits)
+    };
+    let squeeze_circuit = |num_bits| {
+      let mut cs = SatisfyingAssignment::<E>::new();
+      let mut ro_gadget: PoseidonROCircuit<E::Scalar> =
+        PoseidonROCircuit::new(constants.clone(), 1);
+      ro_gadget.absorb(&alloc_one(cs.namespace(|| "one")));
+      ro_gadget.squeeze(&mut cs, num_bits)
+    };
+
+    assert_eq!(squeeze(10_000), Err(NovaError::InvalidNumBits));
+    assert!(matches!(squeeze_circuit(10_000), Err(SynthesisError::IncompatibleLengthVector(_))));
+
+    // the full bit length of the squeezed element is still accepted
+    assert!(squeeze(field_bits).is_ok());
+    assert_eq!(squeeze_circuit(field_bits).unwrap().len(), field_bits);
+  }
+
+  #[test]
+  fn test_poseidon_ro_num_bits_bound() {
+    test_poseidon_ro_num_bits_bound_with::<Bn256EngineKZG>();
+    test_poseidon_ro_num_bits_bound_with::<GrumpkinEngine>();
+  }
+
+  fn test_poseidon_ro_absorb_bytes_with<E: Engine>() {
+    let squeeze = |bytes: &[u8]| {
+      let mut ro: PoseidonRO<E::Base, E::Scalar> =
+        PoseidonRO::new(PoseidonConstantsCircuit::default(), 1);
+      ro.absorb(E::Base::ONE);
+      ro.absorb_bytes(bytes);
+      ro.squeeze(NUM_CHALLENGE_BITS).unwrap()
+    };
+
+    // 40 bytes span two packed elements, on top of the length prefix
+    let message = (0..40u8).collect::<Vec<_>>();
+    let mut other = message.clone();
+    other[35] ^= 1;
+    assert_eq!(squeeze(&message), squeeze(&message));
+    assert_ne!(squeeze(&message), squeeze(&other));
+
+    // the length prefix tells apart messages that only differ in trailing zeros
+    assert_ne!(squeeze(&[1]), squeeze(&[1, 0]));
+    assert_ne!(squeeze(&[]), squeeze(&[0]));
+  }
+
+  #[test]
+  fn test_poseidon_ro_absorb_bytes() {
+    test_poseidon_ro_absorb_bytes_with::<Bn256EngineKZG>();
+    test_poseidon_ro_absorb_bytes_with::<GrumpkinEngine>();
+  }
+
+  fn test_poseidon_ro_arity_with<E: Engine>() {
+    // absorb more elements than the state holds, so the sponge permutes several times
+    let constants = PoseidonConstantsCircuit::<E::Scalar, U8>::default();
+    let inputs = (0..20).map(|i| E::Scalar::from(i)).collect::<Vec<_>>();
+    let mut ro: PoseidonRO<E::Scalar, E::Base, U8> =
+      PoseidonRO::new(constants.clone(), inputs.len());
+    let mut ro_gadget: PoseidonROCircuit<E::Scalar, U8> =
+      PoseidonROCircuit::new(constants, inputs.len());
+    let mut cs = SatisfyingAssignment::<E>::new();
+    for (i, input) in inputs.iter().enumerate() {
+      ro.absorb(*input);
+      ro_gadget
+        .absorb(&AllocatedNum::alloc_infallible(cs.namespace(|| format!("data {i}")), || *input));
+    }
+    let num = ro.squeeze(NUM_CHALLENGE_BITS).unwrap();
+    let num_bits = ro_gadget.squeeze(&mut cs, NUM_CHALLENGE_BITS).unwrap();
+    let num_gadget = le_bits_to_num(&mut cs, &num_bits).unwrap();
+    assert_eq!(num.to_repr().as_ref(), num_gadget.get_value().unwrap().to_repr().as_ref());
+
+    // the default arity hashes the same inputs differently
+    let mut ro_default: PoseidonRO<E::Scalar, E::Base> =
+      PoseidonRO::new(PoseidonConstantsCircuit::default(), inputs.len());
+    inputs.iter().for_each(|input| ro_default.absorb(*input));
+    assert_ne!(num, ro_default.squeeze(NUM_CHALLENGE_BITS).unwrap());
+  }
+
+  #[test]
+  fn test_poseidon_ro_arity() {
+    test_poseidon_ro_arity_with::<Bn256EngineKZG>();
+    test_poseidon_ro_arity_with::<GrumpkinEngine>();
+  }
+
+  fn test_poseidon_constants_cache_with<E: Engine>() {
+    // cached constants are the ones a fresh generation yields, per arity and
+    // strength
+    for strength in [Strength::Standard, Strength::Strengthened] {
+      let fresh = Sponge::<E::Scalar, U24>::api_constants(strength);
+      assert_eq!(PoseidonConstantsCircuit::<E::Scalar>::with_strength(strength).0, fresh);
+      assert_eq!(PoseidonConstantsCircuit::<E::Scalar>::with_strength(strength).0, fresh);
+    }
+    let fresh = Sponge::<E::Scalar, U8>::api_constants(Strength::Standard);
+    assert_eq!(PoseidonConstantsCircuit::<E::Scalar, U8>::default().0, fresh);
+    assert_eq!(PoseidonConstantsCircuit::<E::Scalar, U8>::default().0, fresh);
+    assert_eq!(
+      PoseidonConstantsCircuit::<E::Scalar>::default(),
+      PoseidonConstantsCircuit::<E::Scalar>::default()
+    );
+  }
+
+  #[test]
+  fn test_poseidon_constants_cache() {
+    test_poseidon_constants_cache_with::<Bn256EngineKZG>();
+    test_poseidon_constants_cache_with::<GrumpkinEngine>();
+  }
+
+  #[cfg(feature = "transcript-trace")]
+  #[tracing_test::traced_test]
+  #[test]
+  fn test_poseidon_ro_transcript_trace() {
+    type E = Bn256EngineKZG;
+    let mut ro: PoseidonRO<<E as Engine>::Scalar, <E as Engine>::Base> =
+      PoseidonRO::new(PoseidonConstantsCircuit::default(), 5);
+    (0..5u64).for_each(|i| ro.absorb(<E as Engine>::Scalar::from(i)));
+    ro.squeeze(NUM_CHALLENGE_BITS).unwrap();
+
+    logs_assert(|lines: &[&str]| {
+      let count = |event: &str| lines.iter().filter(|line| line.contains(event)).count();
+      match (count("poseidon ro absorb"), count("poseidon ro squeeze")) {
+        (5, 1) => Ok(()),
+        (absorbs, squeezes) => Err(format!("logged {absorbs} absorbs and {squeezes} squeezes")),
+      }
+    });
+  }
+
+  #[test]
+  fn test_poseidon_ro_strengthened() {
+    test_poseidon_ro_with::<Bn256EngineKZG>(Strength::Strengthened);
+    test_poseidon_ro_with::<GrumpkinEngine>(Strength::Strengthened);
   }
 }
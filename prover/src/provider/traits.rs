@@ -6,7 +6,10 @@ use group::{
 };
 use serde::{Deserialize, Serialize};
 
-use crate::traits::{Group, TranscriptReprTrait};
+use crate::{
+  provider::MsmConfig,
+  traits::{Group, TranscriptReprTrait},
+};
 
 /// A trait that defines extensions to the Group trait
 pub trait DlogGroup:
@@ -39,9 +42,21 @@ pub trait DlogGroup:
   /// A method to compute a multiexponentation
   fn vartime_multiscalar_mul(scalars: &[Self::ScalarExt], bases: &[Self::AffineExt]) -> Self;
 
+  /// A method to compute a multiexponentation with the backend selected by
+  /// `msm`
+  fn vartime_multiscalar_mul_with(
+    msm: MsmConfig,
+    scalars: &[Self::ScalarExt],
+    bases: &[Self::AffineExt],
+  ) -> Self;
+
   /// Produce a vector of group elements using a static label
   fn from_label(label: &'static [u8], n: usize) -> Vec<Self::Affine>;
 
+  /// Hash each 32-byte uniform string to a group element, as `from_label`
+  /// does for each 32 bytes it reads from the label's XOF
+  fn hash_to_affine(uniform_bytes: &[[u8; 32]]) -> Vec<Self::Affine>;
+
   /// Returns the affine coordinates (x, y, infinity) for the point
   fn to_coordinates(&self) -> (<Self as Group>::Base, <Self as Group>::Base, bool);
 }
@@ -74,6 +89,10 @@ macro_rules! impl_traits {
 
         (A, B, order, base)
       }
+
+      fn hash_to_group(bytes: &[u8]) -> Self {
+        $name::Point::hash_to_curve($crate::constants::HASH_TO_CURVE_DOMAIN)(bytes)
+      }
     }
 
     impl DlogGroup for $name::Point {
@@ -95,6 +114,19 @@ macro_rules! impl_traits {
         cpu_best_msm(bases, scalars)
       }
 
+      fn vartime_multiscalar_mul_with(
+        msm: $crate::provider::MsmConfig,
+        scalars: &[Self::ScalarExt],
+        bases: &[Self::AffineExt],
+      ) -> Self {
+        match msm {
+          $crate::provider::MsmConfig::Auto => Self::vartime_multiscalar_mul(scalars, bases),
+          $crate::provider::MsmConfig::Rayon => cpu_best_msm(bases, scalars),
+          $crate::provider::MsmConfig::Serial =>
+            $crate::provider::util::msm::cpu_serial_msm(bases, scalars),
+        }
+      }
+
       fn from_label(label: &'static [u8], n: usize) -> Vec<Self::Affine> {
         let mut shake = Shake256::default();
         shake.update(label);
@@ -141,6 +173,20 @@ macro_rules! impl_traits {
         }
       }
 
+      fn hash_to_affine(uniform_bytes: &[[u8; 32]]) -> Vec<Self::Affine> {
+        let gens_proj: Vec<$name::Point> = uniform_bytes
+          .par_iter()
+          .map(|bytes| {
+            let hash = $name::Point::hash_to_curve("from_uniform_bytes");
+            hash(bytes)
+          })
+          .collect();
+
+        let mut gens = vec![$name::Affine::identity(); uniform_bytes.len()];
+        <Self as Curve>::batch_normalize(&gens_proj, &mut gens);
+        gens
+      }
+
       fn to_coordinates(&self) -> (Self::Base, Self::Base, bool) {
         let coordinates = self.to_affine().coordinates();
         if coordinates.is_some().unwrap_u8() == 1 && ($name::Point::identity() != *self) {
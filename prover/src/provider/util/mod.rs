@@ -1,13 +1,38 @@
 //! Utilities for provider module.
 pub(in crate::provider) mod fb_msm;
 pub mod msm {
-  use halo2curves::{msm::best_multiexp, CurveAffine};
+  use group::Group;
+  use halo2curves::{
+    msm::{best_multiexp, multiexp_serial},
+    CurveAffine,
+  };
+
+  /// Selects the backend used to compute the multi-scalar multiplications of
+  /// a commitment. All backends produce the same result.
+  #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+  pub enum MsmConfig {
+    /// The curve's default dispatch: the accelerated `grumpkin-msm` backend
+    /// for large inputs where available (on the GPU when built with its
+    /// `cuda` feature), and `Rayon` otherwise
+    #[default]
+    Auto,
+    /// A multi-threaded MSM over the rayon thread pool
+    Rayon,
+    /// A single-threaded MSM, which avoids the fork overhead on small inputs
+    Serial,
+  }
 
   // this argument swap is useful until Rust gets named arguments
   // and saves significant complexity in macro code
   pub fn cpu_best_msm<C: CurveAffine>(bases: &[C], scalars: &[C::Scalar]) -> C::Curve {
     best_multiexp(scalars, bases)
   }
+
+  pub fn cpu_serial_msm<C: CurveAffine>(bases: &[C], scalars: &[C::Scalar]) -> C::Curve {
+    let mut acc = C::Curve::identity();
+    multiexp_serial(scalars, bases, &mut acc);
+    acc
+  }
 }
 
 pub mod field {
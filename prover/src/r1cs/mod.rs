@@ -9,7 +9,7 @@ use once_cell::sync::OnceCell;
 use rand_core::{CryptoRng, RngCore};
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
-pub(crate) use sparse::SparseMatrix;
+pub use sparse::{RowData, SparseMatrix};
 
 use crate::{
   constants::{BN_LIMB_WIDTH, BN_N_LIMBS},
@@ -17,7 +17,8 @@ use crate::{
   errors::NovaError,
   gadgets::{f_to_nat, nat_to_limbs, scalar_as_base},
   traits::{
-    commitment::CommitmentEngineTrait, AbsorbInROTrait, Engine, ROTrait, TranscriptReprTrait,
+    commitment::{CommitmentEngineTrait, CommitmentTrait},
+    AbsorbInROTrait, Engine, ROTrait, TranscriptReprTrait,
   },
   zip_with, Commitment, CommitmentKey, CE,
 };
@@ -199,6 +200,15 @@ impl<E: Engine> R1CSShape<E> {
     (witness, instance)
   }
 
+  /// Returns the number of constraints in this [`R1CSShape`]
+  pub const fn num_cons(&self) -> usize { self.num_cons }
+
+  /// Returns the number of witness variables in this [`R1CSShape`]
+  pub const fn num_vars(&self) -> usize { self.num_vars }
+
+  /// Returns the number of public inputs/outputs in this [`R1CSShape`]
+  pub const fn num_io(&self) -> usize { self.num_io }
+
   /// returned the digest of the `R1CSShape`
   pub fn digest(&self) -> E::Scalar {
     self
@@ -320,7 +330,7 @@ impl<E: Engine> R1CSShape<E> {
     W.E.par_iter().zip_eq(E.into_par_iter()).enumerate().try_for_each(|(i, (we, e))| {
       if *we != e {
         // constraint failed, retrieve constraint name
-        Err(NovaError::UnSatIndex(i))
+        Err(NovaError::UnSatIndex("relaxed_r1cs", i))
       } else {
         Ok(())
       }
@@ -329,8 +339,8 @@ impl<E: Engine> R1CSShape<E> {
     // verify if comm_E and comm_W are commitments to E and W
     let res_comm = {
       let (comm_W, comm_E) =
-        rayon::join(|| CE::<E>::commit(ck, &W.W), || CE::<E>::commit(ck, &W.E));
-      U.comm_W == comm_W && U.comm_E == comm_E
+        rayon::join(|| CE::<E>::try_commit(ck, &W.W), || CE::<E>::try_commit(ck, &W.E));
+      U.comm_W == comm_W? && U.comm_E == comm_E?
     };
 
     if !res_comm {
@@ -345,22 +355,116 @@ impl<E: Engine> R1CSShape<E> {
     ck: &CommitmentKey<E>,
     U: &R1CSInstance<E>,
     W: &R1CSWitness<E>,
+  ) -> Result<(), NovaError> {
+    self.is_sat_detailed(ck, U, W, "r1cs")
+  }
+
+  /// Checks if the R1CS instance is satisfiable given a witness and its shape,
+  /// checking the rows on the rayon thread pool when `parallel` is set and
+  /// one after the other otherwise. Both paths return the same result, and
+  /// [`R1CSShape::is_sat`] picks one with the `parallel-paths` feature.
+  pub fn is_sat_with(
+    &self,
+    ck: &CommitmentKey<E>,
+    U: &R1CSInstance<E>,
+    W: &R1CSWitness<E>,
+    parallel: bool,
+  ) -> Result<(), NovaError> {
+    self.check_sat(ck, U, W, "r1cs", parallel)
+  }
+
+  /// Checks if the R1CS instance is satisfiable given a witness and its shape,
+  /// reporting the first row where `Az * Bz != Cz` as
+  /// `NovaError::UnSatIndex(stage, row)`. The rows are checked in parallel
+  /// with the `parallel-paths` feature, see [`R1CSShape::is_sat_with`].
+  pub fn is_sat_detailed(
+    &self,
+    ck: &CommitmentKey<E>,
+    U: &R1CSInstance<E>,
+    W: &R1CSWitness<E>,
+    stage: &'static str,
+  ) -> Result<(), NovaError> {
+    self.check_sat(ck, U, W, stage, cfg!(feature = "parallel-paths"))
+  }
+
+  /// Checks if the R1CS instance is satisfiable given a witness and its shape,
+  /// reporting the first unsatisfied row as `NovaError::UnSatSymbolic` with
+  /// that row rendered over `names`, the names of the entries of
+  /// `z = (W, 1, X)` as returned by
+  /// [`TestShapeCS::variable_names`](crate::bellpepper::test_shape_cs::TestShapeCS::variable_names)
+  #[cfg(feature = "debug-symbols")]
+  pub fn is_sat_symbolic(
+    &self,
+    ck: &CommitmentKey<E>,
+    U: &R1CSInstance<E>,
+    W: &R1CSWitness<E>,
+    names: &[String],
+  ) -> Result<(), NovaError> {
+    assert_eq!(names.len(), self.num_vars + 1 + self.num_io);
+    match self.check_sat(ck, U, W, "r1cs", false) {
+      Err(NovaError::UnSatIndex(_, row)) => {
+        let z = [W.W.as_slice(), &[E::Scalar::ONE], &U.X].concat();
+        Err(NovaError::UnSatSymbolic { row, constraint: self.render_row(row, &z, names) })
+      },
+      res => res,
+    }
+  }
+
+  /// Renders row `row` as `(Σ a_i·z_i)(Σ b_i·z_i) - Σ c_i·z_i = d`, where `d`
+  /// is the value the left-hand side takes on `z`
+  #[cfg(feature = "debug-symbols")]
+  fn render_row(&self, row: usize, z: &[E::Scalar], names: &[String]) -> String {
+    let render = |M: &SparseMatrix<E::Scalar>| {
+      let row = M.iter_rows().nth(row).expect("row out of range");
+      let terms = M
+        .get_row(row)
+        .map(|(val, col)| {
+          if *val == E::Scalar::ONE {
+            names[*col].clone()
+          } else if *val == -E::Scalar::ONE {
+            format!("-{}", names[*col])
+          } else {
+            format!("{val:?}·{}", names[*col])
+          }
+        })
+        .collect::<Vec<_>>();
+      let value = M.get_row(row).map(|(val, col)| *val * z[*col]).sum::<E::Scalar>();
+      (if terms.is_empty() { "0".to_string() } else { terms.join(" + ") }, value)
+    };
+
+    let ((a, az), (b, bz), (c, cz)) = (render(&self.A), render(&self.B), render(&self.C));
+    format!("({a})({b}) - {c} = {:?}", az * bz - cz)
+  }
+
+  fn check_sat(
+    &self,
+    ck: &CommitmentKey<E>,
+    U: &R1CSInstance<E>,
+    W: &R1CSWitness<E>,
+    stage: &'static str,
+    parallel: bool,
   ) -> Result<(), NovaError> {
     assert_eq!(W.W.len(), self.num_vars);
     assert_eq!(U.X.len(), self.num_io);
 
     // verify if Az * Bz - u*Cz = 0
-    let E = self.compute_E(&W.W, &E::Scalar::ONE, &U.X)?;
-    E.into_par_iter().enumerate().try_for_each(|(i, e)| {
-      if e != E::Scalar::ZERO {
-        Err(NovaError::UnSatIndex(i))
-      } else {
-        Ok(())
-      }
-    })?;
+    let unsat_row = if parallel {
+      let E = self.compute_E(&W.W, &E::Scalar::ONE, &U.X)?;
+      E.into_par_iter().position_first(|e| e != E::Scalar::ZERO)
+    } else {
+      let z = [W.W.as_slice(), &[E::Scalar::ONE], &U.X].concat();
+      let dot = |M: &SparseMatrix<E::Scalar>, row| {
+        M.get_row(row).map(|(val, col)| *val * z[*col]).sum::<E::Scalar>()
+      };
+      let rows = self.A.iter_rows().zip(self.B.iter_rows()).zip(self.C.iter_rows());
+      rows.position(|((a, b), c)| dot(&self.A, a) * dot(&self.B, b) != dot(&self.C, c))
+    };
+    if let Some(row) = unsat_row {
+      return Err(NovaError::UnSatIndex(stage, row));
+    }
 
     // verify if comm_W is a commitment to W
-    if U.comm_W != CE::<E>::commit(ck, &W.W) {
+    if U.comm_W != CE::<E>::try_commit(ck, &W.W)? {
       return Err(NovaError::UnSat);
     }
     Ok(())
@@ -405,7 +509,7 @@ impl<E: Engine> R1CSShape<E> {
         .collect::<Vec<E::Scalar>>()
     });
 
-    let comm_T = CE::<E>::commit(ck, &T);
+    let comm_T = CE::<E>::try_commit(ck, &T)?;
 
     Ok((T, comm_T))
   }
@@ -450,7 +554,34 @@ impl<E: Engine> R1CSShape<E> {
         .collect_into_vec(T)
     });
 
-    Ok(CE::<E>::commit(ck, T))
+    CE::<E>::try_commit(ck, T)
+  }
+
+  /// A method to compute a commitment to the cross-term `T` given two
+  /// Relaxed R1CS instance-witness pairs, where the second one is no longer a
+  /// plain R1CS instance: `T = AZ_1 ∘ BZ_2 + AZ_2 ∘ BZ_1 - u_1 ⋅ CZ_2 - u_2 ⋅ CZ_1`
+  pub fn commit_T_relaxed(
+    &self,
+    ck: &CommitmentKey<E>,
+    U1: &RelaxedR1CSInstance<E>,
+    W1: &RelaxedR1CSWitness<E>,
+    U2: &RelaxedR1CSInstance<E>,
+    W2: &RelaxedR1CSWitness<E>,
+  ) -> Result<(Vec<E::Scalar>, Commitment<E>), NovaError> {
+    let (ABC_Z_1, ABC_Z_2) = rayon::join(
+      || self.multiply_witness(&W1.W, &U1.u, &U1.X),
+      || self.multiply_witness(&W2.W, &U2.u, &U2.X),
+    );
+    let ((AZ_1, BZ_1, CZ_1), (AZ_2, BZ_2, CZ_2)) = (ABC_Z_1?, ABC_Z_2?);
+
+    let T = (0..AZ_1.len())
+      .into_par_iter()
+      .map(|i| AZ_1[i] * BZ_2[i] + AZ_2[i] * BZ_1[i] - U1.u * CZ_2[i] - U2.u * CZ_1[i])
+      .collect::<Vec<E::Scalar>>();
+
+    let comm_T = CE::<E>::try_commit(ck, &T)?;
+
+    Ok((T, comm_T))
   }
 
   /// Pads the `R1CSShape` so that the shape passes `is_regular_shape`
@@ -464,22 +595,9 @@ impl<E: Engine> R1CSShape<E> {
     // equalize the number of variables, constraints, and public IO
     let m = max(max(self.num_vars, self.num_cons), self.num_io).next_power_of_two();
 
-    // check if the number of variables are as expected, then
-    // we simply set the number of constraints to the next power of two
-    if self.num_vars == m {
-      return Self {
-        num_cons: m,
-        num_vars: m,
-        num_io:   self.num_io,
-        A:        self.A.clone(),
-        B:        self.B.clone(),
-        C:        self.C.clone(),
-        digest:   OnceCell::new(),
-      };
-    }
-
-    // otherwise, we need to pad the number of variables and renumber variable
-    // accesses
+    // pad the number of variables and renumber variable accesses, and append
+    // empty rows for the new constraints; when the number of variables is
+    // already `m` only the rows are added
     let num_vars_padded = m;
     let num_cons_padded = m;
 
@@ -539,6 +657,21 @@ impl<E: Engine> R1CSWitness<E> {
 
   /// Commits to the witness using the supplied generators
   pub fn commit(&self, ck: &CommitmentKey<E>) -> Commitment<E> { CE::<E>::commit(ck, &self.W) }
+
+  /// Same as [`R1CSWitness::commit`], returning
+  /// [`NovaError::CommitmentKeyTooSmall`] if the witness is longer than `ck`
+  pub fn try_commit(&self, ck: &CommitmentKey<E>) -> Result<Commitment<E>, NovaError> {
+    CE::<E>::try_commit(ck, &self.W)
+  }
+
+  /// Pads the witness with zeros to the number of variables of `S`, e.g. to
+  /// satisfy [`R1CSShape::pad`] of the shape it was produced for. This leaves
+  /// its commitment unchanged.
+  pub fn pad(&self, S: &R1CSShape<E>) -> Self {
+    let mut W = self.W.clone();
+    W.extend(vec![E::Scalar::ZERO; S.num_vars.saturating_sub(W.len())]);
+    Self { W }
+  }
 }
 
 impl<E: Engine> R1CSInstance<E> {
@@ -567,7 +700,11 @@ impl<E: Engine> AbsorbInROTrait<E> for R1CSInstance<E> {
 
 impl<E: Engine> RelaxedR1CSWitness<E> {
   /// Produces a default `RelaxedR1CSWitness` given an `R1CSShape`
-  pub fn default(S: &R1CSShape<E>) -> Self {
+  pub fn default(S: &R1CSShape<E>) -> Self { Self::default_for_shape(S) }
+
+  /// Produces the all-zero `RelaxedR1CSWitness` sized for `S`, which
+  /// satisfies [`RelaxedR1CSInstance::default_for_shape`]
+  pub fn default_for_shape(S: &R1CSShape<E>) -> Self {
     Self { W: vec![E::Scalar::ZERO; S.num_vars], E: vec![E::Scalar::ZERO; S.num_cons] }
   }
 
@@ -617,6 +754,25 @@ impl<E: Engine> RelaxedR1CSWitness<E> {
     Ok(())
   }
 
+  /// Folds an incoming `RelaxedR1CSWitness` into the current one, using the
+  /// cross-term `T` from [`R1CSShape::commit_T_relaxed`]
+  pub fn fold_relaxed(
+    &self,
+    W2: &RelaxedR1CSWitness<E>,
+    T: &[E::Scalar],
+    r: &E::Scalar,
+  ) -> Result<Self, NovaError> {
+    if self.W.len() != W2.W.len() || self.E.len() != W2.E.len() {
+      return Err(NovaError::InvalidWitnessLength);
+    }
+
+    let r_sq = r.square();
+    let W = zip_with!((self.W.par_iter(), &W2.W), |a, b| *a + *r * *b).collect::<Vec<E::Scalar>>();
+    let E = zip_with!(par_iter, (self.E, T, W2.E), |a, b, c| *a + *r * *b + r_sq * *c)
+      .collect::<Vec<E::Scalar>>();
+    Ok(Self { W, E })
+  }
+
   /// Pads the provided witness to the correct length
   pub fn pad(&self, S: &R1CSShape<E>) -> Self {
     let mut W = self.W.clone();
@@ -632,11 +788,31 @@ impl<E: Engine> RelaxedR1CSWitness<E> {
 impl<E: Engine> RelaxedR1CSInstance<E> {
   /// Produces a default `RelaxedR1CSInstance` given `R1CSGens` and
   /// `R1CSShape`
-  pub fn default(_ck: &CommitmentKey<E>, S: &R1CSShape<E>) -> Self {
+  pub fn default(_ck: &CommitmentKey<E>, S: &R1CSShape<E>) -> Self { Self::default_for_shape(S) }
+
+  /// Produces the all-zero `RelaxedR1CSInstance` for `S`, with `X` sized to
+  /// the shape's public IO. It is satisfied by
+  /// [`RelaxedR1CSWitness::default_for_shape`] and is the natural starting
+  /// point of a fold.
+  pub fn default_for_shape(S: &R1CSShape<E>) -> Self {
     let (comm_W, comm_E) = (Commitment::<E>::default(), Commitment::<E>::default());
     Self { comm_W, comm_E, u: E::Scalar::ZERO, X: vec![E::Scalar::ZERO; S.num_io] }
   }
 
+  /// Checks that the instance is well-formed for `S` without checking its
+  /// satisfiability: `X` must have one entry per public IO of the shape and
+  /// both commitments must lie in the prime-order subgroup. This is cheap
+  /// enough to run between the steps of a long chain.
+  pub fn validate_shape(&self, S: &R1CSShape<E>) -> Result<(), NovaError> {
+    if self.X.len() != S.num_io {
+      return Err(NovaError::InvalidInputLength);
+    }
+    if !self.comm_W.is_in_correct_subgroup() || !self.comm_E.is_in_correct_subgroup() {
+      return Err(NovaError::InvalidCommitmentSubgroup);
+    }
+    Ok(())
+  }
+
   /// Initializes a new `RelaxedR1CSInstance` from an `R1CSInstance`
   pub fn from_r1cs_instance(
     _ck: &CommitmentKey<E>,
@@ -678,6 +854,22 @@ impl<E: Engine> RelaxedR1CSInstance<E> {
     Self { comm_W, comm_E, X, u }
   }
 
+  /// Folds an incoming `RelaxedR1CSInstance` into the current one, with the
+  /// incoming error term weighted by `r^2`
+  pub fn fold_relaxed(
+    &self,
+    U2: &RelaxedR1CSInstance<E>,
+    comm_T: &Commitment<E>,
+    r: &E::Scalar,
+  ) -> Self {
+    let X = zip_with!((self.X.par_iter(), &U2.X), |a, b| *a + *r * *b).collect::<Vec<E::Scalar>>();
+    let comm_W = self.comm_W + U2.comm_W * *r;
+    let comm_E = self.comm_E + (*comm_T + U2.comm_E * *r) * *r;
+    let u = self.u + *r * U2.u;
+
+    Self { comm_W, comm_E, X, u }
+  }
+
   /// Mutably folds an incoming `RelaxedR1CSInstance` into the current one
   pub fn fold_mut(&mut self, U2: &R1CSInstance<E>, comm_T: &Commitment<E>, r: &E::Scalar) {
     let (X2, comm_W_2) = (&U2.X, &U2.comm_W);
@@ -692,6 +884,12 @@ impl<E: Engine> RelaxedR1CSInstance<E> {
   }
 }
 
+impl<E: Engine> TranscriptReprTrait<E::GE> for R1CSInstance<E> {
+  fn to_transcript_bytes(&self) -> Vec<u8> {
+    [self.comm_W.to_transcript_bytes(), self.X.as_slice().to_transcript_bytes()].concat()
+  }
+}
+
 impl<E: Engine> TranscriptReprTrait<E::GE> for RelaxedR1CSInstance<E> {
   fn to_transcript_bytes(&self) -> Vec<u8> {
     [
@@ -813,6 +1011,71 @@ pub(crate) mod tests {
   #[test]
   fn test_pad_tiny_r1cs() { test_pad_tiny_r1cs_with::<Bn256EngineKZG>(); }
 
+  fn test_pad_preserves_sat_with<E: Engine>() {
+    let tiny = tiny_r1cs::<E>(4);
+    // drop the last constraint, so that only the number of constraints needs
+    // padding
+    let truncate = |M: &SparseMatrix<E::Scalar>| {
+      let entries = M.iter().filter(|(row, ..)| *row < 3).collect::<Vec<_>>();
+      SparseMatrix::new(&entries, 3, M.num_cols())
+    };
+    let truncated =
+      R1CSShape::new(3, 4, 2, truncate(&tiny.A), truncate(&tiny.B), truncate(&tiny.C)).unwrap();
+
+    let ck = E::CE::setup(b"ipa", 8);
+    // x = 2, so the satisfying witness is (x^2, x^3, x^3 + x) and y = x^3 + x + 5
+    let vars = [E::Scalar::from(4), E::Scalar::from(8), E::Scalar::from(10)];
+    let X = vec![E::Scalar::from(2), E::Scalar::from(15)];
+
+    for S in [tiny_r1cs::<E>(3), truncated] {
+      assert!(!S.is_regular_shape());
+      let mut W = vars.to_vec();
+      W.resize(S.num_vars, E::Scalar::ZERO);
+      let W = R1CSWitness::new(&S, W).unwrap();
+      let U = R1CSInstance::new(&S, W.commit(&ck), X.clone()).unwrap();
+      S.is_sat(&ck, &U, &W).unwrap();
+
+      let S_padded = S.pad();
+      assert!(S_padded.is_regular_shape());
+      assert_eq!(S_padded.digest(), S.pad().digest());
+
+      // the zero-extended witness satisfies the padded shape
+      let mut W_padded = W.W.clone();
+      W_padded.resize(S_padded.num_vars, E::Scalar::ZERO);
+      let W_padded = R1CSWitness::new(&S_padded, W_padded).unwrap();
+      let U_padded = R1CSInstance::new(&S_padded, W_padded.commit(&ck), X.clone()).unwrap();
+      S_padded.is_sat(&ck, &U_padded, &W_padded).unwrap();
+    }
+  }
+
+  #[test]
+  fn test_pad_preserves_sat() { test_pad_preserves_sat_with::<Bn256EngineIPA>(); }
+
+  fn test_is_sat_parallel_with<E: Engine>() {
+    let S = tiny_r1cs::<E>(4);
+    let ck = E::CE::setup(b"ipa", 8);
+    // x = 2, so the satisfying witness is (x^2, x^3, x^3 + x) and y = x^3 + x + 5
+    let vars = [4, 8, 10, 0].map(E::Scalar::from).to_vec();
+    let X = vec![E::Scalar::from(2), E::Scalar::from(15)];
+
+    let check = |vars: Vec<E::Scalar>| {
+      let W = R1CSWitness::new(&S, vars).unwrap();
+      let U = R1CSInstance::new(&S, W.commit(&ck), X.clone()).unwrap();
+      let serial = S.is_sat_with(&ck, &U, &W, false);
+      assert_eq!(serial, S.is_sat_with(&ck, &U, &W, true));
+      serial
+    };
+    assert_eq!(check(vars.clone()), Ok(()));
+
+    // a wrong x^3 breaks constraints 1 and 2, and both paths report the first
+    let mut wrong = vars;
+    wrong[1] += E::Scalar::ONE;
+    assert_eq!(check(wrong), Err(NovaError::UnSatIndex("r1cs", 1)));
+  }
+
+  #[test]
+  fn test_is_sat_parallel() { test_is_sat_parallel_with::<Bn256EngineIPA>(); }
+
   fn test_random_r1cs_with<E: Engine>() {
     let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
 
@@ -830,4 +1093,97 @@ pub(crate) mod tests {
 
   #[test]
   fn test_random_r1cs() { test_random_r1cs_with::<Bn256EngineIPA>(); }
+
+  fn test_default_for_shape_with<E: Engine>() {
+    let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+    let ck = E::CE::setup(b"ipa", 64);
+
+    let shapes = [tiny_r1cs::<E>(4), R1CSShape::<E>::random(16, 32, 12, 8, &mut rng)];
+    for S in shapes {
+      let U = RelaxedR1CSInstance::default_for_shape(&S);
+      let W = RelaxedR1CSWitness::default_for_shape(&S);
+      assert_eq!(U.X.len(), S.num_io);
+      assert_eq!((W.W.len(), W.E.len()), (S.num_vars, S.num_cons));
+      S.is_sat_relaxed(&ck, &U, &W).unwrap();
+    }
+  }
+
+  #[test]
+  fn test_default_for_shape() {
+    test_default_for_shape_with::<Bn256EngineIPA>();
+    test_default_for_shape_with::<Bn256EngineKZG>();
+  }
+
+  fn test_is_sat_detailed_with<E: Engine>() {
+    let ck = E::CE::setup(b"ipa", 8);
+    let S = tiny_r1cs::<E>(4);
+
+    // x = 2, so the satisfying witness is (x^2, x^3, x^3 + x) and y = x^3 + x + 5
+    let x = E::Scalar::from(2);
+    let mut vars = vec![E::Scalar::from(4), E::Scalar::from(8), E::Scalar::from(10)];
+    vars.resize(S.num_vars, E::Scalar::ZERO);
+    let X = vec![x, E::Scalar::from(15)];
+
+    let W = R1CSWitness::new(&S, vars.clone()).unwrap();
+    let U = R1CSInstance::new(&S, W.commit(&ck), X.clone()).unwrap();
+    S.is_sat_detailed(&ck, &U, &W, "tiny").unwrap();
+
+    // corrupting Z1 breaks rows 1 and 2, and the first one is reported
+    vars[1] += E::Scalar::ONE;
+    let W = R1CSWitness::new(&S, vars).unwrap();
+    let U = R1CSInstance::new(&S, W.commit(&ck), X).unwrap();
+    assert_eq!(S.is_sat_detailed(&ck, &U, &W, "tiny"), Err(NovaError::UnSatIndex("tiny", 1)));
+    assert_eq!(S.is_sat(&ck, &U, &W), Err(NovaError::UnSatIndex("r1cs", 1)));
+  }
+
+  #[test]
+  fn test_is_sat_detailed() {
+    test_is_sat_detailed_with::<Bn256EngineIPA>();
+    test_is_sat_detailed_with::<Bn256EngineKZG>();
+  }
+
+  fn test_validate_shape_with<E: Engine>() {
+    let S = tiny_r1cs::<E>(4);
+    let mut U = RelaxedR1CSInstance::default_for_shape(&S);
+    U.validate_shape(&S).unwrap();
+
+    U.X.push(E::Scalar::ONE);
+    assert_eq!(U.validate_shape(&S), Err(NovaError::InvalidInputLength));
+    U.X.truncate(S.num_io - 1);
+    assert_eq!(U.validate_shape(&S), Err(NovaError::InvalidInputLength));
+  }
+
+  #[test]
+  fn test_validate_shape() { test_validate_shape_with::<Bn256EngineKZG>(); }
+
+  fn test_relaxed_instance_eq_with<E: Engine>() {
+    let S = tiny_r1cs::<E>(4);
+    let ck = E::CE::setup(b"ipa", 8);
+    // x = 2, so the satisfying witness is (x^2, x^3, x^3 + x) and y = x^3 + x + 5
+    let vars = [4, 8, 10, 0].map(E::Scalar::from).to_vec();
+    let W = R1CSWitness::new(&S, vars).unwrap();
+    let U2 =
+      R1CSInstance::new(&S, W.commit(&ck), vec![E::Scalar::from(2), E::Scalar::from(15)]).unwrap();
+    let comm_T = U2.comm_W * E::Scalar::from(3);
+    let r = E::Scalar::from(5);
+
+    // the identity compares equal whichever projective representation it has
+    let U1 = RelaxedR1CSInstance::default_for_shape(&S);
+    assert_eq!(U1.comm_W, U2.comm_W * E::Scalar::ZERO);
+
+    // the same fold computed along two paths compares equal
+    let folded = U1.fold(&U2, &comm_T, &r);
+    let mut folded_mut = U1.clone();
+    folded_mut.fold_mut(&U2, &comm_T, &r);
+    assert_eq!(folded, folded_mut);
+
+    // a fold with another challenge does not
+    assert_ne!(folded, U1.fold(&U2, &comm_T, &(r + E::Scalar::ONE)));
+  }
+
+  #[test]
+  fn test_relaxed_instance_eq() {
+    test_relaxed_instance_eq_with::<Bn256EngineIPA>();
+    test_relaxed_instance_eq_with::<Bn256EngineKZG>();
+  }
 }
@@ -76,6 +76,24 @@ impl<F: PrimeField> SparseMatrix<F> {
     Self { data, indices, indptr, cols }
   }
 
+  /// Construct from COO triplets `(row, col, value)` given in any order, as
+  /// scipy's `coo_matrix` does: entries are sorted by position and the values
+  /// of duplicate positions are summed.
+  pub fn from_coo(entries: &[(usize, usize, F)], rows: usize, cols: usize) -> Self {
+    let mut entries = entries.to_vec();
+    entries.sort_by_key(|(row, col, _)| (*row, *col));
+    entries.dedup_by(|(row, col, val), (prev_row, prev_col, prev_val)| {
+      let duplicate = (row, col) == (prev_row, prev_col);
+      if duplicate {
+        *prev_val += *val;
+      }
+      duplicate
+    });
+    assert!(entries.iter().all(|(row, col, _)| *row < rows && *col < cols), "entry out of range");
+
+    Self::new(&entries, rows, cols)
+  }
+
   /// Samples a new random matrix of size `rows` x `cols` with `num_entries`
   /// non-zero entries.
   pub fn random<R: RngCore + CryptoRng>(
@@ -215,8 +233,10 @@ impl<F: PrimeField> SparseMatrix<F> {
     Iter { matrix: self, row, i: 0, nnz: *self.indptr.last().unwrap() }
   }
 
+  /// number of rows
   pub fn num_rows(&self) -> usize { self.indptr.len() - 1 }
 
+  /// number of columns
   pub fn num_cols(&self) -> usize { self.cols }
 }
 
@@ -256,6 +276,56 @@ impl<F: PrimeField> Iterator for Iter<'_, F> {
   }
 }
 
+#[cfg(test)]
+mod tests {
+  use ff::Field;
+
+  use super::*;
+  use crate::provider::pasta::pallas::Scalar as Fr;
+
+  // multiply by the dense representation of `matrix`, row by row
+  fn dense_multiply(matrix: &SparseMatrix<Fr>, vector: &[Fr]) -> Vec<Fr> {
+    let mut dense = vec![vec![Fr::ZERO; matrix.num_cols()]; matrix.num_rows()];
+    for (row, col, val) in matrix.iter() {
+      dense[row][col] = val;
+    }
+    dense.iter().map(|row| row.iter().zip_eq(vector).map(|(a, b)| *a * b).sum()).collect()
+  }
+
+  #[test]
+  fn test_from_coo() {
+    // unsorted, with a duplicate position whose values are summed
+    let entries =
+      [(2, 0, Fr::from(4)), (0, 2, Fr::from(7)), (1, 2, Fr::from(3)), (0, 1, Fr::from(2))];
+    let matrix = SparseMatrix::from_coo(&[&entries[..], &[(0, 2, Fr::from(1))]].concat(), 3, 3);
+
+    assert_eq!(matrix.indptr, vec![0, 2, 3, 4]);
+    assert_eq!(matrix.indices, vec![1, 2, 2, 0]);
+    assert_eq!(matrix.data, vec![Fr::from(2), Fr::from(8), Fr::from(3), Fr::from(4)]);
+  }
+
+  #[test]
+  fn test_multiply_dense_reference() {
+    let entries = [
+      (0, 1, Fr::from(2)),
+      (0, 3, Fr::from(7)),
+      (1, 0, Fr::from(3)),
+      (1, 4, Fr::from(5)),
+      (3, 2, Fr::from(11)),
+    ];
+    let matrix = SparseMatrix::from_coo(&entries, 4, 5);
+    let z = (1..=5).map(Fr::from).collect::<Vec<_>>();
+
+    let expected = dense_multiply(&matrix, &z);
+    assert_eq!(expected, [32, 28, 0, 33].map(Fr::from).to_vec());
+    assert_eq!(matrix.multiply_vec(&z), expected);
+
+    // z = (W, u, X) with the witness in the first two columns and u in the third
+    let (W, u, X) = (&z[..2], &z[2], &z[3..]);
+    assert_eq!(matrix.multiply_witness(W, u, X), expected);
+  }
+}
+
 // #[cfg(test)]
 // mod tests {
 //     #[cfg(not(target_arch = "wasm32"))]
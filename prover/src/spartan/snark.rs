@@ -500,3 +500,45 @@ pub(in crate::spartan) fn batch_eval_verify<E: Engine>(
 
   Ok(u_joint)
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    provider::{ipa_pc, Bn256EngineIPA, GrumpkinEngine},
+    r1cs::{commitment_key, tests::tiny_r1cs, R1CSInstance, R1CSWitness},
+  };
+
+  fn test_relaxed_r1cs_snark_with<E: Engine, S: RelaxedR1CSSNARKTrait<E>>() {
+    let shape = tiny_r1cs::<E>(4);
+    let ck = Arc::new(commitment_key(&shape, &*S::ck_floor()));
+    let (pk, vk) = S::setup(ck.clone(), &shape).unwrap();
+
+    // x = 2, so the satisfying witness is (x^2, x^3, x^3 + x) and y = x^3 + x + 5
+    let vars = vec![E::Scalar::from(4), E::Scalar::from(8), E::Scalar::from(10), E::Scalar::ZERO];
+    let W = R1CSWitness::new(&shape, vars).unwrap();
+    let U = R1CSInstance::new(&shape, W.commit(&ck), vec![E::Scalar::from(2), E::Scalar::from(15)])
+      .unwrap();
+    let W = RelaxedR1CSWitness::from_r1cs_witness(&shape, W);
+    let mut U = RelaxedR1CSInstance::from_r1cs_instance(&ck, &shape, U);
+
+    let snark = S::prove(&ck, &pk, &shape, &U, &W).unwrap();
+    snark.verify(&vk, &U).unwrap();
+
+    // the proof does not verify against a different instance
+    U.X[1] += E::Scalar::ONE;
+    assert!(snark.verify(&vk, &U).is_err());
+  }
+
+  #[test]
+  fn test_relaxed_r1cs_snark() {
+    test_relaxed_r1cs_snark_with::<
+      Bn256EngineIPA,
+      RelaxedR1CSSNARK<Bn256EngineIPA, ipa_pc::EvaluationEngine<Bn256EngineIPA>>,
+    >();
+    test_relaxed_r1cs_snark_with::<
+      GrumpkinEngine,
+      RelaxedR1CSSNARK<GrumpkinEngine, ipa_pc::EvaluationEngine<GrumpkinEngine>>,
+    >();
+  }
+}
@@ -6,13 +6,13 @@ use thiserror::Error;
 use crate::errors::NovaError;
 
 // TODO: These are in a dumb spot imo, they should be defined at the crate root
-// and cover everything. Also, we should use `transparent`
+// and cover everything.
 
 /// Errors returned by Nova
 #[derive(Debug, Eq, PartialEq, Error)]
 pub enum SuperNovaError {
   /// Nova error
-  #[error("NovaError")]
+  #[error(transparent)]
   NovaError(#[from] NovaError),
   /// missing commitment key
   #[error("MissingCK")]
@@ -20,4 +20,19 @@ pub enum SuperNovaError {
   /// Extended error for supernova
   #[error("UnSatIndex")]
   UnSatIndex(&'static str, usize),
+  /// A folded instance is unsatisfied; `reason` names the failing instance
+  #[error(
+    "UnSatIndexDetailed: {reason} of circuit {circuit_index} unsatisfied at constraint \
+     {constraint} after step {step}"
+  )]
+  UnSatIndexDetailed {
+    /// index of the augmented circuit whose instance failed
+    circuit_index: usize,
+    /// number of steps proven when verification failed
+    step:          usize,
+    /// index of the first unsatisfied constraint
+    constraint:    usize,
+    /// the failing instance, one of `r_primary`, `r_secondary`, `l_secondary`
+    reason:        String,
+  },
 }
@@ -28,6 +28,7 @@ use crate::{
   traits::{
     commitment::{CommitmentEngineTrait, CommitmentTrait},
     AbsorbInROTrait, CurveCycleEquipped, Dual, Engine, ROConstants, ROConstantsCircuit, ROTrait,
+    StepIndex,
   },
   Commitment, CommitmentKey, R1CSWithArity,
 };
@@ -817,7 +818,7 @@ where E1: CurveCycleEquipped
     let inputs_primary: SuperNovaAugmentedCircuitInputs<'_, Dual<E1>> =
       SuperNovaAugmentedCircuitInputs::new(
         scalar_as_base::<E1>(self.pp_digest),
-        E1::Scalar::from(self.i as u64),
+        StepIndex(self.i as u64).to_field(),
         &self.z0_primary,
         Some(&self.zi_primary),
         Some(&r_U_secondary_i),
@@ -885,7 +886,7 @@ where E1: CurveCycleEquipped
     let inputs_secondary: SuperNovaAugmentedCircuitInputs<'_, E1> =
       SuperNovaAugmentedCircuitInputs::new(
         self.pp_digest,
-        <Dual<E1> as Engine>::Scalar::from(self.i as u64),
+        StepIndex(self.i as u64).to_field(),
         &self.z0_secondary,
         Some(&self.zi_secondary),
         Some(&r_U_primary_i),
@@ -1015,7 +1016,7 @@ where E1: CurveCycleEquipped
 
       let mut hasher = <Dual<E1> as Engine>::RO::new(pp.ro_consts_secondary.clone(), num_absorbs);
       hasher.absorb(self.pp_digest);
-      hasher.absorb(E1::Scalar::from(self.i as u64));
+      AbsorbInROTrait::<Dual<E1>>::absorb_in_ro(&StepIndex(self.i as u64), &mut hasher);
       hasher.absorb(self.program_counter);
 
       for e in z0_primary {
@@ -1026,7 +1027,7 @@ where E1: CurveCycleEquipped
       }
 
       self.r_U_secondary.absorb_in_ro(&mut hasher);
-      hasher.squeeze(NUM_HASH_BITS)
+      hasher.squeeze(NUM_HASH_BITS)?
     };
 
     let hash_secondary = {
@@ -1038,7 +1039,7 @@ where E1: CurveCycleEquipped
       );
       let mut hasher = <E1 as Engine>::RO::new(pp.ro_consts_primary.clone(), num_absorbs);
       hasher.absorb(scalar_as_base::<E1>(self.pp_digest));
-      hasher.absorb(<Dual<E1> as Engine>::Scalar::from(self.i as u64));
+      AbsorbInROTrait::<E1>::absorb_in_ro(&StepIndex(self.i as u64), &mut hasher);
 
       for e in z0_secondary {
         hasher.absorb(*e);
@@ -1052,7 +1053,7 @@ where E1: CurveCycleEquipped
           .unwrap_or(&RelaxedR1CSInstance::default(&*pp.ck_primary, &pp[i].r1cs_shape))
           .absorb_in_ro(&mut hasher);
       });
-      hasher.squeeze(NUM_HASH_BITS)
+      hasher.squeeze(NUM_HASH_BITS)?
     };
 
     if hash_primary != self.l_u_secondary.X[0] {
@@ -1076,7 +1077,7 @@ where E1: CurveCycleEquipped
         self.r_U_primary.par_iter().zip_eq(self.r_W_primary.par_iter()).enumerate().try_for_each(
           |(i, (u, w))| {
             if let (Some(u), Some(w)) = (u, w) {
-              pp[i].r1cs_shape.is_sat_relaxed(&pp.ck_primary, u, w)?
+              pp[i].r1cs_shape.is_sat_relaxed(&pp.ck_primary, u, w).map_err(|e| (i, e))?
             }
             Ok(())
           },
@@ -1102,18 +1103,19 @@ where E1: CurveCycleEquipped
       },
     );
 
-    res_r_primary.map_err(|err| match err {
-      NovaError::UnSatIndex(i) => SuperNovaError::UnSatIndex("r_primary", i),
-      e => SuperNovaError::NovaError(e),
-    })?;
-    res_r_secondary.map_err(|err| match err {
-      NovaError::UnSatIndex(i) => SuperNovaError::UnSatIndex("r_secondary", i),
-      e => SuperNovaError::NovaError(e),
-    })?;
-    res_l_secondary.map_err(|err| match err {
-      NovaError::UnSatIndex(i) => SuperNovaError::UnSatIndex("l_secondary", i),
+    // the secondary instances were last folded alongside `proven_circuit_index`
+    let unsat = |reason: &str, circuit_index: usize, err: NovaError| match err {
+      NovaError::UnSatIndex(_, constraint) => SuperNovaError::UnSatIndexDetailed {
+        circuit_index,
+        step: self.i,
+        constraint,
+        reason: reason.to_string(),
+      },
       e => SuperNovaError::NovaError(e),
-    })?;
+    };
+    res_r_primary.map_err(|(i, err)| unsat("r_primary", i, err))?;
+    res_r_secondary.map_err(|err| unsat("r_secondary", self.proven_circuit_index, err))?;
+    res_l_secondary.map_err(|err| unsat("l_secondary", self.proven_circuit_index, err))?;
 
     Ok((self.zi_primary.clone(), self.zi_secondary.clone()))
   }
@@ -10,7 +10,7 @@ use crate::{
   scalar_as_base,
   traits::{
     snark::{BatchedRelaxedR1CSSNARKTrait, RelaxedR1CSSNARKTrait},
-    AbsorbInROTrait, CurveCycleEquipped, Dual, Engine, ROTrait,
+    AbsorbInROTrait, CurveCycleEquipped, Dual, Engine, ROTrait, StepIndex,
   },
   RelaxedR1CSInstance, NIFS,
 };
@@ -203,7 +203,7 @@ where
         <Dual<E1> as Engine>::RO::new(pp.ro_consts_secondary.clone(), num_field_primary_ro);
 
       hasher.absorb(pp.digest());
-      hasher.absorb(E1::Scalar::from(self.num_steps as u64));
+      AbsorbInROTrait::<Dual<E1>>::absorb_in_ro(&StepIndex(self.num_steps as u64), &mut hasher);
       hasher.absorb(self.program_counter);
 
       for e in z0_primary {
@@ -220,7 +220,7 @@ where
         <E1 as Engine>::RO::new(pp.ro_consts_primary.clone(), num_field_secondary_ro);
 
       hasher2.absorb(scalar_as_base::<E1>(pp.digest()));
-      hasher2.absorb(<Dual<E1> as Engine>::Scalar::from(self.num_steps as u64));
+      AbsorbInROTrait::<E1>::absorb_in_ro(&StepIndex(self.num_steps as u64), &mut hasher2);
 
       for e in z0_secondary {
         hasher2.absorb(*e);
@@ -234,7 +234,7 @@ where
         U.absorb_in_ro(&mut hasher2);
       });
 
-      (hasher.squeeze(NUM_HASH_BITS), hasher2.squeeze(NUM_HASH_BITS))
+      (hasher.squeeze(NUM_HASH_BITS)?, hasher2.squeeze(NUM_HASH_BITS)?)
     };
 
     // Compare the computed hashes with the public IO of the last invocation of
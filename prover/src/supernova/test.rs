@@ -214,7 +214,9 @@ fn print_constraints_name_on_error_index<
   E1: CurveCycleEquipped,
 {
   match err {
-    SuperNovaError::UnSatIndex(msg, index) if *msg == "r_primary" => {
+    SuperNovaError::UnSatIndexDetailed { constraint: index, reason: msg, .. }
+      if msg == "r_primary" =>
+    {
       let circuit_primary: SuperNovaAugmentedCircuit<'_, Dual<E1>, C1> =
         SuperNovaAugmentedCircuit::new(
           &pp.augmented_circuit_params_primary,
@@ -229,7 +231,9 @@ fn print_constraints_name_on_error_index<
         .get(*index)
         .tap_some(|constraint| debug!("{msg} failed at constraint {}", constraint.3));
     },
-    SuperNovaError::UnSatIndex(msg, index) if *msg == "r_secondary" || *msg == "l_secondary" => {
+    SuperNovaError::UnSatIndexDetailed { constraint: index, reason: msg, .. }
+      if msg == "r_secondary" || msg == "l_secondary" =>
+    {
       let circuit_secondary: SuperNovaAugmentedCircuit<'_, E1, C2> = SuperNovaAugmentedCircuit::new(
         &pp.augmented_circuit_params_secondary,
         None,
@@ -404,6 +408,118 @@ fn test_trivial_nivc() {
   test_trivial_nivc_with::<Bn256EngineIPA>();
 }
 
+fn test_unsat_index_detailed_with<E1>()
+where E1: CurveCycleEquipped {
+  let test_rom = TestROM::<E1>::new(vec![OPCODE_1, OPCODE_0]);
+  let pp = PublicParams::setup(&test_rom, &*default_ck_hint(), &*default_ck_hint());
+
+  let mut z0_primary = vec![<E1 as Engine>::Scalar::ONE, <E1 as Engine>::Scalar::ZERO];
+  z0_primary.extend(test_rom.rom.iter().map(|opcode| <E1 as Engine>::Scalar::from(*opcode as u64)));
+  let z0_secondary = vec![<Dual<E1> as Engine>::Scalar::ONE];
+
+  let circuit_secondary = test_rom.secondary_circuit();
+  let mut recursive_snark = RecursiveSNARK::new(
+    &pp,
+    &test_rom,
+    &test_rom.primary_circuit(test_rom.rom[0]),
+    &circuit_secondary,
+    &z0_primary,
+    &z0_secondary,
+  )
+  .unwrap();
+  for &op_code in test_rom.rom.iter() {
+    let circuit_primary = test_rom.primary_circuit(op_code);
+    recursive_snark.prove_step(&pp, &circuit_primary, &circuit_secondary).unwrap();
+  }
+  recursive_snark.verify(&pp, &z0_primary, &z0_secondary).unwrap();
+
+  // break the first constraint of the running secondary instance
+  recursive_snark.r_W_secondary.E[0] += <Dual<E1> as Engine>::Scalar::ONE;
+
+  let err = recursive_snark.verify(&pp, &z0_primary, &z0_secondary).unwrap_err();
+  let SuperNovaError::UnSatIndexDetailed { circuit_index, step, constraint, ref reason } = err
+  else {
+    panic!("unexpected error: {err:?}");
+  };
+  assert_eq!(circuit_index, OPCODE_0);
+  assert_eq!(step, recursive_snark.i);
+  assert_eq!(constraint, 0);
+  assert_eq!(reason, "r_secondary");
+  assert_eq!(
+    err.to_string(),
+    format!(
+      "UnSatIndexDetailed: r_secondary of circuit {OPCODE_0} unsatisfied at constraint 0 after \
+       step {step}"
+    )
+  );
+}
+
+#[test]
+fn test_unsat_index_detailed() { test_unsat_index_detailed_with::<Bn256EngineIPA>(); }
+
+#[test]
+fn test_nova_error_display() {
+  let err = SuperNovaError::from(NovaError::ProofVerifyError);
+  assert_eq!(format!("{err}"), NovaError::ProofVerifyError.to_string());
+  assert!(format!("{err}").contains("ProofVerifyError"));
+}
+
+fn run_nivc_rom<E1>(rom: Vec<usize>) -> Vec<<E1 as Engine>::Scalar>
+where E1: CurveCycleEquipped {
+  let test_rom = TestROM::<E1>::new(rom);
+  let pp = PublicParams::setup(&test_rom, &*default_ck_hint(), &*default_ck_hint());
+
+  let mut z0_primary = vec![<E1 as Engine>::Scalar::ONE, <E1 as Engine>::Scalar::ZERO];
+  z0_primary.extend(test_rom.rom.iter().map(|opcode| <E1 as Engine>::Scalar::from(*opcode as u64)));
+  let z0_secondary = vec![<Dual<E1> as Engine>::Scalar::ONE];
+
+  let circuit_secondary = test_rom.secondary_circuit();
+  let mut recursive_snark = RecursiveSNARK::new(
+    &pp,
+    &test_rom,
+    &test_rom.primary_circuit(test_rom.initial_circuit_index()),
+    &circuit_secondary,
+    &z0_primary,
+    &z0_secondary,
+  )
+  .unwrap();
+  for (i, &op_code) in test_rom.rom.iter().enumerate() {
+    let circuit_primary = test_rom.primary_circuit(op_code);
+    recursive_snark.prove_step(&pp, &circuit_primary, &circuit_secondary).unwrap();
+    // each step outputs the program counter selecting the next circuit
+    let pc_next = test_rom.rom.get(i + 1).map_or(-<E1 as Engine>::Scalar::ONE, |op_code| {
+      <E1 as Engine>::Scalar::from(*op_code as u64)
+    });
+    assert_eq!(recursive_snark.program_counter, pc_next);
+  }
+  let (zn_primary, _) = recursive_snark.verify(&pp, &z0_primary, &z0_secondary).unwrap();
+  zn_primary
+}
+
+fn test_nivc_alternating_pc_with<E1>()
+where E1: CurveCycleEquipped {
+  let expected = |rom: &[usize]| {
+    rom.iter().fold(<E1 as Engine>::Scalar::ONE, |x, &op_code| match op_code {
+      OPCODE_0 => x * x * x + x + <E1 as Engine>::Scalar::from(5u64),
+      OPCODE_1 => x * x + x + <E1 as Engine>::Scalar::from(5u64),
+      _ => unreachable!(),
+    })
+  };
+
+  let rom = vec![OPCODE_0, OPCODE_1, OPCODE_0, OPCODE_1];
+  let swapped = vec![OPCODE_1, OPCODE_0, OPCODE_1, OPCODE_0];
+  let zn = run_nivc_rom::<E1>(rom.clone());
+  let zn_swapped = run_nivc_rom::<E1>(swapped.clone());
+
+  assert_eq!(zn[0], expected(&rom));
+  assert_eq!(zn[1], <E1 as Engine>::Scalar::from(rom.len() as u64));
+  assert_eq!(zn_swapped[0], expected(&swapped));
+  assert_ne!(zn[0], zn_swapped[0]);
+}
+
+#[test]
+fn test_nivc_alternating_pc() { test_nivc_alternating_pc_with::<Bn256EngineIPA>(); }
+
 // In the following we use 1 to refer to the primary, and 2 to refer to the
 // secondary circuit
 fn test_recursive_circuit_with<E1>(
@@ -0,0 +1,166 @@
+//! This module provides ready-made step circuits for examples and downstream
+//! tests of incremental computations.
+use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+use ff::{Field, PrimeField};
+
+use crate::{
+  errors::NovaError,
+  supernova::StepCircuit,
+  traits::{CurveCycleEquipped, Dual, Engine},
+  PublicParams, RecursiveSNARK,
+};
+
+/// A step circuit of configurable arity that returns its input unchanged.
+///
+/// It synthesizes no constraint of its own, so folding it measures the
+/// overhead of the IVC machinery alone, see [`fold_n`].
+#[derive(Clone, Debug)]
+pub struct TrivialCircuit {
+  arity: usize,
+}
+
+impl TrivialCircuit {
+  /// Create a `TrivialCircuit` taking and returning `arity` elements
+  pub const fn new(arity: usize) -> Self { Self { arity } }
+}
+
+impl Default for TrivialCircuit {
+  fn default() -> Self { Self::new(1) }
+}
+
+/// The zero-cost circuit [`fold_n`] folds: a [`TrivialCircuit`] synthesizes
+/// no constraint, so folding it costs only the IVC machinery.
+pub type ZeroCostCircuit = TrivialCircuit;
+
+impl<F: PrimeField> StepCircuit<F> for TrivialCircuit {
+  fn arity(&self) -> usize { self.arity }
+
+  fn circuit_index(&self) -> usize { 0 }
+
+  fn synthesize<CS: ConstraintSystem<F>>(
+    &self,
+    _cs: &mut CS,
+    pc: Option<&AllocatedNum<F>>,
+    z: &[AllocatedNum<F>],
+  ) -> Result<(Option<AllocatedNum<F>>, Vec<AllocatedNum<F>>), SynthesisError> {
+    Ok((pc.cloned(), z.to_vec()))
+  }
+}
+
+/// Fold `num_steps` steps of [`TrivialCircuit`] on both sides, starting from
+/// `z0 = (0, 1, ..)`. `pp` must have been set up for `TrivialCircuit`s, of any
+/// arity. As the circuits cost nothing, this times the folding machinery alone.
+pub fn fold_n<E1: CurveCycleEquipped>(
+  pp: &PublicParams<E1>,
+  num_steps: usize,
+) -> Result<RecursiveSNARK<E1>, NovaError> {
+  let c_primary = TrivialCircuit::new(pp.F_arity_primary);
+  let c_secondary = TrivialCircuit::new(pp.F_arity_secondary);
+  let z0_primary = (0..pp.F_arity_primary as u64).map(E1::Scalar::from).collect::<Vec<_>>();
+  let z0_secondary =
+    (0..pp.F_arity_secondary as u64).map(<Dual<E1> as Engine>::Scalar::from).collect::<Vec<_>>();
+
+  let mut recursive_snark =
+    RecursiveSNARK::new(pp, &c_primary, &c_secondary, &z0_primary, &z0_secondary)?;
+  for _ in 0..num_steps {
+    recursive_snark.prove_step(pp, &c_primary, &c_secondary)?;
+  }
+  Ok(recursive_snark)
+}
+
+/// A step circuit of arity 1 that adds a constant `increment` to `z[0]`
+#[derive(Clone, Debug)]
+pub struct CounterCircuit<F> {
+  increment: F,
+}
+
+impl<F: PrimeField> CounterCircuit<F> {
+  /// Create a `CounterCircuit` adding `increment` at each step
+  pub const fn new(increment: F) -> Self { Self { increment } }
+}
+
+impl<F: PrimeField> Default for CounterCircuit<F> {
+  fn default() -> Self { Self::new(F::ONE) }
+}
+
+impl<F: PrimeField> StepCircuit<F> for CounterCircuit<F> {
+  fn arity(&self) -> usize { 1 }
+
+  fn circuit_index(&self) -> usize { 0 }
+
+  fn synthesize<CS: ConstraintSystem<F>>(
+    &self,
+    cs: &mut CS,
+    pc: Option<&AllocatedNum<F>>,
+    z: &[AllocatedNum<F>],
+  ) -> Result<(Option<AllocatedNum<F>>, Vec<AllocatedNum<F>>), SynthesisError> {
+    let z_next = AllocatedNum::alloc(cs.namespace(|| "z_next"), || {
+      z[0].get_value().map(|v| v + self.increment).ok_or(SynthesisError::AssignmentMissing)
+    })?;
+
+    cs.enforce(
+      || "z_next = z + increment",
+      |lc| lc + z[0].get_variable() + (self.increment, CS::one()),
+      |lc| lc + CS::one(),
+      |lc| lc + z_next.get_variable(),
+    );
+
+    Ok((pc.cloned(), vec![z_next]))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{provider::Bn256EngineIPA, traits::snark::default_ck_hint};
+
+  fn test_counter_circuit_with<E1: CurveCycleEquipped>() {
+    let increment = E1::Scalar::from(3u64);
+    let c_primary = CounterCircuit::new(increment);
+    let c_secondary = TrivialCircuit::default();
+    let pp =
+      PublicParams::<E1>::setup(&c_primary, &c_secondary, &*default_ck_hint(), &*default_ck_hint())
+        .unwrap();
+
+    let z0_primary = vec![E1::Scalar::ZERO];
+    let z0_secondary = vec![<Dual<E1> as Engine>::Scalar::ZERO];
+    let mut recursive_snark =
+      RecursiveSNARK::new(&pp, &c_primary, &c_secondary, &z0_primary, &z0_secondary).unwrap();
+
+    let num_steps = 5;
+    for _ in 0..num_steps {
+      recursive_snark.prove_step(&pp, &c_primary, &c_secondary).unwrap();
+    }
+
+    let (zn_primary, zn_secondary) =
+      recursive_snark.verify(&pp, num_steps, &z0_primary, &z0_secondary).unwrap();
+    assert_eq!(zn_primary, vec![increment * E1::Scalar::from(num_steps as u64)]);
+    assert_eq!(zn_secondary, z0_secondary);
+  }
+
+  #[test]
+  fn test_counter_circuit() { test_counter_circuit_with::<Bn256EngineIPA>(); }
+
+  fn test_fold_n_with<E1: CurveCycleEquipped>() {
+    let c_primary = ZeroCostCircuit::new(3);
+    let c_secondary = ZeroCostCircuit::default();
+    let pp =
+      PublicParams::<E1>::setup(&c_primary, &c_secondary, &*default_ck_hint(), &*default_ck_hint())
+        .unwrap();
+
+    let num_steps = 4;
+    let recursive_snark = fold_n(&pp, num_steps).unwrap();
+    assert_eq!(recursive_snark.num_steps(), num_steps);
+
+    // z is left unchanged
+    let z0_primary = [0, 1, 2].map(E1::Scalar::from).to_vec();
+    let z0_secondary = vec![<Dual<E1> as Engine>::Scalar::ZERO];
+    let (zn_primary, zn_secondary) =
+      recursive_snark.verify(&pp, num_steps, &z0_primary, &z0_secondary).unwrap();
+    assert_eq!(zn_primary, z0_primary);
+    assert_eq!(zn_secondary, z0_secondary);
+  }
+
+  #[test]
+  fn test_fold_n() { test_fold_n_with::<Bn256EngineIPA>(); }
+}
@@ -6,6 +6,7 @@ use core::{
   ops::{Add, Mul, MulAssign},
 };
 
+use ff::Field;
 use serde::{Deserialize, Serialize};
 
 use crate::{
@@ -53,6 +54,11 @@ pub trait CommitmentTrait<E: Engine>:
 
   /// Decompresses a compressed commitment into a commitment
   fn decompress(c: &Self::CompressedCommitment) -> Result<Self, NovaError>;
+
+  /// Returns true if the commitment lies in the prime-order subgroup, which
+  /// always holds when `E::COFACTOR` is 1. Commitments on a curve with a
+  /// larger cofactor must override this, as they are rejected otherwise.
+  fn is_in_correct_subgroup(&self) -> bool { E::COFACTOR == 1 }
 }
 
 /// A trait that helps determine the length of a structure.
@@ -82,6 +88,90 @@ pub trait CommitmentEngineTrait<E: Engine>: Clone + Send + Sync {
   /// Samples a new commitment key of a specified size
   fn setup(label: &'static [u8], n: usize) -> Self::CommitmentKey;
 
-  /// Commits to the provided vector using the provided generators
+  /// Returns a key made of the first `n` generators of `ck`, which commits to
+  /// vectors of length at most `n` exactly as `ck` does, or
+  /// [`NovaError::CommitmentKeyTooSmall`] if `ck` has fewer than `n`
+  fn trim(ck: &Self::CommitmentKey, n: usize) -> Result<Self::CommitmentKey, NovaError>;
+
+  /// Commits to the provided vector using the provided generators.
+  /// Panics if `v` is longer than `ck`, see [`Self::try_commit`].
   fn commit(ck: &Self::CommitmentKey, v: &[E::Scalar]) -> Self::Commitment;
+
+  /// Commits to the provided vector using the provided generators, returning
+  /// [`NovaError::CommitmentKeyTooSmall`] if `v` is longer than `ck`
+  fn try_commit(ck: &Self::CommitmentKey, v: &[E::Scalar]) -> Result<Self::Commitment, NovaError> {
+    let have = ck.length();
+    if v.len() > have {
+      return Err(NovaError::CommitmentKeyTooSmall { needed: v.len(), have });
+    }
+    Ok(Self::commit(ck, v))
+  }
+
+  /// Commits to `v` with the blinding factor `r` times a dedicated blinding
+  /// generator, which hides `v` when `r` is sampled uniformly at random.
+  /// Panics if `v` is longer than `ck`, see [`Self::try_commit_blinded`].
+  fn commit_blinded(ck: &Self::CommitmentKey, v: &[E::Scalar], r: E::Scalar) -> Self::Commitment;
+
+  /// Same as [`Self::commit_blinded`], returning
+  /// [`NovaError::CommitmentKeyTooSmall`] if `v` is longer than `ck`
+  fn try_commit_blinded(
+    ck: &Self::CommitmentKey,
+    v: &[E::Scalar],
+    r: E::Scalar,
+  ) -> Result<Self::Commitment, NovaError> {
+    let have = ck.length();
+    if v.len() > have {
+      return Err(NovaError::CommitmentKeyTooSmall { needed: v.len(), have });
+    }
+    Ok(Self::commit_blinded(ck, v, r))
+  }
+
+  /// Checks that `comm` is the blinded commitment to `v` with blinding factor
+  /// `r`, returning [`NovaError::InvalidCommitmentOpening`] otherwise, or
+  /// [`NovaError::CommitmentKeyTooSmall`] if `v` is longer than `ck`
+  fn verify_open(
+    ck: &Self::CommitmentKey,
+    comm: &Self::Commitment,
+    v: &[E::Scalar],
+    r: E::Scalar,
+  ) -> Result<(), NovaError> {
+    if Self::try_commit_blinded(ck, v, r)? == *comm {
+      Ok(())
+    } else {
+      Err(NovaError::InvalidCommitmentOpening)
+    }
+  }
+
+  /// Commits to the scalars produced by `v`, with the same result as
+  /// [`Self::commit`] on the collected vector. Engines may override this to
+  /// avoid materializing `v` in full.
+  fn commit_iter(
+    ck: &Self::CommitmentKey,
+    v: impl ExactSizeIterator<Item = E::Scalar>,
+  ) -> Self::Commitment {
+    Self::commit(ck, &v.collect::<Vec<_>>())
+  }
+
+  /// Commits to the vector that holds `values[i]` at position `indices[i]` and
+  /// zero elsewhere, with the same result as [`Self::commit`] on that vector.
+  /// Values sharing an index are summed. Engines may override this to run an
+  /// MSM over the given positions only. Panics if `indices` and `values`
+  /// differ in length or an index is out of the range of `ck`.
+  fn commit_sparse(
+    ck: &Self::CommitmentKey,
+    indices: &[usize],
+    values: &[E::Scalar],
+  ) -> Self::Commitment {
+    assert_eq!(indices.len(), values.len());
+    let mut v = vec![E::Scalar::ZERO; indices.iter().max().map_or(0, |i| i + 1)];
+    for (i, value) in indices.iter().zip(values) {
+      v[*i] += *value;
+    }
+    Self::commit(ck, &v)
+  }
+
+  /// Commits to each of the provided vectors using the same generators
+  fn batch_commit(ck: &Self::CommitmentKey, vs: &[&[E::Scalar]]) -> Vec<Self::Commitment> {
+    vs.iter().map(|v| Self::commit(ck, v)).collect()
+  }
 }
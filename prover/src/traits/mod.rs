@@ -1,10 +1,12 @@
 //! This module defines various traits required by the users of the library to
 //! implement.
-use core::fmt::Debug;
+use core::{any::TypeId, fmt::Debug};
 
 use bellpepper_core::{boolean::AllocatedBit, num::AllocatedNum, ConstraintSystem, SynthesisError};
-use ff::{PrimeField, PrimeFieldBits};
-use num_bigint::BigInt;
+use ff::{Field, PrimeField, PrimeFieldBits};
+use num_bigint::{BigInt, BigUint};
+use rand_chacha::ChaCha20Rng;
+use rand_core::SeedableRng;
 use serde::{Deserialize, Serialize};
 
 use crate::errors::NovaError;
@@ -25,6 +27,12 @@ pub trait Group: Clone + Copy + Debug + Send + Sync + Sized + Eq + PartialEq {
   /// Returns A, B, the order of the group, the size of the base field as big
   /// integers
   fn group_params() -> (Self::Base, Self::Base, BigInt, BigInt);
+
+  /// Deterministically maps `bytes` to a group element with the curve's
+  /// standard hash-to-curve map (simplified SWU, or Shallue-van de Woestijne
+  /// where the curve does not admit it), so that outputs are uniformly
+  /// distributed with unknown discrete logarithms
+  fn hash_to_group(bytes: &[u8]) -> Self;
 }
 
 /// A collection of engines that are required by the library
@@ -57,6 +65,72 @@ pub trait Engine: Clone + Copy + Debug + Send + Sync + Sized + Eq + PartialEq {
 
   /// A type that defines a commitment engine over scalars in the group
   type CE: CommitmentEngineTrait<Self>;
+
+  /// The cofactor of the curve, i.e. the index of the prime-order subgroup
+  /// in which commitments must lie. All currently supported curves have
+  /// cofactor 1.
+  const COFACTOR: u64 = 1;
+
+  /// Deterministically maps `bytes`, e.g. a Fiat-Shamir challenge, to a
+  /// uniformly distributed group element, see [`Group::hash_to_group`]
+  fn hash_to_curve(bytes: &[u8]) -> Self::GE { Self::GE::hash_to_group(bytes) }
+
+  /// Returns a deterministic RNG seeded with `seed`, so that tests and
+  /// benchmarks can reproduce the exact random elements they draw. It is not
+  /// used by the prover or verifier.
+  fn test_rng(seed: u64) -> ChaCha20Rng { ChaCha20Rng::seed_from_u64(seed) }
+
+  /// Returns the modulus of the scalar field as big-endian bytes, without
+  /// leading zeros
+  fn scalar_modulus_bytes() -> Vec<u8> { modulus_bytes::<Self::Scalar>() }
+
+  /// Returns the modulus of the base field as big-endian bytes, without
+  /// leading zeros
+  fn base_modulus_bytes() -> Vec<u8> { modulus_bytes::<Self::Base>() }
+
+  /// Converts a base field element to the scalar field element with the same
+  /// canonical integer value, or returns `None` if that value is not smaller
+  /// than the scalar modulus, rather than silently reducing it
+  fn base_to_scalar(e: Self::Base) -> Option<Self::Scalar> {
+    let bits = e.to_le_bits();
+    let value = le_bits_to_biguint(bits.iter().map(|bit| *bit));
+    let modulus = le_bits_to_biguint(Self::Scalar::char_le_bits().iter().map(|bit| *bit));
+    if value >= modulus {
+      return None;
+    }
+    Some(bits.iter().rev().fold(Self::Scalar::ZERO, |acc, bit| {
+      let acc = acc.double();
+      if *bit {
+        acc + Self::Scalar::ONE
+      } else {
+        acc
+      }
+    }))
+  }
+
+  /// Returns whether `Self` and `E2` form a curve cycle, i.e. the base field
+  /// of each is the scalar field of the other. This is the runtime counterpart
+  /// of the bound [`CurveCycleEquipped::Secondary`] places at compile time,
+  /// which rejects non-cycle pairings in [`crate::PublicParams::setup`].
+  fn is_cycle_with<E2: Engine>() -> bool {
+    TypeId::of::<Self::Base>() == TypeId::of::<E2::Scalar>()
+      && TypeId::of::<Self::Scalar>() == TypeId::of::<E2::Base>()
+  }
+}
+
+/// Reads little-endian `bits` as an unsigned integer
+fn le_bits_to_biguint(bits: impl Iterator<Item = bool>) -> BigUint {
+  let bits = bits.collect::<Vec<_>>();
+  let le_bytes = bits
+    .chunks(8)
+    .map(|byte| byte.iter().enumerate().fold(0u8, |acc, (i, bit)| acc | (u8::from(*bit) << i)))
+    .collect::<Vec<_>>();
+  BigUint::from_bytes_le(&le_bytes)
+}
+
+/// The characteristic of `F` as big-endian bytes, without leading zeros
+fn modulus_bytes<F: PrimeFieldBits>() -> Vec<u8> {
+  le_bits_to_biguint(F::char_le_bits().iter().map(|bit| *bit)).to_bytes_be()
 }
 
 /// This is a convenience trait to pair engines which fields are in a curve
@@ -75,6 +149,53 @@ pub trait AbsorbInROTrait<E: Engine> {
   fn absorb_in_ro(&self, ro: &mut E::RO);
 }
 
+impl<E: Engine, A: AbsorbInROTrait<E>, B: AbsorbInROTrait<E>> AbsorbInROTrait<E> for (A, B) {
+  fn absorb_in_ro(&self, ro: &mut E::RO) {
+    self.0.absorb_in_ro(ro);
+    self.1.absorb_in_ro(ro);
+  }
+}
+
+impl<E: Engine, A: AbsorbInROTrait<E>, B: AbsorbInROTrait<E>, C: AbsorbInROTrait<E>>
+  AbsorbInROTrait<E> for (A, B, C)
+{
+  fn absorb_in_ro(&self, ro: &mut E::RO) {
+    self.0.absorb_in_ro(ro);
+    self.1.absorb_in_ro(ro);
+    self.2.absorb_in_ro(ro);
+  }
+}
+
+/// Absorbs the length of the slice first, followed by each element, so that
+/// `[a, b]` and `[a], [b]` are absorbed differently. Callers must account for
+/// the extra element in the RO's `num_absorbs`.
+impl<E: Engine, T: AbsorbInROTrait<E>> AbsorbInROTrait<E> for &[T] {
+  fn absorb_in_ro(&self, ro: &mut E::RO) {
+    ro.absorb(E::Base::from(self.len() as u64));
+    for t in self.iter() {
+      t.absorb_in_ro(ro);
+    }
+  }
+}
+
+/// The index `i` of a folding step.
+///
+/// Its canonical encoding is the field element `i`, see
+/// [`StepIndex::to_field`]. The native verifier absorbs it through
+/// [`AbsorbInROTrait`] and the prover allocates the same element in the
+/// augmented circuits, so both sides hash `i` identically.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StepIndex(pub u64);
+
+impl StepIndex {
+  /// Returns the canonical encoding of the step index
+  pub fn to_field<F: PrimeField>(self) -> F { F::from(self.0) }
+}
+
+impl<E: Engine> AbsorbInROTrait<E> for StepIndex {
+  fn absorb_in_ro(&self, ro: &mut E::RO) { ro.absorb(self.to_field()); }
+}
+
 /// A helper trait that defines the behavior of a hash function that we use as
 /// an RO
 pub trait ROTrait<Base: PrimeField, Scalar> {
@@ -99,8 +220,10 @@ pub trait ROTrait<Base: PrimeField, Scalar> {
   /// Adds a scalar to the internal state
   fn absorb(&mut self, e: Base);
 
-  /// Returns a challenge of `num_bits` by hashing the internal state
-  fn squeeze(&mut self, num_bits: usize) -> Scalar;
+  /// Returns a challenge of `num_bits` by hashing the internal state, or
+  /// [`NovaError::InvalidNumBits`] if `num_bits` exceeds the bit length of
+  /// the field the RO squeezes from
+  fn squeeze(&mut self, num_bits: usize) -> Result<Scalar, NovaError>;
 }
 
 /// A helper trait that defines the behavior of a hash function that we use as
@@ -127,7 +250,8 @@ pub trait ROCircuitTrait<Base: PrimeField> {
   /// Adds a scalar to the internal state
   fn absorb(&mut self, e: &AllocatedNum<Base>);
 
-  /// Returns a challenge of `num_bits` by hashing the internal state
+  /// Returns a challenge of `num_bits` by hashing the internal state, or an
+  /// error if `num_bits` exceeds the bit length of `Base`
   fn squeeze<CS: ConstraintSystem<Base>>(
     &mut self,
     cs: CS,
@@ -180,3 +304,99 @@ impl<G: Group, T: TranscriptReprTrait<G>> TranscriptReprTrait<G> for &[T] {
 
 pub mod evaluation;
 pub mod snark;
+
+#[cfg(test)]
+mod tests {
+  use bellpepper_core::test_cs::TestConstraintSystem;
+  use ff::Field;
+  use rand::rngs::OsRng;
+
+  use super::*;
+  use crate::{
+    constants::NUM_CHALLENGE_BITS,
+    provider::{Bn256EngineKZG, GrumpkinEngine},
+  };
+
+  #[derive(Clone, Copy)]
+  struct Fe<E: Engine>(E::Base);
+
+  impl<E: Engine> AbsorbInROTrait<E> for Fe<E> {
+    fn absorb_in_ro(&self, ro: &mut E::RO) { ro.absorb(self.0); }
+  }
+
+  fn squeeze<E: Engine>(num_absorbs: usize, f: impl FnOnce(&mut E::RO)) -> E::Scalar {
+    let mut ro = E::RO::new(ROConstants::<E>::default(), num_absorbs);
+    f(&mut ro);
+    ro.squeeze(NUM_CHALLENGE_BITS).unwrap()
+  }
+
+  fn test_absorb_tuples_with<E: Engine>() {
+    let [a, b, c] = [(); 3].map(|_| Fe::<E>(E::Base::random(OsRng)));
+
+    let expected = squeeze::<E>(2, |ro| {
+      a.absorb_in_ro(ro);
+      b.absorb_in_ro(ro);
+    });
+    assert_eq!(expected, squeeze::<E>(2, |ro| AbsorbInROTrait::<E>::absorb_in_ro(&(a, b), ro)));
+
+    let expected = squeeze::<E>(3, |ro| {
+      a.absorb_in_ro(ro);
+      b.absorb_in_ro(ro);
+      c.absorb_in_ro(ro);
+    });
+    assert_eq!(expected, squeeze::<E>(3, |ro| AbsorbInROTrait::<E>::absorb_in_ro(&(a, b, c), ro)));
+  }
+
+  #[test]
+  fn test_absorb_tuples() {
+    test_absorb_tuples_with::<Bn256EngineKZG>();
+    test_absorb_tuples_with::<GrumpkinEngine>();
+  }
+
+  fn test_absorb_slices_with<E: Engine>() {
+    let [a, b] = [(); 2].map(|_| Fe::<E>(E::Base::random(OsRng)));
+
+    let (ab, a_only, b_only, empty): (&[Fe<E>], &[Fe<E>], &[Fe<E>], &[Fe<E>]) =
+      (&[a, b], &[a], &[b], &[]);
+
+    // [a, b] and ([a], [b]) carry the same elements but distinct length prefixes
+    let joined = squeeze::<E>(4, |ro| AbsorbInROTrait::<E>::absorb_in_ro(&(ab, empty), ro));
+    let split = squeeze::<E>(4, |ro| AbsorbInROTrait::<E>::absorb_in_ro(&(a_only, b_only), ro));
+    assert_ne!(joined, split);
+
+    let expected = squeeze::<E>(3, |ro| {
+      ro.absorb(E::Base::from(2));
+      a.absorb_in_ro(ro);
+      b.absorb_in_ro(ro);
+    });
+    assert_eq!(expected, squeeze::<E>(3, |ro| AbsorbInROTrait::<E>::absorb_in_ro(&ab, ro)));
+  }
+
+  #[test]
+  fn test_absorb_slices() {
+    test_absorb_slices_with::<Bn256EngineKZG>();
+    test_absorb_slices_with::<GrumpkinEngine>();
+  }
+
+  fn test_absorb_step_index_with<E: Engine>() {
+    let i = StepIndex(42);
+    let native = squeeze::<E>(1, |ro| AbsorbInROTrait::<E>::absorb_in_ro(&i, ro));
+
+    let mut cs = TestConstraintSystem::<E::Base>::new();
+    let i_gadget = AllocatedNum::alloc_infallible(cs.namespace(|| "i"), || i.to_field());
+    let mut ro_gadget = E::ROCircuit::new(ROConstantsCircuit::<E>::default(), 1);
+    ro_gadget.absorb(&i_gadget);
+    let bits = ro_gadget.squeeze(cs.namespace(|| "squeeze"), NUM_CHALLENGE_BITS).unwrap();
+
+    let native_bits = native.to_le_bits();
+    for (j, bit) in bits.iter().enumerate() {
+      assert_eq!(bit.get_value(), Some(native_bits[j]));
+    }
+  }
+
+  #[test]
+  fn test_absorb_step_index() {
+    test_absorb_step_index_with::<Bn256EngineKZG>();
+    test_absorb_step_index_with::<GrumpkinEngine>();
+  }
+}